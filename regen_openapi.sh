@@ -41,6 +41,35 @@ jq --indent 4 '.components.schemas |= with_entries(
             else .
             end
         end
+    )
+    # Message payloads are kept as raw JSON, and only parsed when accessed.
+    | .components.schemas.MessageOut.properties.payload["x-rust-type"] = "crate::api::RawPayload"
+    | .components.schemas.EndpointMessageOut.properties.payload["x-rust-type"] = "crate::api::RawPayload"
+    # Event type names, channels and tags are checked when they're created.
+    | .components.schemas.MessageIn.properties.eventType["x-rust-type"] = "crate::api::EventTypeName"
+    | .components.schemas.MessageIn.properties.channels["x-rust-type"] = "Vec<crate::api::Channel>"
+    | .components.schemas.MessageIn.properties.tags["x-rust-type"] = "Vec<crate::api::Tag>"
+    | .components.schemas.EventTypeIn.properties.name["x-rust-type"] = "crate::api::EventTypeName"
+    # Timestamps are `Timestamp`s, which convert to and from `chrono` and
+    # `time` types.
+    | .components.schemas[].properties[]? |= (
+        if .format == "date-time" then .["x-rust-type"] = "crate::api::Timestamp" else . end
+    )
+    | .paths[][].parameters[]? |= (
+        if .schema.format == "date-time" then .["x-rust-type"] = "crate::api::Timestamp" else . end
+    )
+    # Large JSON fields of response models are boxed, so they don't inflate
+    # every model (and every page of a list) they're part of. Only for fields
+    # which aren't nullable.
+    | .components.schemas.EventTypeOut.properties.schemas["x-rust-boxed"] = true
+    | .components.schemas.EventTypeFromOpenApi.properties.schemas["x-rust-boxed"] = true
+    | .components.schemas.BackgroundTaskOut.properties.data["x-rust-boxed"] = true
+    # Request body models get builders.
+    | .components.schemas |= with_entries(
+        if (.key | endswith("In") or endswith("Patch")) and (.value | has("properties"))
+        then .value["x-rust-builder"] = true
+        else .
+        end
     )' \
     < openapi.json \
     > rust/openapi.json
@@ -1,27 +1,61 @@
-use std::sync::Arc;
+#[cfg(any(
+    feature = "api-message",
+    all(feature = "api-endpoint", feature = "api-event-type")
+))]
+use std::collections::HashSet;
+use std::{collections::HashMap, sync::Arc};
 
+#[cfg(any(
+    feature = "api-endpoint",
+    feature = "api-message",
+    all(feature = "api-statistics", feature = "api-background-tasks")
+))]
+use futures_util::StreamExt as _;
+#[cfg(any(feature = "api-endpoint", feature = "api-message"))]
+use futures_util::TryStreamExt as _;
 use hyper_util::{client::legacy::Client as HyperClient, rt::TokioExecutor};
 
+#[cfg(feature = "api-application")]
+use crate::apis::application_api;
+#[cfg(feature = "api-authentication")]
+use crate::apis::authentication_api;
+#[cfg(feature = "api-background-tasks")]
+use crate::apis::background_tasks_api;
+#[cfg(feature = "api-endpoint")]
+use crate::apis::endpoint_api;
+#[cfg(feature = "api-environment")]
+use crate::apis::environment_api;
+#[cfg(feature = "api-event-type")]
+use crate::apis::event_type_api;
+#[cfg(feature = "api-inbound")]
+use crate::apis::inbound_api;
+#[cfg(feature = "api-integration")]
+use crate::apis::integration_api;
+#[cfg(feature = "api-message")]
+use crate::apis::message_api;
+#[cfg(feature = "api-message-attempt")]
+use crate::apis::message_attempt_api;
+#[cfg(feature = "api-statistics")]
+use crate::apis::statistics_api;
+#[cfg(feature = "api-stream")]
+use crate::apis::stream_api;
+#[cfg(feature = "api-stream")]
+use crate::apis::stream_event_types_api;
+#[cfg(feature = "api-transformation-template")]
+use crate::apis::transformation_template_api;
+// unclear where 'operational_' got dropped in the codegen, but it's a private module and
+// the types inside it use the 'Operational' prefix so it doesn't really matter
+#[cfg(feature = "api-webhook-endpoint")]
+use crate::apis::webhook_endpoint_api as operational_webhook_endpoint_api;
+// Which of these are used depends on the enabled API groups
+#[allow(unused_imports)]
 use crate::{
-    apis::{
-        application_api,
-        authentication_api,
-        background_tasks_api,
-        endpoint_api,
-        event_type_api,
-        integration_api,
-        message_api,
-        message_attempt_api,
-        statistics_api,
-        // unclear where 'operational_' got dropped in the codegen, but it's a private module and
-        // the types inside it use the 'Operational' prefix so it doesn't really matter
-        webhook_endpoint_api as operational_webhook_endpoint_api,
-    },
-    error::Result,
-    Configuration,
+    error::{Error, Result},
+    request::Request,
 };
+use crate::{Configuration, SharedConfiguration};
 
-#[cfg(feature = "svix_beta")]
+#[cfg(all(feature = "svix_beta", feature = "api-message"))]
 pub use crate::apis::message_api::{
     V1PeriodMessagePeriodCreateError, V1PeriodMessagePeriodCreateParams,
     V1PeriodMessagePeriodEventsParams, V1PeriodMessagePeriodEventsSubscriptionError,
@@ -29,9 +63,63 @@ pub use crate::apis::message_api::{
 };
 pub use crate::models::*;
 
+#[cfg(feature = "api-event-type")]
+mod asyncapi;
+#[cfg(feature = "api-message")]
+mod batch;
+pub mod cache;
+#[cfg(feature = "api-endpoint")]
+mod endpoint_url;
+pub mod hooks;
+#[cfg(feature = "schemars")]
+mod json_schema;
+mod names;
+mod operational_webhook;
+#[cfg(feature = "api-poller")]
+mod poller;
+mod raw;
+#[cfg(feature = "api-endpoint")]
+mod sink;
+mod timestamp;
+pub(crate) mod traits;
+
+#[cfg(feature = "api-message")]
+pub use self::batch::Batch;
+pub use self::cache::{CacheStore, CachedResponse, MemoryCacheStore};
+pub use self::hooks::{
+    AuditEvent, AuditHook, AuditOutcome, RateLimit, RequestEnd, RequestEndHook, RequestStart,
+    RequestStartHook, RequestTimings,
+};
+#[cfg(feature = "schemars")]
+pub use self::json_schema::event_type_schemas;
+pub use self::names::{Channel, EventTypeName, Tag};
+pub use self::operational_webhook::{OperationalWebhookEventType, OPERATIONAL_WEBHOOK_EVENT_TYPES};
+#[cfg(feature = "api-poller")]
+pub use self::poller::{
+    Poller, PollerConsumerPollOptions, PollerPollOptions, PollingConsumer,
+    PollingEndpointConsumerSeekIn, PollingEndpointConsumerSeekOut, PollingEndpointMessageOut,
+    PollingEndpointOut,
+};
+pub use self::raw::{Interner, ListResponse, RawPayload, RawResponse};
+pub use self::timestamp::Timestamp;
+pub use self::traits::{ApplicationApi, EndpointApi, EventTypeApi, MessageApi, MessageAttemptApi};
+
 const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-#[cfg(feature = "svix_beta")]
+/// Adds the query parameter `name` to `req` if `value` is set.
+#[cfg(any(
+    feature = "api-event-type",
+    feature = "api-message",
+    feature = "api-message-attempt"
+))]
+fn with_query(req: Request, name: &str, value: Option<impl ToString>) -> Request {
+    match value {
+        Some(value) => req.with_query_param(name.to_owned(), value.to_string()),
+        None => req,
+    }
+}
+
+#[cfg(all(feature = "svix_beta", feature = "api-stream"))]
 pub mod raw_stream_api {
     pub use crate::{
         apis::stream_api::*,
@@ -42,6 +130,11 @@ pub mod raw_stream_api {
 }
 
 pub struct SvixOptions {
+    /// Record how long the stages of every request took, from resolving the
+    /// server's name to receiving the response body, in
+    /// [`RequestEnd::timings`] and as `tracing` debug events.
+    ///
+    /// Default: `false`.
     pub debug: bool,
     pub server_url: Option<String>,
     /// Timeout for HTTP requests.
@@ -52,6 +145,44 @@ pub struct SvixOptions {
     ///
     /// Default: 15 seconds.
     pub timeout: Option<std::time::Duration>,
+    /// Where to cache responses with cache validators, which are then only
+    /// downloaded again if they changed. See [`cache`](self::cache).
+    ///
+    /// Default: `None`, only event type listings are cached.
+    pub cache: Option<Arc<dyn CacheStore>>,
+    /// Called before every request is sent. See [`hooks`](self::hooks).
+    pub on_request_start: Option<RequestStartHook>,
+    /// Called after every request finished, with its status and how long it
+    /// took. See [`hooks`](self::hooks).
+    pub on_request_end: Option<RequestEndHook>,
+    /// Called after every request which changes something, for an audit
+    /// trail. See [`hooks`](self::hooks).
+    pub on_audit: Option<AuditHook>,
+    /// Requests which take longer than this are logged as a warning, with
+    /// `tracing`, along with the request ID the API assigned to them.
+    ///
+    /// Default: `None`, requests are never logged.
+    pub slow_request_threshold: Option<std::time::Duration>,
+    /// Allow endpoints with `http` URLs for any host, instead of only for
+    /// `localhost`. Endpoint URLs are checked before they're sent, and
+    /// otherwise have to use `https`.
+    ///
+    /// For self-hosted servers delivering to a private network.
+    ///
+    /// Default: `false`.
+    pub allow_http_endpoints: bool,
+    /// Append every request and its response to this file, one JSON object
+    /// per line, for attaching to support tickets. The `authorization`
+    /// header, signatures, and secrets, tokens and App Portal login links
+    /// in bodies are redacted.
+    ///
+    /// Default: the path in the `SVIX_DEBUG_DUMP` environment variable, if
+    /// it's set, else `None`.
+    pub debug_dump: Option<std::path::PathBuf>,
+    /// The meter request durations are recorded with, instead of one from
+    /// the global meter provider.
+    #[cfg(feature = "opentelemetry")]
+    pub meter: Option<opentelemetry::metrics::Meter>,
 }
 
 impl Default for SvixOptions {
@@ -60,6 +191,15 @@ impl Default for SvixOptions {
             debug: false,
             server_url: None,
             timeout: Some(std::time::Duration::from_secs(15)),
+            cache: None,
+            on_request_start: None,
+            on_request_end: None,
+            on_audit: None,
+            slow_request_threshold: None,
+            allow_http_endpoints: false,
+            debug_dump: None,
+            #[cfg(feature = "opentelemetry")]
+            meter: None,
         }
     }
 }
@@ -68,7 +208,7 @@ impl Default for SvixOptions {
 #[derive(Clone)]
 pub struct Svix {
     cfg: Arc<Configuration>,
-    server_url: Option<String>,
+    server_url: Option<Arc<str>>,
 }
 
 impl Svix {
@@ -76,16 +216,31 @@ impl Svix {
         let options = options.unwrap_or_default();
 
         let cfg = Arc::new(Configuration {
-            user_agent: Some(format!("svix-libs/{CRATE_VERSION}/rust")),
-            client: HyperClient::builder(TokioExecutor::new()).build(crate::default_connector()),
-            timeout: options.timeout,
             // These fields will be set by `with_token` below
             base_path: String::new(),
             bearer_access_token: None,
+            shared: Arc::new(SharedConfiguration {
+                user_agent: Some(format!("svix-libs/{CRATE_VERSION}/rust")),
+                client: HyperClient::builder(TokioExecutor::new())
+                    .build(crate::default_connector()),
+                timeout: options.timeout,
+                cache: options.cache,
+                on_request_start: options.on_request_start,
+                on_request_end: options.on_request_end,
+                on_audit: options.on_audit,
+                slow_request_threshold: options.slow_request_threshold,
+                allow_http_endpoints: options.allow_http_endpoints,
+                debug: options.debug,
+                wire_dump: crate::dump::WireDump::from_options(options.debug_dump),
+                #[cfg(feature = "opentelemetry")]
+                request_duration: crate::otel::request_duration(options.meter),
+            }),
+            response_cache: Default::default(),
+            rate_limit: Default::default(),
         });
         let svix = Self {
             cfg,
-            server_url: options.server_url,
+            server_url: options.server_url.map(Into::into),
         };
         svix.with_token(token)
     }
@@ -95,23 +250,26 @@ impl Svix {
     /// an existing `Svix` instance.
     ///
     /// This can be used to change the token without incurring
-    /// the cost of TLS initialization.
+    /// the cost of TLS initialization. Only the token and the
+    /// server URL derived from it are copied, everything else
+    /// is shared with `self`.
     pub fn with_token(&self, token: String) -> Self {
-        let base_path = self.server_url.clone().unwrap_or_else(|| {
-            match token.split('.').last() {
+        let base_path = match &self.server_url {
+            Some(server_url) => server_url.to_string(),
+            None => match token.split('.').next_back() {
                 Some("us") => "https://api.us.svix.com",
                 Some("eu") => "https://api.eu.svix.com",
                 Some("in") => "https://api.in.svix.com",
                 _ => "https://api.svix.com",
             }
-            .to_string()
-        });
+            .to_owned(),
+        };
         let cfg = Arc::new(Configuration {
             base_path,
-            user_agent: self.cfg.user_agent.clone(),
             bearer_access_token: Some(token),
-            client: self.cfg.client.clone(),
-            timeout: self.cfg.timeout,
+            shared: self.cfg.shared.clone(),
+            response_cache: Default::default(),
+            rate_limit: Default::default(),
         });
 
         Self {
@@ -120,46 +278,97 @@ impl Svix {
         }
     }
 
+    #[cfg(feature = "api-authentication")]
     pub fn authentication(&self) -> Authentication<'_> {
         Authentication::new(&self.cfg)
     }
 
+    #[cfg(feature = "api-application")]
     pub fn application(&self) -> Application<'_> {
         Application::new(&self.cfg)
     }
 
+    #[cfg(feature = "api-background-tasks")]
     pub fn background_task(&self) -> BackgroundTask<'_> {
         BackgroundTask::new(&self.cfg)
     }
 
+    #[cfg(feature = "api-message")]
+    pub fn batch(&self) -> Batch<'_> {
+        Batch::new(&self.cfg)
+    }
+
+    #[cfg(feature = "api-endpoint")]
     pub fn endpoint(&self) -> Endpoint<'_> {
         Endpoint::new(&self.cfg)
     }
 
+    #[cfg(feature = "api-inbound")]
+    pub fn inbound(&self) -> Inbound<'_> {
+        Inbound::new(&self.cfg)
+    }
+
+    #[cfg(feature = "api-environment")]
+    pub fn environment(&self) -> Environment<'_> {
+        Environment::new(&self.cfg)
+    }
+
+    #[cfg(feature = "api-integration")]
     pub fn integration(&self) -> Integration<'_> {
         Integration::new(&self.cfg)
     }
 
+    #[cfg(feature = "api-event-type")]
     pub fn event_type(&self) -> EventType<'_> {
         EventType::new(&self.cfg)
     }
 
+    #[cfg(feature = "api-message")]
     pub fn message(&self) -> Message<'_> {
         Message::new(&self.cfg)
     }
 
+    #[cfg(feature = "api-message-attempt")]
     pub fn message_attempt(&self) -> MessageAttempt<'_> {
         MessageAttempt::new(&self.cfg)
     }
 
+    #[cfg(feature = "api-webhook-endpoint")]
     pub fn operational_webhook_endpoint(&self) -> OperationalWebhookEndpoint<'_> {
         OperationalWebhookEndpoint::new(&self.cfg)
     }
 
+    #[cfg(feature = "api-poller")]
+    pub fn poller(&self) -> Poller<'_> {
+        Poller::new(&self.cfg)
+    }
+
+    #[cfg(feature = "api-endpoint")]
+    pub fn sink(&self) -> Sink<'_> {
+        Sink::new(&self.cfg)
+    }
+
+    #[cfg(feature = "api-statistics")]
     pub fn statistics(&self) -> Statistics<'_> {
         Statistics::new(&self.cfg)
     }
 
+    #[cfg(feature = "api-stream")]
+    pub fn stream(&self) -> Stream<'_> {
+        Stream::new(&self.cfg)
+    }
+
+    #[cfg(feature = "api-transformation-template")]
+    pub fn transformation_template(&self) -> TransformationTemplate<'_> {
+        TransformationTemplate::new(&self.cfg)
+    }
+
+    /// The rate limit quota the API reported with the last response to this
+    /// client, if any.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        *self.cfg.rate_limit.lock().unwrap()
+    }
+
     #[cfg(feature = "svix_beta")]
     pub fn cfg(&self) -> &Configuration {
         &self.cfg
@@ -171,15 +380,52 @@ pub struct PostOptions {
     pub idempotency_key: Option<String>,
 }
 
+/// Options for [`Authentication::app_portal_access`].
+#[derive(Default)]
+pub struct AppPortalAccessOptions {
+    /// How long the App Portal session is valid for. Sub-second precision is
+    /// ignored.
+    pub expiry: Option<std::time::Duration>,
+    /// Feature flags enabled for this session, gating event types that
+    /// require them.
+    pub feature_flags: Vec<String>,
+    /// Only allow viewing, not modifying, the application's endpoints.
+    pub read_only: bool,
+    /// Creates the application with these details if it doesn't exist yet.
+    pub application: Option<ApplicationIn>,
+}
+
+impl From<AppPortalAccessOptions> for AppPortalAccessIn {
+    fn from(options: AppPortalAccessOptions) -> Self {
+        let AppPortalAccessOptions {
+            expiry,
+            feature_flags,
+            read_only,
+            application,
+        } = options;
+        Self {
+            application: application.map(Box::new),
+            expiry: expiry.map(|expiry| expiry.as_secs().try_into().unwrap_or(i32::MAX)),
+            feature_flags: (!feature_flags.is_empty()).then_some(feature_flags),
+            read_only: read_only.then_some(true),
+            #[cfg(feature = "unknown-fields")]
+            extra: Default::default(),
+        }
+    }
+}
+
+#[cfg(feature = "api-authentication")]
 pub struct Authentication<'a> {
     cfg: &'a Configuration,
 }
 
+#[cfg(feature = "api-authentication")]
 impl<'a> Authentication<'a> {
     fn new(cfg: &'a Configuration) -> Self {
         Self { cfg }
     }
 
+    #[deprecated = "Use app_portal_access or app_portal_magic_link instead"]
     pub async fn dashboard_access(
         &self,
         app_id: String,
@@ -196,10 +442,14 @@ impl<'a> Authentication<'a> {
         .await
     }
 
+    /// Returns a link to the App Portal for the application `app_id`.
+    ///
+    /// Accepts either an [`AppPortalAccessIn`] or the typed
+    /// [`AppPortalAccessOptions`].
     pub async fn app_portal_access(
         &self,
         app_id: String,
-        app_portal_access_in: AppPortalAccessIn,
+        app_portal_access_in: impl Into<AppPortalAccessIn>,
         options: Option<PostOptions>,
     ) -> Result<AppPortalAccessOut> {
         let options = options.unwrap_or_default();
@@ -207,13 +457,47 @@ impl<'a> Authentication<'a> {
             self.cfg,
             authentication_api::V1PeriodAuthenticationPeriodAppPortalAccessParams {
                 app_id,
-                app_portal_access_in,
+                app_portal_access_in: app_portal_access_in.into(),
                 idempotency_key: options.idempotency_key,
             },
         )
         .await
     }
 
+    /// Returns a one-time App Portal link for the application `app_id`.
+    ///
+    /// The link logs the user in when it's first opened, and the resulting
+    /// session is valid for `expiry` (by default 7 days).
+    pub async fn app_portal_magic_link(
+        &self,
+        app_id: String,
+        expiry: Option<std::time::Duration>,
+    ) -> Result<String> {
+        let options = AppPortalAccessOptions {
+            expiry,
+            ..Default::default()
+        };
+        Ok(self.app_portal_access(app_id, options, None).await?.url)
+    }
+
+    /// Exchanges the one-time token of an App Portal link for a session
+    /// token.
+    pub async fn exchange_one_time_token(
+        &self,
+        one_time_token_in: OneTimeTokenIn,
+        options: Option<PostOptions>,
+    ) -> Result<OneTimeTokenOut> {
+        let PostOptions { idempotency_key } = options.unwrap_or_default();
+        authentication_api::v1_period_authentication_period_exchange_one_time_token(
+            self.cfg,
+            authentication_api::V1PeriodAuthenticationPeriodExchangeOneTimeTokenParams {
+                idempotency_key,
+                one_time_token_in,
+            },
+        )
+        .await
+    }
+
     pub async fn logout(&self, options: Option<PostOptions>) -> Result<()> {
         let PostOptions { idempotency_key } = options.unwrap_or_default();
         authentication_api::v1_period_authentication_period_logout(
@@ -237,10 +521,12 @@ pub struct ApplicationListOptions {
     pub order: Option<Ordering>,
 }
 
+#[cfg(feature = "api-application")]
 pub struct Application<'a> {
     cfg: &'a Configuration,
 }
 
+#[cfg(feature = "api-application")]
 impl<'a> Application<'a> {
     fn new(cfg: &'a Configuration) -> Self {
         Self { cfg }
@@ -340,6 +626,61 @@ impl<'a> Application<'a> {
         .await
     }
 
+    /// Summarizes the delivery attempts to all of the application's endpoints
+    /// in the given time window.
+    #[cfg(feature = "api-endpoint")]
+    pub async fn delivery_health(
+        &self,
+        app_id: String,
+        window: Option<EndpointStatsOptions>,
+    ) -> Result<DeliveryHealth> {
+        let endpoints = Endpoint::new(self.cfg)
+            .get_stats_by_endpoint(app_id, window)
+            .await?;
+        let total = endpoints
+            .values()
+            .fold(EndpointStats::new(0, 0, 0, 0), |total, stats| {
+                EndpointStats {
+                    fail: total.fail + stats.fail,
+                    pending: total.pending + stats.pending,
+                    sending: total.sending + stats.sending,
+                    success: total.success + stats.success,
+                    #[cfg(feature = "unknown-fields")]
+                    extra: Default::default(),
+                }
+            });
+        Ok(DeliveryHealth { total, endpoints })
+    }
+
+    /// Limits delivery to the application's endpoints to `per_second`
+    /// messages per second.
+    pub async fn set_rate_limit(&self, app_id: String, per_second: i32) -> Result<ApplicationOut> {
+        self.patch_rate_limit(app_id, Some(per_second)).await
+    }
+
+    /// Removes the application's rate limit.
+    pub async fn clear_rate_limit(&self, app_id: String) -> Result<ApplicationOut> {
+        self.patch_rate_limit(app_id, None).await
+    }
+
+    /// Returns the application's rate limit in messages per second, or `None`
+    /// if it isn't limited.
+    pub async fn get_rate_limit(&self, app_id: String) -> Result<Option<i32>> {
+        Ok(self.get(app_id).await?.rate_limit)
+    }
+
+    async fn patch_rate_limit(
+        &self,
+        app_id: String,
+        rate_limit: Option<i32>,
+    ) -> Result<ApplicationOut> {
+        let application_patch = ApplicationPatch {
+            rate_limit: Some(rate_limit),
+            ..ApplicationPatch::new()
+        };
+        self.patch(app_id, application_patch, None).await
+    }
+
     pub async fn delete(&self, app_id: String) -> Result<()> {
         application_api::v1_period_application_period_delete(
             self.cfg,
@@ -349,23 +690,84 @@ impl<'a> Application<'a> {
     }
 }
 
+/// Delivery attempt counts of an application, see
+/// [`Application::delivery_health`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeliveryHealth {
+    /// The attempt counts of all endpoints combined.
+    pub total: EndpointStats,
+    /// The attempt counts of each endpoint, keyed by endpoint id.
+    pub endpoints: HashMap<String, EndpointStats>,
+}
+
+impl DeliveryHealth {
+    /// The share of finished attempts that succeeded, or `None` if there
+    /// weren't any.
+    pub fn success_ratio(&self) -> Option<f64> {
+        success_ratio(&self.total)
+    }
+
+    /// Like [`success_ratio`](Self::success_ratio), for a single endpoint.
+    pub fn endpoint_success_ratio(&self, endpoint_id: &str) -> Option<f64> {
+        success_ratio(self.endpoints.get(endpoint_id)?)
+    }
+}
+
+fn success_ratio(stats: &EndpointStats) -> Option<f64> {
+    let finished = stats.success + stats.fail;
+    (finished > 0).then(|| stats.success as f64 / finished as f64)
+}
+
+/// An endpoint created by [`Endpoint::clone_to`].
+#[cfg(feature = "api-endpoint")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClonedEndpoint {
+    /// The new endpoint.
+    pub endpoint: EndpointOut,
+    /// Names of the source endpoint's sensitive headers, which weren't
+    /// copied because the API doesn't return their values.
+    pub sensitive_headers: Vec<String>,
+}
+
 #[derive(Default)]
 pub struct EndpointListOptions {
     pub iterator: Option<String>,
     pub limit: Option<i32>,
     pub order: Option<Ordering>,
+    /// Only return endpoints that are (or aren't) disabled.
+    ///
+    /// The API can't filter on this, so it's applied to each page after it
+    /// was fetched. Pages may therefore contain fewer than `limit` endpoints.
+    pub disabled: Option<bool>,
+    /// Only return the endpoint with this UID, if there is one.
+    ///
+    /// The endpoint is looked up directly instead of listing all endpoints,
+    /// and the other options besides `disabled` are ignored.
+    pub uid: Option<String>,
+}
+
+/// Which messages [`Endpoint::bulk_resend`] resends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BulkResendFilter {
+    /// Messages whose delivery to the endpoint failed.
+    Failed,
+    /// Messages that were never sent to the endpoint, e.g. because it was
+    /// disabled or didn't exist yet.
+    Missing,
 }
 
+#[cfg(feature = "api-endpoint")]
 pub struct Endpoint<'a> {
     cfg: &'a Configuration,
 }
 
 #[derive(Default)]
 pub struct EndpointStatsOptions {
-    pub since: Option<String>,
-    pub until: Option<String>,
+    pub since: Option<Timestamp>,
+    pub until: Option<Timestamp>,
 }
 
+#[cfg(feature = "api-endpoint")]
 impl<'a> Endpoint<'a> {
     fn new(cfg: &'a Configuration) -> Self {
         Self { cfg }
@@ -380,17 +782,42 @@ impl<'a> Endpoint<'a> {
             iterator,
             limit,
             order,
+            disabled,
+            uid,
         } = options.unwrap_or_default();
-        endpoint_api::v1_period_endpoint_period_list(
-            self.cfg,
-            endpoint_api::V1PeriodEndpointPeriodListParams {
-                app_id,
-                order,
-                iterator,
-                limit,
-            },
-        )
-        .await
+        let mut list = match uid {
+            Some(uid) => self.list_by_uid(app_id, uid).await?,
+            None => {
+                endpoint_api::v1_period_endpoint_period_list(
+                    self.cfg,
+                    endpoint_api::V1PeriodEndpointPeriodListParams {
+                        app_id,
+                        order,
+                        iterator,
+                        limit,
+                    },
+                )
+                .await?
+            }
+        };
+
+        if let Some(disabled) = disabled {
+            list.data
+                .retain(|endpoint| endpoint.disabled.unwrap_or(false) == disabled);
+        }
+        Ok(list)
+    }
+
+    async fn list_by_uid(&self, app_id: String, uid: String) -> Result<ListResponseEndpointOut> {
+        // The endpoint routes accept UIDs in place of IDs, but also IDs, so
+        // check that it's actually the endpoint's UID that matched.
+        let data = match self.get(app_id, uid.clone()).await {
+            Ok(endpoint) if endpoint.uid.as_ref() == Some(&uid) => vec![endpoint],
+            Ok(_) => vec![],
+            Err(Error::Http(e)) if e.status == http02::StatusCode::NOT_FOUND => vec![],
+            Err(e) => return Err(e),
+        };
+        Ok(ListResponseEndpointOut::new(data, true, None))
     }
 
     pub async fn create(
@@ -399,6 +826,7 @@ impl<'a> Endpoint<'a> {
         endpoint_in: EndpointIn,
         options: Option<PostOptions>,
     ) -> Result<EndpointOut> {
+        self.check_url(&endpoint_in.url)?;
         let PostOptions { idempotency_key } = options.unwrap_or_default();
         endpoint_api::v1_period_endpoint_period_create(
             self.cfg,
@@ -429,6 +857,7 @@ impl<'a> Endpoint<'a> {
         endpoint_update: EndpointUpdate,
         _: Option<PostOptions>,
     ) -> Result<EndpointOut> {
+        self.check_url(&endpoint_update.url)?;
         endpoint_api::v1_period_endpoint_period_update(
             self.cfg,
             endpoint_api::V1PeriodEndpointPeriodUpdateParams {
@@ -447,6 +876,9 @@ impl<'a> Endpoint<'a> {
         endpoint_patch: EndpointPatch,
         _: Option<PostOptions>,
     ) -> Result<EndpointOut> {
+        if let Some(url) = &endpoint_patch.url {
+            self.check_url(url)?;
+        }
         endpoint_api::v1_period_endpoint_period_patch(
             self.cfg,
             endpoint_api::V1PeriodEndpointPeriodPatchParams {
@@ -458,6 +890,74 @@ impl<'a> Endpoint<'a> {
         .await
     }
 
+    /// Checks an endpoint URL before it's sent, see
+    /// [`SvixOptions::allow_http_endpoints`].
+    fn check_url(&self, url: &str) -> Result<()> {
+        endpoint_url::parse_endpoint_url(url, self.cfg.shared.allow_http_endpoints).map(drop)
+    }
+
+    /// Pauses delivery to the endpoint.
+    pub async fn disable(&self, app_id: String, endpoint_id: String) -> Result<EndpointOut> {
+        self.set_disabled(app_id, endpoint_id, true).await
+    }
+
+    /// Resumes delivery to a disabled endpoint.
+    pub async fn enable(&self, app_id: String, endpoint_id: String) -> Result<EndpointOut> {
+        self.set_disabled(app_id, endpoint_id, false).await
+    }
+
+    async fn set_disabled(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        disabled: bool,
+    ) -> Result<EndpointOut> {
+        let endpoint_patch = EndpointPatch {
+            disabled: Some(disabled),
+            ..EndpointPatch::new()
+        };
+        self.patch(app_id, endpoint_id, endpoint_patch, None).await
+    }
+
+    /// Limits delivery to the endpoint to `per_second` messages per second.
+    pub async fn set_rate_limit(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        per_second: i32,
+    ) -> Result<EndpointOut> {
+        self.patch_rate_limit(app_id, endpoint_id, Some(per_second))
+            .await
+    }
+
+    /// Removes the endpoint's rate limit.
+    pub async fn clear_rate_limit(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+    ) -> Result<EndpointOut> {
+        self.patch_rate_limit(app_id, endpoint_id, None).await
+    }
+
+    /// Returns the endpoint's rate limit in messages per second, or `None` if
+    /// it isn't limited.
+    pub async fn get_rate_limit(&self, app_id: String, endpoint_id: String) -> Result<Option<i32>> {
+        Ok(self.get(app_id, endpoint_id).await?.rate_limit)
+    }
+
+    async fn patch_rate_limit(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        rate_limit: Option<i32>,
+    ) -> Result<EndpointOut> {
+        let endpoint_patch = EndpointPatch {
+            rate_limit: Some(rate_limit),
+            ..EndpointPatch::new()
+        };
+        self.patch(app_id, endpoint_id, endpoint_patch, None).await
+    }
+
     pub async fn delete(&self, app_id: String, endpoint_id: String) -> Result<()> {
         endpoint_api::v1_period_endpoint_period_delete(
             self.cfg,
@@ -469,6 +969,84 @@ impl<'a> Endpoint<'a> {
         .await
     }
 
+    /// Creates a copy of the endpoint that sends to `new_url`.
+    ///
+    /// The copy has the same description, metadata, event type filter,
+    /// channels, rate limit, headers and transformation, but a new secret.
+    /// It's created disabled and only enabled (if the source is) once its
+    /// headers and transformation are set, and it's deleted again if setting
+    /// them fails.
+    ///
+    /// The API doesn't return the values of sensitive headers, so those
+    /// aren't copied. Their names are returned in
+    /// [`ClonedEndpoint::sensitive_headers`], to be set on the copy again.
+    pub async fn clone_to(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        new_url: String,
+    ) -> Result<ClonedEndpoint> {
+        let (endpoint, headers, transformation) = futures_util::future::try_join3(
+            self.get(app_id.clone(), endpoint_id.clone()),
+            self.get_headers(app_id.clone(), endpoint_id.clone()),
+            self.transformation_get(app_id.clone(), endpoint_id),
+        )
+        .await?;
+
+        let endpoint_in = EndpointIn {
+            channels: endpoint.channels,
+            description: Some(endpoint.description),
+            disabled: Some(true),
+            filter_types: endpoint.filter_types,
+            metadata: Some(endpoint.metadata),
+            rate_limit: endpoint.rate_limit,
+            version: Some(endpoint.version),
+            ..EndpointIn::new(new_url)
+        };
+        let clone = self.create(app_id.clone(), endpoint_in, None).await?;
+
+        let configured = async {
+            if !headers.headers.is_empty() {
+                let endpoint_headers_in = EndpointHeadersIn::new(headers.headers);
+                self.update_headers(app_id.clone(), clone.id.clone(), endpoint_headers_in)
+                    .await?;
+            }
+            if transformation.code.is_some() {
+                let endpoint_transformation_in = EndpointTransformationIn {
+                    code: transformation.code,
+                    enabled: transformation.enabled,
+                    #[cfg(feature = "unknown-fields")]
+                    extra: Default::default(),
+                };
+                self.transformation_partial_update(
+                    app_id.clone(),
+                    clone.id.clone(),
+                    endpoint_transformation_in,
+                )
+                .await?;
+            }
+            match endpoint.disabled {
+                Some(true) => Ok(clone.clone()),
+                _ => self.enable(app_id.clone(), clone.id.clone()).await,
+            }
+        }
+        .await;
+
+        match configured {
+            Ok(endpoint) => Ok(ClonedEndpoint {
+                endpoint,
+                sensitive_headers: headers.sensitive,
+            }),
+            Err(e) => match self.delete(app_id, clone.id.clone()).await {
+                Ok(()) => Err(e),
+                Err(delete_err) => Err(Error::Generic(format!(
+                    "failed to configure endpoint {}: {e}, and failed to delete it: {delete_err}",
+                    clone.id
+                ))),
+            },
+        }
+    }
+
     pub async fn get_secret(
         &self,
         app_id: String,
@@ -502,12 +1080,18 @@ impl<'a> Endpoint<'a> {
         .await
     }
 
+    /// Resends the messages that failed to be delivered to the endpoint
+    /// since the given time.
+    ///
+    /// The returned background task can be tracked with
+    /// [`BackgroundTask::wait`], or use
+    /// [`recover_and_wait`](Self::recover_and_wait).
     pub async fn recover(
         &self,
         app_id: String,
         endpoint_id: String,
         recover_in: RecoverIn,
-    ) -> Result<()> {
+    ) -> Result<RecoverOut> {
         endpoint_api::v1_period_endpoint_period_recover(
             self.cfg,
             endpoint_api::V1PeriodEndpointPeriodRecoverParams {
@@ -517,8 +1101,80 @@ impl<'a> Endpoint<'a> {
                 idempotency_key: None,
             },
         )
-        .await?;
-        Ok(())
+        .await
+    }
+
+    /// Like [`recover`](Self::recover), but waits for the recovery to
+    /// finish.
+    ///
+    /// Returns an error if the recovery task failed, or `timeout` elapsed.
+    #[cfg(feature = "api-background-tasks")]
+    pub async fn recover_and_wait(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        recover_in: RecoverIn,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<BackgroundTaskOut> {
+        let out = self.recover(app_id, endpoint_id, recover_in).await?;
+        BackgroundTask::new(self.cfg)
+            .wait_finished(out.id, timeout)
+            .await
+    }
+
+    /// Resends all messages matching `filter` that were sent to the
+    /// endpoint between `since` and `until` (or now).
+    ///
+    /// This runs on the server, and the returned background task can be
+    /// tracked with [`BackgroundTask::wait`].
+    #[cfg(feature = "api-background-tasks")]
+    pub async fn bulk_resend(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        filter: BulkResendFilter,
+        since: Timestamp,
+        until: Option<Timestamp>,
+        options: Option<PostOptions>,
+    ) -> Result<BackgroundTaskOut> {
+        let PostOptions { idempotency_key } = options.unwrap_or_default();
+        let task_id = match filter {
+            BulkResendFilter::Failed => {
+                endpoint_api::v1_period_endpoint_period_recover(
+                    self.cfg,
+                    endpoint_api::V1PeriodEndpointPeriodRecoverParams {
+                        app_id,
+                        endpoint_id,
+                        recover_in: RecoverIn {
+                            since,
+                            until,
+                            #[cfg(feature = "unknown-fields")]
+                            extra: Default::default(),
+                        },
+                        idempotency_key,
+                    },
+                )
+                .await?
+                .id
+            }
+            BulkResendFilter::Missing => {
+                self.replay_missing(
+                    app_id,
+                    endpoint_id,
+                    ReplayIn {
+                        since,
+                        until,
+                        #[cfg(feature = "unknown-fields")]
+                        extra: Default::default(),
+                    },
+                    Some(PostOptions { idempotency_key }),
+                )
+                .await?
+                .id
+            }
+        };
+
+        BackgroundTask::new(self.cfg).get(task_id).await
     }
 
     pub async fn get_headers(
@@ -589,13 +1245,114 @@ impl<'a> Endpoint<'a> {
         .await
     }
 
+    /// Returns the application's endpoints that still filter on archived
+    /// event types.
+    #[cfg(feature = "api-event-type")]
+    pub async fn list_with_archived_event_types(&self, app_id: String) -> Result<Vec<EndpointOut>> {
+        let event_type = EventType::new(self.cfg);
+        let mut archived = HashSet::new();
+        let mut iterator = None;
+        loop {
+            let event_types = event_type
+                .list(Some(EventTypeListOptions {
+                    iterator,
+                    limit: Some(250),
+                    include_archived: Some(true),
+                    ..Default::default()
+                }))
+                .await?;
+            archived.extend(
+                event_types
+                    .data
+                    .into_iter()
+                    .filter(|event_type| event_type.archived == Some(true))
+                    .map(|event_type| event_type.name),
+            );
+            if event_types.done {
+                break;
+            }
+            iterator = event_types.iterator;
+        }
+
+        let mut endpoints = self.list_all(app_id).await?;
+        endpoints.retain(|endpoint| {
+            endpoint
+                .filter_types
+                .iter()
+                .flatten()
+                .any(|filter_type| archived.contains(filter_type))
+        });
+        Ok(endpoints)
+    }
+
+    /// Fetches all pages of the application's endpoints.
+    async fn list_all(&self, app_id: String) -> Result<Vec<EndpointOut>> {
+        let mut endpoints = Vec::new();
+        let mut iterator = None;
+        loop {
+            let page = self
+                .list(
+                    app_id.clone(),
+                    Some(EndpointListOptions {
+                        iterator,
+                        limit: Some(250),
+                        ..Default::default()
+                    }),
+                )
+                .await?;
+            endpoints.extend(page.data);
+            if page.done {
+                break;
+            }
+            iterator = page.iterator;
+        }
+        Ok(endpoints)
+    }
+
+    /// Returns the attempt counts of every endpoint of the application, keyed
+    /// by endpoint id.
+    ///
+    /// The API only has per-endpoint statistics, so this lists the endpoints
+    /// and fetches their statistics a few at a time.
+    pub async fn get_stats_by_endpoint(
+        &self,
+        app_id: String,
+        options: Option<EndpointStatsOptions>,
+    ) -> Result<HashMap<String, EndpointStats>> {
+        let EndpointStatsOptions { since, until } = options.unwrap_or_default();
+
+        let endpoints = self.list_all(app_id.clone()).await?;
+        futures_util::stream::iter(endpoints)
+            .map(
+                |EndpointOut {
+                     id: endpoint_id, ..
+                 }| {
+                    let options = EndpointStatsOptions { since, until };
+                    async {
+                        let stats = self
+                            .get_stats(app_id.clone(), endpoint_id.clone(), Some(options))
+                            .await?;
+                        Ok((endpoint_id, stats))
+                    }
+                },
+            )
+            .buffer_unordered(8)
+            .try_collect()
+            .await
+    }
+
+    /// Sends the messages the endpoint never received, e.g. because it was
+    /// disabled, since the given time.
+    ///
+    /// The returned background task can be tracked with
+    /// [`BackgroundTask::wait`].
     pub async fn replay_missing(
         &self,
         app_id: String,
         endpoint_id: String,
         replay_in: ReplayIn,
         options: Option<PostOptions>,
-    ) -> Result<()> {
+    ) -> Result<ReplayOut> {
         let PostOptions { idempotency_key } = options.unwrap_or_default();
         endpoint_api::v1_period_endpoint_period_replay(
             self.cfg,
@@ -606,8 +1363,7 @@ impl<'a> Endpoint<'a> {
                 idempotency_key,
             },
         )
-        .await?;
-        Ok(())
+        .await
     }
 
     pub async fn transformation_get(
@@ -643,6 +1399,58 @@ impl<'a> Endpoint<'a> {
         Ok(())
     }
 
+    /// Enables the endpoint's transformation, keeping its code.
+    pub async fn transformation_enable(&self, app_id: String, endpoint_id: String) -> Result<()> {
+        self.set_transformation_enabled(app_id, endpoint_id, true)
+            .await
+    }
+
+    /// Disables the endpoint's transformation, keeping its code.
+    ///
+    /// Messages are sent untransformed until it's enabled again.
+    pub async fn transformation_disable(&self, app_id: String, endpoint_id: String) -> Result<()> {
+        self.set_transformation_enabled(app_id, endpoint_id, false)
+            .await
+    }
+
+    async fn set_transformation_enabled(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        enabled: bool,
+    ) -> Result<()> {
+        let endpoint_transformation_in = EndpointTransformationIn {
+            enabled: Some(enabled),
+            ..EndpointTransformationIn::new()
+        };
+        self.transformation_partial_update(app_id, endpoint_id, endpoint_transformation_in)
+            .await
+    }
+
+    /// Runs the transformation `code` against a sample payload, without
+    /// changing the endpoint's transformation.
+    ///
+    /// Returns the URL, method and payload the message would be sent with.
+    pub async fn transformation_simulate(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        endpoint_transformation_simulate_in: EndpointTransformationSimulateIn,
+        options: Option<PostOptions>,
+    ) -> Result<EndpointTransformationSimulateOut> {
+        let PostOptions { idempotency_key } = options.unwrap_or_default();
+        endpoint_api::v1_period_endpoint_period_transformation_simulate(
+            self.cfg,
+            endpoint_api::V1PeriodEndpointPeriodTransformationSimulateParams {
+                app_id,
+                endpoint_id,
+                idempotency_key,
+                endpoint_transformation_simulate_in,
+            },
+        )
+        .await
+    }
+
     pub async fn send_example(
         &self,
         app_id: String,
@@ -671,10 +1479,12 @@ pub struct IntegrationListOptions {
     pub order: Option<Ordering>,
 }
 
+#[cfg(feature = "api-integration")]
 pub struct Integration<'a> {
     cfg: &'a Configuration,
 }
 
+#[cfg(feature = "api-integration")]
 impl<'a> Integration<'a> {
     fn new(cfg: &'a Configuration) -> Self {
         Self { cfg }
@@ -775,47 +1585,121 @@ impl<'a> Integration<'a> {
     }
 }
 
-#[derive(Default)]
-pub struct EventTypeListOptions {
-    pub iterator: Option<String>,
-    pub limit: Option<i32>,
-    pub with_content: Option<bool>,
-    pub include_archived: Option<bool>,
-}
-
-pub struct EventType<'a> {
+#[cfg(feature = "api-environment")]
+pub struct Environment<'a> {
     cfg: &'a Configuration,
 }
 
-impl<'a> EventType<'a> {
+#[cfg(feature = "api-environment")]
+impl<'a> Environment<'a> {
     fn new(cfg: &'a Configuration) -> Self {
         Self { cfg }
     }
 
-    pub async fn list(
-        &self,
-        options: Option<EventTypeListOptions>,
-    ) -> Result<ListResponseEventTypeOut> {
-        let EventTypeListOptions {
-            iterator,
-            limit,
-            with_content,
-            include_archived,
-        } = options.unwrap_or_default();
-        event_type_api::v1_period_event_type_period_list(
+    /// Exports the environment's settings, event types and connectors.
+    pub async fn export(&self, options: Option<PostOptions>) -> Result<EnvironmentOut> {
+        let PostOptions { idempotency_key } = options.unwrap_or_default();
+        environment_api::v1_period_environment_period_export(
             self.cfg,
-            event_type_api::V1PeriodEventTypePeriodListParams {
-                iterator,
-                limit,
-                with_content,
-                include_archived,
-                order: None,
-            },
+            environment_api::V1PeriodEnvironmentPeriodExportParams { idempotency_key },
         )
         .await
     }
 
-    pub async fn create(
+    /// Imports an exported environment into this one.
+    ///
+    /// Nothing is deleted: the event types and settings in `environment_in`
+    /// are only added or updated.
+    pub async fn import(
+        &self,
+        environment_in: EnvironmentIn,
+        options: Option<PostOptions>,
+    ) -> Result<()> {
+        let PostOptions { idempotency_key } = options.unwrap_or_default();
+        environment_api::v1_period_environment_period_import(
+            self.cfg,
+            environment_api::V1PeriodEnvironmentPeriodImportParams {
+                idempotency_key,
+                environment_in,
+            },
+        )
+        .await
+    }
+}
+
+#[cfg(feature = "api-inbound")]
+pub struct Inbound<'a> {
+    cfg: &'a Configuration,
+}
+
+#[cfg(feature = "api-inbound")]
+impl<'a> Inbound<'a> {
+    fn new(cfg: &'a Configuration) -> Self {
+        Self { cfg }
+    }
+
+    /// Invalidates the application's current inbound URL (if one exists) and
+    /// returns a new one.
+    pub async fn rotate_url(
+        &self,
+        app_id: String,
+        options: Option<PostOptions>,
+    ) -> Result<RotatedUrlOut> {
+        let PostOptions { idempotency_key } = options.unwrap_or_default();
+        inbound_api::v1_period_inbound_period_rotate_url(
+            self.cfg,
+            inbound_api::V1PeriodInboundPeriodRotateUrlParams {
+                app_id,
+                idempotency_key,
+            },
+        )
+        .await
+    }
+}
+
+#[derive(Default)]
+pub struct EventTypeListOptions {
+    pub iterator: Option<String>,
+    pub limit: Option<i32>,
+    pub with_content: Option<bool>,
+    pub include_archived: Option<bool>,
+}
+
+#[cfg(feature = "api-event-type")]
+pub struct EventType<'a> {
+    cfg: &'a Configuration,
+}
+
+#[cfg(feature = "api-event-type")]
+impl<'a> EventType<'a> {
+    fn new(cfg: &'a Configuration) -> Self {
+        Self { cfg }
+    }
+
+    /// Lists the event types.
+    ///
+    /// Responses are cached with their `ETag`, so listing the same page again
+    /// only downloads it if it changed.
+    pub async fn list(
+        &self,
+        options: Option<EventTypeListOptions>,
+    ) -> Result<ListResponseEventTypeOut> {
+        let EventTypeListOptions {
+            iterator,
+            limit,
+            with_content,
+            include_archived,
+        } = options.unwrap_or_default();
+        let req = Request::new(http1::Method::GET, "/api/v1/event-type".to_owned())
+            .with_operation("v1.event-type.list");
+        let req = with_query(req, "limit", limit);
+        let req = with_query(req, "iterator", iterator);
+        let req = with_query(req, "include_archived", include_archived);
+        let req = with_query(req, "with_content", with_content);
+        req.execute_cached(self.cfg).await
+    }
+
+    pub async fn create(
         &self,
         event_type_in: EventTypeIn,
         options: Option<PostOptions>,
@@ -882,6 +1766,38 @@ impl<'a> EventType<'a> {
         .await
     }
 
+    /// Deprecates and archives `event_type_name` in favour of `replacement`.
+    ///
+    /// The replacement must already exist, and is recorded in the deprecated
+    /// event type's description ("Deprecated in favour of `replacement`."),
+    /// so the App Portal points consumers to it. Endpoints that filter on the
+    /// old event type aren't changed; they can be found with
+    /// [`Endpoint::list_with_archived_event_types`] and migrated one by one.
+    pub async fn deprecate(
+        &self,
+        event_type_name: String,
+        replacement: String,
+    ) -> Result<EventTypeOut> {
+        let replacement = self.get(replacement).await?;
+        let event_type = self.get(event_type_name.clone()).await?;
+
+        let note = format!("Deprecated in favour of `{}`.", replacement.name);
+        let description = if event_type.description.is_empty() {
+            note
+        } else if event_type.description.ends_with(&note) {
+            event_type.description
+        } else {
+            format!("{}\n\n{note}", event_type.description)
+        };
+        let event_type_patch = EventTypePatch {
+            archived: Some(true),
+            deprecated: Some(true),
+            description: Some(description),
+            ..EventTypePatch::new()
+        };
+        self.patch(event_type_name, event_type_patch, None).await
+    }
+
     pub async fn import_openapi(
         &self,
         event_type_import_open_api_in: EventTypeImportOpenApiIn,
@@ -897,6 +1813,28 @@ impl<'a> EventType<'a> {
         )
         .await
     }
+
+    /// Creates or updates event types from the messages of an AsyncAPI
+    /// (2.x or 3.x) document in JSON form.
+    ///
+    /// The document is converted to the OpenAPI format expected by
+    /// [`import_openapi`](Self::import_openapi) before being uploaded.
+    pub async fn import_asyncapi(
+        &self,
+        spec: serde_json::Value,
+        dry_run: Option<bool>,
+        options: Option<PostOptions>,
+    ) -> Result<EventTypeImportOpenApiOut> {
+        let event_type_import_open_api_in = EventTypeImportOpenApiIn {
+            dry_run,
+            spec: Some(asyncapi::to_openapi(&spec)?),
+            spec_raw: None,
+            #[cfg(feature = "unknown-fields")]
+            extra: Default::default(),
+        };
+        self.import_openapi(event_type_import_open_api_in, options)
+            .await
+    }
 }
 
 #[derive(Default)]
@@ -904,20 +1842,99 @@ pub struct MessageListOptions {
     pub iterator: Option<String>,
     pub limit: Option<i32>,
     pub event_types: Option<Vec<String>>,
-    // FIXME: make before and after actual dates
-    /// RFC3339 date string
-    pub before: Option<String>,
-    /// RFC3339 date string
-    pub after: Option<String>,
+    pub before: Option<Timestamp>,
+    pub after: Option<Timestamp>,
     pub channel: Option<String>,
     pub with_content: Option<bool>,
     pub tag: Option<String>,
 }
 
+/// Options for [`Message::export_ndjson`].
+#[derive(Default)]
+pub struct MessageExportOptions {
+    pub before: Option<Timestamp>,
+    pub after: Option<Timestamp>,
+    pub event_types: Option<Vec<String>>,
+    /// Include the message payloads. Defaults to `true`.
+    pub with_content: Option<bool>,
+    /// Add the delivery attempts of each message, as an `attempts` array.
+    pub with_attempts: bool,
+    /// Fetch the next page while the current one is written, so the export
+    /// doesn't wait for a round trip after every page. At most one page is
+    /// fetched ahead.
+    pub prefetch: bool,
+}
+
+/// A message and everything that happened while delivering it, see
+/// [`Message::delivery_timeline`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct MessageDeliveryTimeline {
+    pub message: MessageOut,
+    /// The endpoints the message was sent to, with their delivery status and
+    /// the time of the next attempt.
+    pub destinations: Vec<MessageEndpointOut>,
+    /// All delivery attempts to all endpoints, oldest first.
+    pub attempts: Vec<MessageAttemptOut>,
+}
+
+impl MessageDeliveryTimeline {
+    /// Returns the attempts to deliver the message to one endpoint, oldest
+    /// first.
+    pub fn attempts_for<'a>(
+        &'a self,
+        endpoint_id: &'a str,
+    ) -> impl Iterator<Item = &'a MessageAttemptOut> + 'a {
+        self.attempts
+            .iter()
+            .filter(move |attempt| attempt.endpoint_id == endpoint_id)
+    }
+}
+
+/// Removes messages that occur more than once, and sorts them newest first.
+#[cfg(feature = "api-message")]
+fn dedup_newest_first(messages: impl IntoIterator<Item = MessageOut>) -> Vec<MessageOut> {
+    let mut seen = HashSet::new();
+    let mut messages: Vec<_> = messages
+        .into_iter()
+        .filter(|msg| seen.insert(msg.id.clone()))
+        .collect();
+    messages.sort_by_key(|msg| std::cmp::Reverse(msg.timestamp));
+    messages
+}
+
+#[derive(Default)]
+pub struct MessageExpungeAllContentOptions {
+    pub before: Option<Timestamp>,
+    pub after: Option<Timestamp>,
+}
+
+/// Progress of [`Message::expunge_all_content`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExpungeProgress {
+    /// Number of messages whose content has been expunged so far.
+    pub messages: usize,
+    /// Number of message attempts whose content has been expunged so far.
+    pub attempts: usize,
+}
+
+#[derive(Default)]
+pub struct MessageCreateBatchOptions {
+    /// How many messages are sent at the same time.
+    ///
+    /// Default: 8.
+    pub concurrency: Option<usize>,
+    /// When set, the message at index `i` is sent with the idempotency key
+    /// `{idempotency_key_prefix}-{i}`, so a failed batch can be retried as a
+    /// whole without duplicating the messages that were already created.
+    pub idempotency_key_prefix: Option<String>,
+}
+
+#[cfg(feature = "api-message")]
 pub struct Message<'a> {
     cfg: &'a Configuration,
 }
 
+#[cfg(feature = "api-message")]
 impl<'a> Message<'a> {
     fn new(cfg: &'a Configuration) -> Self {
         Self { cfg }
@@ -955,12 +1972,189 @@ impl<'a> Message<'a> {
         .await
     }
 
+    /// Like [`Message::list`], returning the response body without
+    /// deserializing it. See [`RawResponse`].
+    pub async fn list_raw(
+        &self,
+        app_id: String,
+        options: Option<MessageListOptions>,
+    ) -> Result<RawResponse> {
+        let MessageListOptions {
+            iterator,
+            limit,
+            event_types,
+            before,
+            after,
+            channel,
+            with_content,
+            tag,
+        } = options.unwrap_or_default();
+        let req = Request::new(http1::Method::GET, "/api/v1/app/{app_id}/msg".to_owned())
+            .with_operation("v1.message.list")
+            .with_path_param("app_id".to_owned(), app_id);
+        let req = with_query(req, "iterator", iterator);
+        let req = with_query(req, "limit", limit);
+        let req = with_query(req, "event_types", event_types.map(|t| t.join(",")));
+        let req = with_query(req, "before", before);
+        let req = with_query(req, "after", after);
+        let req = with_query(req, "channel", channel);
+        let req = with_query(req, "with_content", with_content);
+        let req = with_query(req, "tag", tag);
+        req.execute_raw(self.cfg).await.map(RawResponse::new)
+    }
+
+    /// Returns the application's messages whose delivery to at least one
+    /// endpoint has the given status, e.g. all failed messages of the last
+    /// hour.
+    ///
+    /// The API can only filter attempted messages by status per endpoint, so
+    /// this lists the endpoints and fetches all matching messages of each of
+    /// them. `iterator` and `limit` are ignored, and the messages are
+    /// returned newest first.
+    #[cfg(all(feature = "api-endpoint", feature = "api-message-attempt"))]
+    pub async fn list_by_status(
+        &self,
+        app_id: String,
+        status: MessageStatus,
+        options: Option<MessageListOptions>,
+    ) -> Result<Vec<MessageOut>> {
+        let MessageListOptions {
+            iterator: _,
+            limit: _,
+            event_types,
+            before,
+            after,
+            channel,
+            with_content,
+            tag,
+        } = options.unwrap_or_default();
+
+        let endpoints = Endpoint::new(self.cfg).list_all(app_id.clone()).await?;
+        let pages: Vec<Vec<EndpointMessageOut>> = futures_util::stream::iter(endpoints)
+            .map(|endpoint| {
+                let app_id = app_id.clone();
+                let event_types = event_types.clone();
+                let channel = channel.clone();
+                let tag = tag.clone();
+                async move {
+                    let mut messages = Vec::new();
+                    let mut iterator = None;
+                    loop {
+                        let page = MessageAttempt::new(self.cfg)
+                            .list_attempted_messages(
+                                app_id.clone(),
+                                endpoint.id.clone(),
+                                Some(MessageAttemptListOptions {
+                                    iterator,
+                                    limit: Some(250),
+                                    event_types: event_types.clone(),
+                                    before,
+                                    after,
+                                    channel: channel.clone(),
+                                    tag: tag.clone(),
+                                    status: Some(status),
+                                    with_content,
+                                    ..Default::default()
+                                }),
+                            )
+                            .await?;
+                        messages.extend(page.data);
+                        if page.done {
+                            break;
+                        }
+                        iterator = page.iterator;
+                    }
+                    Ok::<_, Error>(messages)
+                }
+            })
+            .buffer_unordered(8)
+            .try_collect()
+            .await?;
+
+        let messages = pages.into_iter().flatten().map(|msg| MessageOut {
+            channels: msg.channels,
+            event_id: msg.event_id,
+            event_type: msg.event_type,
+            id: msg.id,
+            payload: msg.payload,
+            tags: msg.tags,
+            timestamp: msg.timestamp,
+            #[cfg(feature = "unknown-fields")]
+            extra: msg.extra,
+        });
+        Ok(dedup_newest_first(messages))
+    }
+
+    /// Returns the application's messages that have any of the given tags.
+    ///
+    /// The API can only filter by a single tag, so this fetches all matching
+    /// messages of each tag and merges them. `tag`, `iterator` and `limit`
+    /// of the options are ignored, and the messages are returned newest
+    /// first.
+    pub async fn list_by_tags(
+        &self,
+        app_id: String,
+        tags: Vec<String>,
+        options: Option<MessageListOptions>,
+    ) -> Result<Vec<MessageOut>> {
+        let MessageListOptions {
+            iterator: _,
+            limit: _,
+            event_types,
+            before,
+            after,
+            channel,
+            with_content,
+            tag: _,
+        } = options.unwrap_or_default();
+
+        let pages: Vec<Vec<MessageOut>> = futures_util::stream::iter(tags)
+            .map(|tag| {
+                let app_id = app_id.clone();
+                let event_types = event_types.clone();
+                let channel = channel.clone();
+                async move {
+                    let mut messages = Vec::new();
+                    let mut iterator = None;
+                    loop {
+                        let page = self
+                            .list(
+                                app_id.clone(),
+                                Some(MessageListOptions {
+                                    iterator,
+                                    limit: Some(250),
+                                    event_types: event_types.clone(),
+                                    before,
+                                    after,
+                                    channel: channel.clone(),
+                                    with_content,
+                                    tag: Some(tag.clone()),
+                                }),
+                            )
+                            .await?;
+                        messages.extend(page.data);
+                        if page.done {
+                            break;
+                        }
+                        iterator = page.iterator;
+                    }
+                    Ok::<_, Error>(messages)
+                }
+            })
+            .buffer_unordered(8)
+            .try_collect()
+            .await?;
+
+        Ok(dedup_newest_first(pages.into_iter().flatten()))
+    }
+
     pub async fn create(
         &self,
         app_id: String,
         message_in: MessageIn,
         options: Option<PostOptions>,
     ) -> Result<MessageOut> {
+        names::check_message_in(&message_in)?;
         let PostOptions { idempotency_key } = options.unwrap_or_default();
         message_api::v1_period_message_period_create(
             self.cfg,
@@ -974,6 +2168,214 @@ impl<'a> Message<'a> {
         .await
     }
 
+    /// Creates a message, creating its application first if it doesn't exist
+    /// yet.
+    ///
+    /// The application is identified by the `uid` of `application_in`, which
+    /// is therefore required.
+    pub async fn create_with_application(
+        &self,
+        application_in: ApplicationIn,
+        mut message_in: MessageIn,
+        options: Option<PostOptions>,
+    ) -> Result<MessageOut> {
+        let app_id = application_in.uid.clone().ok_or_else(|| {
+            Error::Generic(
+                "`application_in.uid` is required to create it with a message".to_owned(),
+            )
+        })?;
+        message_in.application = Some(Box::new(application_in));
+        self.create(app_id, message_in, options).await
+    }
+
+    /// Creates all of the given messages, a few at a time.
+    ///
+    /// The returned results are in the same order as `messages`; a failure to
+    /// create one message doesn't stop the others from being sent.
+    pub async fn create_batch(
+        &self,
+        app_id: String,
+        messages: Vec<MessageIn>,
+        options: Option<MessageCreateBatchOptions>,
+    ) -> Vec<Result<MessageOut>> {
+        let MessageCreateBatchOptions {
+            concurrency,
+            idempotency_key_prefix,
+        } = options.unwrap_or_default();
+
+        futures_util::stream::iter(messages.into_iter().enumerate())
+            .map(|(i, message_in)| {
+                let idempotency_key = idempotency_key_prefix
+                    .as_ref()
+                    .map(|prefix| format!("{prefix}-{i}"));
+                self.create(
+                    app_id.clone(),
+                    message_in,
+                    Some(PostOptions { idempotency_key }),
+                )
+            })
+            .buffered(concurrency.unwrap_or(8).max(1))
+            .collect()
+            .await
+    }
+
+    /// Writes all of the application's messages in the given range to
+    /// `writer` as newline-delimited JSON, one message per line, newest first.
+    ///
+    /// Returns the number of exported messages.
+    #[cfg(feature = "api-message-attempt")]
+    pub async fn export_ndjson<W>(
+        &self,
+        app_id: String,
+        options: Option<MessageExportOptions>,
+        writer: &mut W,
+    ) -> Result<u64>
+    where
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        use tokio::io::AsyncWriteExt as _;
+
+        let MessageExportOptions {
+            before,
+            after,
+            event_types,
+            with_content,
+            with_attempts,
+            prefetch,
+        } = options.unwrap_or_default();
+
+        let list_page = |iterator| {
+            self.list(
+                app_id.clone(),
+                Some(MessageListOptions {
+                    iterator,
+                    limit: Some(250),
+                    event_types: event_types.clone(),
+                    before,
+                    after,
+                    with_content,
+                    ..Default::default()
+                }),
+            )
+        };
+
+        let mut count = 0;
+        let mut page = list_page(None).await?;
+        loop {
+            let next_iterator = (!page.done).then(|| page.iterator.clone());
+            let next_page = async {
+                match next_iterator {
+                    Some(iterator) if prefetch => Some(list_page(iterator).await),
+                    _ => None,
+                }
+            };
+            let write_page = async {
+                for msg in page.data {
+                    let mut line = serde_json::to_value(&msg).map_err(Error::generic)?;
+                    if with_attempts {
+                        let attempts = self.list_all_attempts(app_id.clone(), msg.id).await?;
+                        line["attempts"] =
+                            serde_json::to_value(attempts).map_err(Error::generic)?;
+                    }
+
+                    let mut line = serde_json::to_vec(&line).map_err(Error::generic)?;
+                    line.push(b'\n');
+                    writer.write_all(&line).await.map_err(Error::generic)?;
+                    count += 1;
+                }
+                Ok::<_, Error>(())
+            };
+            let (next_page, written) = futures_util::future::join(next_page, write_page).await;
+            written?;
+
+            if page.done {
+                break;
+            }
+            page = match next_page {
+                Some(next_page) => next_page?,
+                None => list_page(page.iterator).await?,
+            };
+        }
+
+        writer.flush().await.map_err(Error::generic)?;
+        Ok(count)
+    }
+
+    #[cfg(feature = "api-message-attempt")]
+    async fn list_all_attempts(
+        &self,
+        app_id: String,
+        msg_id: String,
+    ) -> Result<Vec<MessageAttemptOut>> {
+        let mut attempts = Vec::new();
+        let mut iterator = None;
+        loop {
+            let page = MessageAttempt::new(self.cfg)
+                .list_by_msg(
+                    app_id.clone(),
+                    msg_id.clone(),
+                    Some(MessageAttemptListOptions {
+                        iterator,
+                        limit: Some(250),
+                        ..Default::default()
+                    }),
+                )
+                .await?;
+            attempts.extend(page.data);
+            if page.done {
+                break;
+            }
+            iterator = page.iterator;
+        }
+        Ok(attempts)
+    }
+
+    /// Returns the message together with all of its destinations and
+    /// delivery attempts.
+    #[cfg(feature = "api-message-attempt")]
+    pub async fn delivery_timeline(
+        &self,
+        app_id: String,
+        msg_id: String,
+    ) -> Result<MessageDeliveryTimeline> {
+        let destinations = async {
+            let mut destinations = Vec::new();
+            let mut iterator = None;
+            loop {
+                let page = MessageAttempt::new(self.cfg)
+                    .list_attempted_destinations(
+                        app_id.clone(),
+                        msg_id.clone(),
+                        Some(ListOptions {
+                            iterator,
+                            limit: Some(250),
+                        }),
+                    )
+                    .await?;
+                destinations.extend(page.data);
+                if page.done {
+                    break;
+                }
+                iterator = page.iterator;
+            }
+            Ok(destinations)
+        };
+
+        let (message, destinations, mut attempts) = futures_util::future::try_join3(
+            self.get(app_id.clone(), msg_id.clone()),
+            destinations,
+            self.list_all_attempts(app_id.clone(), msg_id.clone()),
+        )
+        .await?;
+        attempts.sort_by_key(|a| a.timestamp);
+
+        Ok(MessageDeliveryTimeline {
+            message,
+            destinations,
+            attempts,
+        })
+    }
+
     pub async fn get(&self, app_id: String, msg_id: String) -> Result<MessageOut> {
         message_api::v1_period_message_period_get(
             self.cfg,
@@ -986,6 +2388,98 @@ impl<'a> Message<'a> {
         .await
     }
 
+    /// Returns the message's payload as the raw string it is stored as.
+    ///
+    /// Unlike [`Message::get`], this works regardless of `with_content`.
+    pub async fn get_raw_payload(
+        &self,
+        app_id: String,
+        msg_id: String,
+    ) -> Result<MessageRawPayloadOut> {
+        message_api::v1_period_message_period_get_raw_payload(
+            self.cfg,
+            message_api::V1PeriodMessagePeriodGetRawPayloadParams { app_id, msg_id },
+        )
+        .await
+    }
+
+    /// Expunges the content of all of the application's messages in the
+    /// given time range, along with the content of their attempts.
+    ///
+    /// This is meant for data deletion requests that cover a whole consumer
+    /// application. `on_progress` is called after each message is done.
+    /// Expunging is idempotent, so after an error this can simply be called
+    /// again with the same options.
+    #[cfg(feature = "api-message-attempt")]
+    pub async fn expunge_all_content(
+        &self,
+        app_id: String,
+        options: Option<MessageExpungeAllContentOptions>,
+        mut on_progress: impl FnMut(&ExpungeProgress),
+    ) -> Result<ExpungeProgress> {
+        let MessageExpungeAllContentOptions { before, after } = options.unwrap_or_default();
+        let message_attempt = MessageAttempt::new(self.cfg);
+        let mut progress = ExpungeProgress::default();
+
+        let mut iterator = None;
+        loop {
+            let messages = self
+                .list(
+                    app_id.clone(),
+                    Some(MessageListOptions {
+                        iterator,
+                        limit: Some(100),
+                        before,
+                        after,
+                        with_content: Some(false),
+                        ..Default::default()
+                    }),
+                )
+                .await?;
+
+            for msg in messages.data {
+                let mut attempt_iterator = None;
+                loop {
+                    let attempts = message_attempt
+                        .list_by_msg(
+                            app_id.clone(),
+                            msg.id.clone(),
+                            Some(MessageAttemptListOptions {
+                                iterator: attempt_iterator,
+                                limit: Some(100),
+                                with_content: Some(false),
+                                ..Default::default()
+                            }),
+                        )
+                        .await?;
+
+                    for attempt in attempts.data {
+                        message_attempt
+                            .expunge_content(app_id.clone(), msg.id.clone(), attempt.id)
+                            .await?;
+                        progress.attempts += 1;
+                    }
+
+                    if attempts.done {
+                        break;
+                    }
+                    attempt_iterator = attempts.iterator;
+                }
+
+                self.expunge_content(app_id.clone(), msg.id).await?;
+                progress.messages += 1;
+                on_progress(&progress);
+            }
+
+            if messages.done {
+                break;
+            }
+            iterator = messages.iterator;
+        }
+
+        Ok(progress)
+    }
+
     pub async fn expunge_content(&self, app_id: String, msg_id: String) -> Result<()> {
         message_api::v1_period_message_period_expunge_content(
             self.cfg,
@@ -1016,11 +2510,8 @@ pub struct MessageAttemptListOptions {
     pub iterator: Option<String>,
     pub limit: Option<i32>,
     pub event_types: Option<Vec<String>>,
-    // FIXME: make before and after actual dates
-    /// RFC3339 date string
-    pub before: Option<String>,
-    /// RFC3339 date string
-    pub after: Option<String>,
+    pub before: Option<Timestamp>,
+    pub after: Option<Timestamp>,
     pub channel: Option<String>,
     pub tag: Option<String>,
     pub status: Option<MessageStatus>,
@@ -1034,11 +2525,8 @@ pub struct MessageAttemptListByEndpointOptions {
     pub iterator: Option<String>,
     pub limit: Option<i32>,
     pub event_types: Option<Vec<String>>,
-    // FIXME: make before and after actual dates
-    /// RFC3339 date string
-    pub before: Option<String>,
-    /// RFC3339 date string
-    pub after: Option<String>,
+    pub before: Option<Timestamp>,
+    pub after: Option<Timestamp>,
     pub channel: Option<String>,
     pub tag: Option<String>,
     pub status: Option<MessageStatus>,
@@ -1048,10 +2536,12 @@ pub struct MessageAttemptListByEndpointOptions {
     pub endpoint_id: Option<String>,
 }
 
+#[cfg(feature = "api-message-attempt")]
 pub struct MessageAttempt<'a> {
     cfg: &'a Configuration,
 }
 
+#[cfg(feature = "api-message-attempt")]
 impl<'a> MessageAttempt<'a> {
     fn new(cfg: &'a Configuration) -> Self {
         Self { cfg }
@@ -1097,6 +2587,48 @@ impl<'a> MessageAttempt<'a> {
         .await
     }
 
+    /// Like [`MessageAttempt::list_by_msg`], returning the response body
+    /// without deserializing it. See [`RawResponse`].
+    pub async fn list_by_msg_raw(
+        &self,
+        app_id: String,
+        msg_id: String,
+        options: Option<MessageAttemptListOptions>,
+    ) -> Result<RawResponse> {
+        let MessageAttemptListOptions {
+            iterator,
+            limit,
+            event_types,
+            before,
+            after,
+            channel,
+            status,
+            tag,
+            status_code_class,
+            endpoint_id,
+            with_content,
+        } = options.unwrap_or_default();
+        let req = Request::new(
+            http1::Method::GET,
+            "/api/v1/app/{app_id}/attempt/msg/{msg_id}".to_owned(),
+        )
+        .with_operation("v1.message-attempt.list-by-msg")
+        .with_path_param("app_id".to_owned(), app_id)
+        .with_path_param("msg_id".to_owned(), msg_id);
+        let req = with_query(req, "iterator", iterator);
+        let req = with_query(req, "limit", limit);
+        let req = with_query(req, "event_types", event_types.map(|t| t.join(",")));
+        let req = with_query(req, "before", before);
+        let req = with_query(req, "after", after);
+        let req = with_query(req, "channel", channel);
+        let req = with_query(req, "status", status);
+        let req = with_query(req, "tag", tag);
+        let req = with_query(req, "status_code_class", status_code_class);
+        let req = with_query(req, "endpoint_id", endpoint_id);
+        let req = with_query(req, "with_content", with_content);
+        req.execute_raw(self.cfg).await.map(RawResponse::new)
+    }
+
     pub async fn list_by_endpoint(
         &self,
         app_id: String,
@@ -1138,13 +2670,15 @@ impl<'a> MessageAttempt<'a> {
         .await
     }
 
-    pub async fn list_attempted_messages(
+    /// Like [`MessageAttempt::list_by_endpoint`], returning the response body
+    /// without deserializing it. See [`RawResponse`].
+    pub async fn list_by_endpoint_raw(
         &self,
         app_id: String,
         endpoint_id: String,
-        options: Option<MessageAttemptListOptions>,
-    ) -> Result<ListResponseEndpointMessageOut> {
-        let MessageAttemptListOptions {
+        options: Option<MessageAttemptListByEndpointOptions>,
+    ) -> Result<RawResponse> {
+        let MessageAttemptListByEndpointOptions {
             iterator,
             limit,
             event_types,
@@ -1153,12 +2687,53 @@ impl<'a> MessageAttempt<'a> {
             channel,
             tag,
             status,
-            status_code_class: _,
-            with_content,
+            status_code_class,
             endpoint_id: _,
+            with_content,
+            with_msg,
         } = options.unwrap_or_default();
-        message_attempt_api::v1_period_message_attempt_period_list_attempted_messages(
-            self.cfg,
+        let req = Request::new(
+            http1::Method::GET,
+            "/api/v1/app/{app_id}/attempt/endpoint/{endpoint_id}".to_owned(),
+        )
+        .with_operation("v1.message-attempt.list-by-endpoint")
+        .with_path_param("app_id".to_owned(), app_id)
+        .with_path_param("endpoint_id".to_owned(), endpoint_id);
+        let req = with_query(req, "iterator", iterator);
+        let req = with_query(req, "limit", limit);
+        let req = with_query(req, "event_types", event_types.map(|t| t.join(",")));
+        let req = with_query(req, "before", before);
+        let req = with_query(req, "after", after);
+        let req = with_query(req, "channel", channel);
+        let req = with_query(req, "tag", tag);
+        let req = with_query(req, "status", status);
+        let req = with_query(req, "status_code_class", status_code_class);
+        let req = with_query(req, "with_content", with_content);
+        let req = with_query(req, "with_msg", with_msg);
+        req.execute_raw(self.cfg).await.map(RawResponse::new)
+    }
+
+    pub async fn list_attempted_messages(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        options: Option<MessageAttemptListOptions>,
+    ) -> Result<ListResponseEndpointMessageOut> {
+        let MessageAttemptListOptions {
+            iterator,
+            limit,
+            event_types,
+            before,
+            after,
+            channel,
+            tag,
+            status,
+            status_code_class: _,
+            with_content,
+            endpoint_id: _,
+        } = options.unwrap_or_default();
+        message_attempt_api::v1_period_message_attempt_period_list_attempted_messages(
+            self.cfg,
             message_attempt_api::V1PeriodMessageAttemptPeriodListAttemptedMessagesParams {
                 app_id,
                 endpoint_id,
@@ -1251,6 +2826,25 @@ impl<'a> MessageAttempt<'a> {
         .await
     }
 
+    /// Returns the headers that were sent with the attempt, and the headers
+    /// of the endpoint's response.
+    pub async fn get_headers(
+        &self,
+        app_id: String,
+        msg_id: String,
+        attempt_id: String,
+    ) -> Result<MessageAttemptHeadersOut> {
+        message_attempt_api::v1_period_message_attempt_period_get_headers(
+            self.cfg,
+            message_attempt_api::V1PeriodMessageAttemptPeriodGetHeadersParams {
+                app_id,
+                msg_id,
+                attempt_id,
+            },
+        )
+        .await
+    }
+
     pub async fn resend(&self, app_id: String, msg_id: String, endpoint_id: String) -> Result<()> {
         message_attempt_api::v1_period_message_attempt_period_resend(
             self.cfg,
@@ -1289,15 +2883,23 @@ pub struct OperationalWebhookEndpointListOptions {
     pub order: Option<Ordering>,
 }
 
+#[cfg(feature = "api-webhook-endpoint")]
 pub struct OperationalWebhookEndpoint<'a> {
     cfg: &'a Configuration,
 }
 
+#[cfg(feature = "api-webhook-endpoint")]
 impl<'a> OperationalWebhookEndpoint<'a> {
     fn new(cfg: &'a Configuration) -> Self {
         Self { cfg }
     }
 
+    /// Returns the event types that can be sent to operational webhook
+    /// endpoints.
+    pub fn event_types(&self) -> &'static [OperationalWebhookEventType] {
+        OPERATIONAL_WEBHOOK_EVENT_TYPES
+    }
+
     pub async fn list(
         &self,
         options: Option<OperationalWebhookEndpointListOptions>,
@@ -1407,10 +3009,12 @@ pub struct BackgroundTaskListOptions {
     pub task: Option<BackgroundTaskType>,
 }
 
+#[cfg(feature = "api-background-tasks")]
 pub struct BackgroundTask<'a> {
     cfg: &'a Configuration,
 }
 
+#[cfg(feature = "api-background-tasks")]
 impl<'a> BackgroundTask<'a> {
     fn new(cfg: &'a Configuration) -> Self {
         Self { cfg }
@@ -1447,23 +3051,264 @@ impl<'a> BackgroundTask<'a> {
         )
         .await
     }
+
+    /// Polls the task until it has finished or failed, and returns it.
+    ///
+    /// The delay between polls starts at `poll_interval` (at least 100ms)
+    /// and doubles after each poll, up to 30 seconds. If the task is still
+    /// running when polled at the end of `timeout`, an error is returned.
+    pub async fn wait(
+        &self,
+        task_id: String,
+        poll_interval: std::time::Duration,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<BackgroundTaskOut> {
+        const MIN_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+        const MAX_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+        let poll_interval = poll_interval.max(MIN_POLL_INTERVAL);
+        let deadline = timeout.map(|timeout| tokio::time::Instant::now() + timeout);
+        let mut delay = poll_interval;
+        loop {
+            let task = self.get(task_id.clone()).await?;
+            if task.status != BackgroundTaskStatus::Running {
+                return Ok(task);
+            }
+
+            // Sleep until the deadline at most, so the task is polled one
+            // last time at the deadline before giving up
+            let sleep = match deadline {
+                Some(deadline) => {
+                    let now = tokio::time::Instant::now();
+                    if now >= deadline {
+                        return Err(Error::Generic(format!(
+                            "timed out waiting for background task {task_id}"
+                        )));
+                    }
+                    delay.min(deadline - now)
+                }
+                None => delay,
+            };
+            tokio::time::sleep(sleep).await;
+            delay = (delay * 2).min(MAX_POLL_INTERVAL.max(poll_interval));
+        }
+    }
+
+    /// Waits for the task like [`wait`](Self::wait), and turns a failed task
+    /// into an error.
+    #[cfg(any(feature = "api-endpoint", feature = "api-statistics"))]
+    async fn wait_finished(
+        &self,
+        task_id: String,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<BackgroundTaskOut> {
+        let task = self
+            .wait(task_id, std::time::Duration::from_secs(1), timeout)
+            .await?;
+        match task.status {
+            BackgroundTaskStatus::Finished => Ok(task),
+            status => Err(Error::Generic(format!(
+                "background task {} ended with status {status}",
+                task.id
+            ))),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SinkListOptions {
+    pub iterator: Option<String>,
+    pub limit: Option<i32>,
+    pub order: Option<Ordering>,
+}
+
+/// Queue and storage destinations (RabbitMQ, SQS, Kafka, HTTP) that an
+/// application's messages are forwarded to.
+///
+/// Sinks can only be listed, created and fetched: the API has no operations
+/// for updating them or rotating their secrets yet.
+#[cfg(feature = "api-endpoint")]
+pub struct Sink<'a> {
+    cfg: &'a Configuration,
+}
+
+#[cfg(feature = "api-endpoint")]
+impl<'a> Sink<'a> {
+    fn new(cfg: &'a Configuration) -> Self {
+        Self { cfg }
+    }
+
+    pub async fn list(
+        &self,
+        app_id: String,
+        options: Option<SinkListOptions>,
+    ) -> Result<ListResponseSinkOut> {
+        let SinkListOptions {
+            iterator,
+            limit,
+            order,
+        } = options.unwrap_or_default();
+        endpoint_api::v1_period_sink_period_list(
+            self.cfg,
+            endpoint_api::V1PeriodSinkPeriodListParams {
+                app_id,
+                limit,
+                iterator,
+                order,
+            },
+        )
+        .await
+    }
+
+    pub async fn create(
+        &self,
+        app_id: String,
+        sink_in: SinkIn,
+        options: Option<PostOptions>,
+    ) -> Result<SinkOut> {
+        let PostOptions { idempotency_key } = options.unwrap_or_default();
+        endpoint_api::v1_period_sink_period_create(
+            self.cfg,
+            endpoint_api::V1PeriodSinkPeriodCreateParams {
+                app_id,
+                idempotency_key,
+                sink_in,
+            },
+        )
+        .await
+    }
+
+    pub async fn get(&self, app_id: String, sink_id: String) -> Result<SinkOut> {
+        endpoint_api::v1_period_sink_period_get(
+            self.cfg,
+            endpoint_api::V1PeriodSinkPeriodGetParams { app_id, sink_id },
+        )
+        .await
+    }
 }
 
+#[cfg(feature = "api-statistics")]
 pub struct Statistics<'a> {
     cfg: &'a Configuration,
 }
 
+#[derive(Default)]
+pub struct AttemptStatisticsOptions {
+    pub start_date: Option<Timestamp>,
+    pub end_date: Option<Timestamp>,
+}
+
 pub struct AggregateAppStatsOptions {
     pub app_ids: Option<Vec<String>>,
-    pub since: String,
-    pub until: String,
+    pub since: Timestamp,
+    pub until: Timestamp,
+}
+
+/// The size of the time windows [`Statistics::aggregate_app_stats_by`]
+/// splits its range into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatsGranularity {
+    Hour,
+    Day,
 }
 
+impl StatsGranularity {
+    #[cfg(all(feature = "api-statistics", feature = "api-background-tasks"))]
+    fn duration(self) -> std::time::Duration {
+        match self {
+            Self::Hour => std::time::Duration::from_secs(60 * 60),
+            Self::Day => std::time::Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+/// Splits the range between `since` and `until` into `granularity` sized
+/// windows. The last window may be shorter.
+#[cfg(all(feature = "api-statistics", feature = "api-background-tasks"))]
+fn stats_windows(
+    since: Timestamp,
+    until: Timestamp,
+    granularity: StatsGranularity,
+) -> Vec<(Timestamp, Timestamp)> {
+    let mut windows = Vec::new();
+    let mut start = since;
+    while start < until {
+        let end = (start + granularity.duration()).min(until);
+        windows.push((start, end));
+        start = end;
+    }
+    windows
+}
+
+/// The usage statistics of one time window, see
+/// [`Statistics::aggregate_app_stats_by`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct AppUsageStatsWindow {
+    pub since: Timestamp,
+    pub until: Timestamp,
+    /// The finished (or failed) aggregation task, holding the statistics in
+    /// its `data`.
+    pub task: BackgroundTaskOut,
+    pub unresolved_app_ids: Vec<String>,
+}
+
+#[cfg(feature = "api-statistics")]
 impl<'a> Statistics<'a> {
     fn new(cfg: &'a Configuration) -> Self {
         Self { cfg }
     }
 
+    /// Aggregates the applications' usage statistics separately for each
+    /// `granularity` sized window between `since` and `until`.
+    ///
+    /// Each window is aggregated by its own background task, and yielded as
+    /// soon as that task is done. This keeps the individual aggregations
+    /// small for long ranges, e.g. a month of daily statistics.
+    #[cfg(feature = "api-background-tasks")]
+    pub fn aggregate_app_stats_by(
+        &self,
+        AggregateAppStatsOptions {
+            app_ids,
+            since,
+            until,
+        }: AggregateAppStatsOptions,
+        granularity: StatsGranularity,
+    ) -> Result<impl futures_util::Stream<Item = Result<AppUsageStatsWindow>> + 'a> {
+        let windows = stats_windows(since, until, granularity);
+
+        let cfg = self.cfg;
+        Ok(
+            futures_util::stream::iter(windows).then(move |(since, until)| {
+                let app_usage_stats_in = AppUsageStatsIn {
+                    app_ids: app_ids.clone(),
+                    since,
+                    until,
+                    #[cfg(feature = "unknown-fields")]
+                    extra: Default::default(),
+                };
+                async move {
+                    let out = statistics_api::v1_period_statistics_period_aggregate_app_stats(
+                        cfg,
+                        statistics_api::V1PeriodStatisticsPeriodAggregateAppStatsParams {
+                            app_usage_stats_in,
+                            idempotency_key: None,
+                        },
+                    )
+                    .await?;
+                    let task = BackgroundTask::new(cfg)
+                        .wait(out.id, std::time::Duration::from_secs(1), None)
+                        .await?;
+                    Ok(AppUsageStatsWindow {
+                        since,
+                        until,
+                        task,
+                        unresolved_app_ids: out.unresolved_app_ids,
+                    })
+                }
+            }),
+        )
+    }
+
     pub async fn aggregate_app_stats(
         &self,
         AggregateAppStatsOptions {
@@ -1479,21 +3324,456 @@ impl<'a> Statistics<'a> {
                 app_ids,
                 since,
                 until,
+                #[cfg(feature = "unknown-fields")]
+                extra: Default::default(),
             },
             idempotency_key: options.idempotency_key,
         };
         statistics_api::v1_period_statistics_period_aggregate_app_stats(self.cfg, params).await
     }
 
+    /// Returns the application's successful and failed attempt counts over
+    /// time.
+    pub async fn app_attempts(
+        &self,
+        app_id: String,
+        options: Option<AttemptStatisticsOptions>,
+    ) -> Result<AttemptStatisticsResponse> {
+        let AttemptStatisticsOptions {
+            start_date,
+            end_date,
+        } = options.unwrap_or_default();
+        statistics_api::v1_period_stats_period_app_attempts(
+            self.cfg,
+            statistics_api::V1PeriodStatsPeriodAppAttemptsParams {
+                start_date,
+                end_date,
+                app_id,
+            },
+        )
+        .await
+    }
+
+    /// Returns the endpoint's successful and failed attempt counts over time.
+    pub async fn endpoint_attempts(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        options: Option<AttemptStatisticsOptions>,
+    ) -> Result<AttemptStatisticsResponse> {
+        let AttemptStatisticsOptions {
+            start_date,
+            end_date,
+        } = options.unwrap_or_default();
+        statistics_api::v1_period_stats_period_endpoint_attempts(
+            self.cfg,
+            statistics_api::V1PeriodStatsPeriodEndpointAttemptsParams {
+                start_date,
+                end_date,
+                app_id,
+                endpoint_id,
+            },
+        )
+        .await
+    }
+
     pub async fn aggregate_event_types(&self) -> Result<AggregateEventTypesOut> {
         statistics_api::v1_period_statistics_period_aggregate_event_types(self.cfg).await
     }
+
+    /// Like [`aggregate_event_types`](Self::aggregate_event_types), but waits
+    /// for the aggregation to finish and returns its result.
+    ///
+    /// Returns an error if the aggregation task failed, or `timeout` elapsed.
+    #[cfg(feature = "api-background-tasks")]
+    pub async fn aggregate_event_types_and_wait(
+        &self,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<serde_json::Value> {
+        let out = self.aggregate_event_types().await?;
+        let task = BackgroundTask::new(self.cfg)
+            .wait_finished(out.id, timeout)
+            .await?;
+        Ok(*task.data)
+    }
+}
+
+#[derive(Default)]
+pub struct StreamListOptions {
+    pub iterator: Option<String>,
+    pub limit: Option<i32>,
+    pub order: Option<Ordering>,
+}
+
+#[derive(Default)]
+pub struct StreamEventsOptions {
+    pub iterator: Option<String>,
+    pub limit: Option<i32>,
+    pub after: Option<Timestamp>,
+}
+
+#[cfg(feature = "api-stream")]
+pub struct Stream<'a> {
+    cfg: &'a Configuration,
+}
+
+#[cfg(feature = "api-stream")]
+impl<'a> Stream<'a> {
+    fn new(cfg: &'a Configuration) -> Self {
+        Self { cfg }
+    }
+
+    pub fn event_type(&self) -> StreamEventType<'a> {
+        StreamEventType::new(self.cfg)
+    }
+
+    pub async fn list(&self, options: Option<StreamListOptions>) -> Result<ListResponseStreamOut> {
+        let StreamListOptions {
+            iterator,
+            limit,
+            order,
+        } = options.unwrap_or_default();
+        stream_api::v1_period_stream_period_list(
+            self.cfg,
+            stream_api::V1PeriodStreamPeriodListParams {
+                limit,
+                iterator,
+                order,
+            },
+        )
+        .await
+    }
+
+    pub async fn create(
+        &self,
+        stream_in: StreamIn,
+        options: Option<PostOptions>,
+    ) -> Result<StreamOut> {
+        let PostOptions { idempotency_key } = options.unwrap_or_default();
+        stream_api::v1_period_stream_period_create(
+            self.cfg,
+            stream_api::V1PeriodStreamPeriodCreateParams {
+                idempotency_key,
+                stream_in,
+            },
+        )
+        .await
+    }
+
+    pub async fn get(&self, stream_id: String) -> Result<StreamOut> {
+        stream_api::v1_period_stream_period_get(
+            self.cfg,
+            stream_api::V1PeriodStreamPeriodGetParams { stream_id },
+        )
+        .await
+    }
+
+    pub async fn update(
+        &self,
+        stream_id: String,
+        stream_in: StreamIn,
+        _: Option<PostOptions>,
+    ) -> Result<StreamOut> {
+        stream_api::v1_period_stream_period_update(
+            self.cfg,
+            stream_api::V1PeriodStreamPeriodUpdateParams {
+                stream_id,
+                stream_in,
+            },
+        )
+        .await
+    }
+
+    pub async fn patch(
+        &self,
+        stream_id: String,
+        stream_patch: StreamPatch,
+        _: Option<PostOptions>,
+    ) -> Result<StreamOut> {
+        stream_api::v1_period_stream_period_patch(
+            self.cfg,
+            stream_api::V1PeriodStreamPeriodPatchParams {
+                stream_id,
+                stream_patch,
+            },
+        )
+        .await
+    }
+
+    pub async fn delete(&self, stream_id: String) -> Result<()> {
+        stream_api::v1_period_stream_period_delete(
+            self.cfg,
+            stream_api::V1PeriodStreamPeriodDeleteParams { stream_id },
+        )
+        .await
+    }
+
+    /// Appends events to the stream.
+    pub async fn create_events(
+        &self,
+        stream_id: String,
+        create_stream_in: CreateStreamIn,
+        options: Option<PostOptions>,
+    ) -> Result<()> {
+        let PostOptions { idempotency_key } = options.unwrap_or_default();
+        stream_api::v1_period_stream_period_create_events(
+            self.cfg,
+            stream_api::V1PeriodStreamPeriodCreateEventsParams {
+                stream_id,
+                idempotency_key,
+                create_stream_in,
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Reads events from the stream, starting after the given iterator.
+    pub async fn events(
+        &self,
+        stream_id: String,
+        options: Option<StreamEventsOptions>,
+    ) -> Result<EventStreamOut> {
+        let StreamEventsOptions {
+            iterator,
+            limit,
+            after,
+        } = options.unwrap_or_default();
+        stream_api::v1_period_stream_period_events_get(
+            self.cfg,
+            stream_api::V1PeriodStreamPeriodEventsGetParams {
+                stream_id,
+                limit,
+                iterator,
+                after,
+            },
+        )
+        .await
+    }
+}
+
+#[cfg(feature = "api-stream")]
+pub struct StreamEventType<'a> {
+    cfg: &'a Configuration,
+}
+
+#[cfg(feature = "api-stream")]
+impl<'a> StreamEventType<'a> {
+    fn new(cfg: &'a Configuration) -> Self {
+        Self { cfg }
+    }
+
+    pub async fn list(
+        &self,
+        options: Option<StreamListOptions>,
+    ) -> Result<ListResponseStreamEventTypeOut> {
+        let StreamListOptions {
+            iterator,
+            limit,
+            order,
+        } = options.unwrap_or_default();
+        stream_event_types_api::v1_period_stream_period_event_type_period_list(
+            self.cfg,
+            stream_event_types_api::V1PeriodStreamPeriodEventTypePeriodListParams {
+                limit,
+                iterator,
+                order,
+            },
+        )
+        .await
+    }
+
+    pub async fn create(
+        &self,
+        stream_event_type_in: StreamEventTypeIn,
+        options: Option<PostOptions>,
+    ) -> Result<StreamEventTypeOut> {
+        let PostOptions { idempotency_key } = options.unwrap_or_default();
+        stream_event_types_api::v1_period_stream_period_event_type_period_create(
+            self.cfg,
+            stream_event_types_api::V1PeriodStreamPeriodEventTypePeriodCreateParams {
+                idempotency_key,
+                stream_event_type_in,
+            },
+        )
+        .await
+    }
+
+    pub async fn get(&self, name: String) -> Result<StreamEventTypeOut> {
+        stream_event_types_api::v1_period_stream_period_event_type_period_get(
+            self.cfg,
+            stream_event_types_api::V1PeriodStreamPeriodEventTypePeriodGetParams { name },
+        )
+        .await
+    }
+
+    pub async fn update(
+        &self,
+        name: String,
+        stream_event_type_in: StreamEventTypeIn,
+        _: Option<PostOptions>,
+    ) -> Result<StreamEventTypeOut> {
+        stream_event_types_api::v1_period_stream_period_event_type_period_update(
+            self.cfg,
+            stream_event_types_api::V1PeriodStreamPeriodEventTypePeriodUpdateParams {
+                name,
+                stream_event_type_in,
+            },
+        )
+        .await
+    }
+
+    pub async fn patch(
+        &self,
+        name: String,
+        stream_event_type_patch: StreamEventTypePatch,
+        _: Option<PostOptions>,
+    ) -> Result<StreamEventTypeOut> {
+        stream_event_types_api::v1_period_stream_period_event_type_period_patch(
+            self.cfg,
+            stream_event_types_api::V1PeriodStreamPeriodEventTypePeriodPatchParams {
+                name,
+                stream_event_type_patch,
+            },
+        )
+        .await
+    }
+
+    pub async fn delete(&self, name: String) -> Result<()> {
+        stream_event_types_api::v1_period_stream_period_event_type_period_delete(
+            self.cfg,
+            stream_event_types_api::V1PeriodStreamPeriodEventTypePeriodDeleteParams { name },
+        )
+        .await
+    }
+}
+
+#[derive(Default)]
+pub struct TransformationTemplateListOptions {
+    pub iterator: Option<String>,
+    pub limit: Option<i32>,
+    pub order: Option<Ordering>,
+    /// Only return templates of the given kind, e.g.
+    /// [`TransformationTemplateKind::Slack`].
+    ///
+    /// Like [`EndpointListOptions::disabled`], this is applied to each page
+    /// after it was fetched.
+    pub kind: Option<TransformationTemplateKind>,
+}
+
+/// Transformation templates, also known as connectors, e.g. for delivering
+/// messages to Slack, Discord or Teams.
+#[cfg(feature = "api-transformation-template")]
+pub struct TransformationTemplate<'a> {
+    cfg: &'a Configuration,
+}
+
+#[cfg(feature = "api-transformation-template")]
+impl<'a> TransformationTemplate<'a> {
+    fn new(cfg: &'a Configuration) -> Self {
+        Self { cfg }
+    }
+
+    pub async fn list(
+        &self,
+        options: Option<TransformationTemplateListOptions>,
+    ) -> Result<ListResponseTemplateOut> {
+        let TransformationTemplateListOptions {
+            iterator,
+            limit,
+            order,
+            kind,
+        } = options.unwrap_or_default();
+        let mut list = transformation_template_api::v1_period_transformation_template_period_list(
+            self.cfg,
+            transformation_template_api::V1PeriodTransformationTemplatePeriodListParams {
+                limit,
+                iterator,
+                order,
+            },
+        )
+        .await?;
+
+        if let Some(kind) = kind {
+            list.data.retain(|template| template.kind == kind);
+        }
+        Ok(list)
+    }
+
+    pub async fn create(
+        &self,
+        template_in: TemplateIn,
+        options: Option<PostOptions>,
+    ) -> Result<TemplateOut> {
+        let PostOptions { idempotency_key } = options.unwrap_or_default();
+        transformation_template_api::v1_period_transformation_template_period_create(
+            self.cfg,
+            transformation_template_api::V1PeriodTransformationTemplatePeriodCreateParams {
+                idempotency_key,
+                template_in,
+            },
+        )
+        .await
+    }
+
+    pub async fn get(&self, transformation_template_id: String) -> Result<TemplateOut> {
+        transformation_template_api::v1_period_transformation_template_period_get(
+            self.cfg,
+            transformation_template_api::V1PeriodTransformationTemplatePeriodGetParams {
+                transformation_template_id,
+            },
+        )
+        .await
+    }
+
+    pub async fn update(
+        &self,
+        transformation_template_id: String,
+        template_update: TemplateUpdate,
+        _: Option<PostOptions>,
+    ) -> Result<TemplateOut> {
+        transformation_template_api::v1_period_transformation_template_period_update(
+            self.cfg,
+            transformation_template_api::V1PeriodTransformationTemplatePeriodUpdateParams {
+                transformation_template_id,
+                template_update,
+            },
+        )
+        .await
+    }
+
+    pub async fn patch(
+        &self,
+        transformation_template_id: String,
+        template_patch: TemplatePatch,
+        _: Option<PostOptions>,
+    ) -> Result<TemplateOut> {
+        transformation_template_api::v1_period_transformation_template_period_patch(
+            self.cfg,
+            transformation_template_api::V1PeriodTransformationTemplatePeriodPatchParams {
+                transformation_template_id,
+                template_patch,
+            },
+        )
+        .await
+    }
+
+    pub async fn delete(&self, transformation_template_id: String) -> Result<()> {
+        transformation_template_api::v1_period_transformation_template_period_delete(
+            self.cfg,
+            transformation_template_api::V1PeriodTransformationTemplatePeriodDeleteParams {
+                transformation_template_id,
+            },
+        )
+        .await
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::api::Svix;
 
+    #[cfg(feature = "api-message")]
     #[test]
     fn test_future_send_sync() {
         fn require_send_sync<T: Send + Sync>(_: T) {}
@@ -1502,5 +3782,805 @@ mod tests {
         let message_api = svix.message();
         let fut = message_api.expunge_content(String::new(), String::new());
         require_send_sync(fut);
+        let fut = message_api.create_batch(String::new(), Vec::new(), None);
+        require_send_sync(fut);
+    }
+
+    #[test]
+    fn test_with_token() {
+        let svix = Svix::new("testsk_a.eu".to_owned(), None);
+        let other = svix.with_token("testsk_b.us".to_owned());
+        assert!(std::sync::Arc::ptr_eq(&svix.cfg.shared, &other.cfg.shared));
+        assert_eq!(svix.cfg.base_path, "https://api.eu.svix.com");
+        assert_eq!(other.cfg.base_path, "https://api.us.svix.com");
+        assert_eq!(
+            other.cfg.bearer_access_token.as_deref(),
+            Some("testsk_b.us")
+        );
+    }
+
+    #[cfg(all(feature = "api-statistics", feature = "api-background-tasks"))]
+    #[test]
+    fn test_stats_windows() {
+        let date = |s: &str| s.parse::<super::Timestamp>().unwrap();
+        let windows = super::stats_windows(
+            date("2024-01-01T00:00:00Z"),
+            date("2024-01-03T12:00:00Z"),
+            super::StatsGranularity::Day,
+        );
+        assert_eq!(
+            windows,
+            [
+                ("2024-01-01T00:00:00Z", "2024-01-02T00:00:00Z"),
+                ("2024-01-02T00:00:00Z", "2024-01-03T00:00:00Z"),
+                ("2024-01-03T00:00:00Z", "2024-01-03T12:00:00Z"),
+            ]
+            .map(|(since, until)| (date(since), date(until)))
+        );
+    }
+
+    #[test]
+    fn test_enum_values() {
+        use super::{BackgroundTaskStatus, MessageStatus};
+
+        let status: BackgroundTaskStatus = serde_json::from_str(r#""finished""#).unwrap();
+        assert_eq!(status, BackgroundTaskStatus::Finished);
+        let status: BackgroundTaskStatus = serde_json::from_str(r#""paused""#).unwrap();
+        assert_eq!(status, BackgroundTaskStatus::Unknown("paused".to_owned()));
+        assert_eq!(serde_json::to_string(&status).unwrap(), r#""paused""#);
+        assert_eq!(status.to_string(), "paused");
+
+        let status: MessageStatus = serde_json::from_str("2").unwrap();
+        assert_eq!(status, MessageStatus::Fail);
+        let status: MessageStatus = serde_json::from_str("7").unwrap();
+        assert_eq!(status, MessageStatus::Unknown(7));
+        assert_eq!(serde_json::to_string(&status).unwrap(), "7");
+
+        // `Display` and `FromStr` use the values on the wire
+        for status in ["running", "paused"] {
+            let parsed: BackgroundTaskStatus = status.parse().unwrap();
+            assert_eq!(parsed.to_string(), status);
+        }
+        assert_eq!("2".parse::<MessageStatus>().unwrap(), MessageStatus::Fail);
+        assert_eq!(MessageStatus::Fail.to_string(), "2");
+        assert!("fail".parse::<MessageStatus>().is_err());
+    }
+
+    #[cfg(feature = "unknown-fields")]
+    #[test]
+    fn test_unknown_fields() {
+        use serde_json::json;
+
+        let json = json!({
+            "createdAt": "2024-01-01T00:00:00Z",
+            "id": "app_1",
+            "metadata": {},
+            "name": "My app",
+            "updatedAt": "2024-01-01T00:00:00Z",
+            "addedLater": { "enabled": true },
+        });
+        let app: super::ApplicationOut = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(app.extra["addedLater"], json!({ "enabled": true }));
+        assert_eq!(serde_json::to_value(&app).unwrap(), json);
+    }
+
+    #[test]
+    fn test_model_builders() {
+        use super::{ApplicationIn, EndpointIn};
+
+        let app_in = ApplicationIn::builder().name("My app").uid("app_1").build();
+        assert_eq!(
+            app_in,
+            ApplicationIn {
+                uid: Some("app_1".to_owned()),
+                ..ApplicationIn::new("My app".to_owned())
+            }
+        );
+
+        let endpoint_in = EndpointIn::builder()
+            .url("https://example.com")
+            .disabled(true)
+            .build();
+        assert_eq!(endpoint_in.url, "https://example.com");
+        assert_eq!(endpoint_in.disabled, Some(true));
+    }
+
+    #[test]
+    fn test_delivery_health_ratios() {
+        use super::{DeliveryHealth, EndpointStats};
+
+        let health = DeliveryHealth {
+            total: EndpointStats::new(1, 5, 0, 3),
+            endpoints: [
+                ("ep_a".to_owned(), EndpointStats::new(1, 5, 0, 1)),
+                ("ep_b".to_owned(), EndpointStats::new(0, 0, 0, 2)),
+                ("ep_c".to_owned(), EndpointStats::new(0, 0, 0, 0)),
+            ]
+            .into(),
+        };
+        assert_eq!(health.success_ratio(), Some(0.75));
+        assert_eq!(health.endpoint_success_ratio("ep_a"), Some(0.5));
+        assert_eq!(health.endpoint_success_ratio("ep_c"), None);
+        assert_eq!(health.endpoint_success_ratio("ep_d"), None);
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let svix = crate::api::Svix::new(
+            "testsk_cache".to_owned(),
+            Some(crate::api::SvixOptions {
+                server_url: Some("https://api.svix.com".to_owned()),
+                ..Default::default()
+            }),
+        );
+        let conf = &svix.cfg;
+        let req = crate::request::Request::new(http1::Method::GET, "/api/v1/event-type".to_owned());
+        // The same on every build, so persisted caches stay valid
+        assert_eq!(
+            req.cache_key(conf),
+            "9164d8200cc3b685 https://api.svix.com/api/v1/event-type"
+        );
+    }
+
+    #[cfg(all(feature = "wiremock", feature = "api-event-type"))]
+    #[tokio::test]
+    async fn test_event_type_list_etag() {
+        use wiremock::{
+            matchers::{header, method, path},
+            Mock, MockServer, ResponseTemplate,
+        };
+
+        use crate::{
+            api::EventTypeOut,
+            testing::wiremock::{list_response, svix_client},
+        };
+
+        let server = MockServer::start().await;
+        let event_type = EventTypeOut {
+            name: "user.signup".to_owned(),
+            ..Default::default()
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/v1/event-type"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .with_priority(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/event-type"))
+            .respond_with(
+                list_response(std::slice::from_ref(&event_type), None)
+                    .insert_header("etag", "\"v1\""),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let svix = svix_client(&server);
+        let first = svix.event_type().list(None).await.unwrap();
+        let second = svix.event_type().list(None).await.unwrap();
+        assert_eq!(first.data, [event_type]);
+        assert_eq!(second, first);
+    }
+    #[cfg(all(feature = "wiremock", feature = "api-background-tasks"))]
+    #[tokio::test(start_paused = true)]
+    async fn test_background_task_wait() {
+        use std::time::Duration;
+
+        use tokio::time::Instant;
+        use wiremock::{
+            matchers::{method, path},
+            Mock, MockServer,
+        };
+
+        use crate::{
+            api::{BackgroundTaskStatus, Svix, SvixOptions},
+            testing::wiremock::json_response,
+        };
+
+        let task = |id: &str, status: &str| {
+            json_response(
+                200,
+                &serde_json::json!({
+                    "data": {},
+                    "id": id,
+                    "status": status,
+                    "task": "endpoint.recover",
+                }),
+            )
+        };
+        let server = MockServer::start().await;
+        for id in ["qtask_1", "qtask_3"] {
+            Mock::given(method("GET"))
+                .and(path(format!("/api/v1/background-task/{id}")))
+                .respond_with(task(id, "running"))
+                .up_to_n_times(2)
+                .expect(2)
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path(format!("/api/v1/background-task/{id}")))
+                .respond_with(task(id, "finished"))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/api/v1/background-task/qtask_2"))
+            .respond_with(task("qtask_2", "running"))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        // Without a request timeout, the paused clock only advances while
+        // waiting between polls
+        let svix = Svix::new(
+            "testsk_wiremock".to_owned(),
+            Some(SvixOptions {
+                server_url: Some(server.uri()),
+                timeout: None,
+                ..Default::default()
+            }),
+        );
+        // A zero interval is raised to the minimum, rather than polling in a
+        // busy loop: 100ms + 200ms
+        let start = Instant::now();
+        let finished = svix
+            .background_task()
+            .wait("qtask_1".to_owned(), Duration::ZERO, None)
+            .await
+            .unwrap();
+        assert_eq!(finished.status, BackgroundTaskStatus::Finished);
+        assert_eq!(start.elapsed(), Duration::from_millis(300));
+
+        // Polled at 0ms, 100ms and, instead of at 300ms, at the deadline
+        let err = svix
+            .background_task()
+            .wait(
+                "qtask_2".to_owned(),
+                Duration::from_millis(100),
+                Some(Duration::from_millis(250)),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "timed out waiting for background task qtask_2"
+        );
+
+        // A task which finishes by the deadline is returned
+        let finished = svix
+            .background_task()
+            .wait(
+                "qtask_3".to_owned(),
+                Duration::from_millis(100),
+                Some(Duration::from_millis(250)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(finished.status, BackgroundTaskStatus::Finished);
+    }
+
+    #[cfg(all(
+        feature = "wiremock",
+        feature = "api-background-tasks",
+        feature = "api-statistics"
+    ))]
+    #[tokio::test]
+    async fn test_aggregate_event_types_and_wait() {
+        use wiremock::{
+            matchers::{method, path},
+            Mock, MockServer,
+        };
+
+        use crate::testing::wiremock::{json_response, svix_client};
+
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/stats/usage/event-types"))
+            .respond_with(json_response(
+                202,
+                &serde_json::json!({
+                    "id": "qtask_1",
+                    "status": "running",
+                    "task": "application.stats",
+                }),
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/background-task/qtask_1"))
+            .respond_with(json_response(
+                200,
+                &serde_json::json!({
+                    "data": { "eventTypes": ["user.signup"] },
+                    "id": "qtask_1",
+                    "status": "finished",
+                    "task": "application.stats",
+                }),
+            ))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/background-task/qtask_1"))
+            .respond_with(json_response(
+                200,
+                &serde_json::json!({
+                    "data": {},
+                    "id": "qtask_1",
+                    "status": "failed",
+                    "task": "application.stats",
+                }),
+            ))
+            .mount(&server)
+            .await;
+
+        let svix = svix_client(&server);
+        let data = svix
+            .statistics()
+            .aggregate_event_types_and_wait(None)
+            .await
+            .unwrap();
+        assert_eq!(data, serde_json::json!({ "eventTypes": ["user.signup"] }));
+        let err = svix
+            .statistics()
+            .aggregate_event_types_and_wait(None)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "background task qtask_1 ended with status failed"
+        );
+    }
+
+    #[cfg(all(feature = "wiremock", feature = "api-event-type"))]
+    #[tokio::test]
+    async fn test_event_type_deprecate() {
+        use wiremock::{
+            matchers::{body_json, method, path},
+            Mock, MockServer,
+        };
+
+        use crate::{
+            api::EventTypeOut,
+            testing::wiremock::{json_response, svix_client},
+        };
+
+        let server = MockServer::start().await;
+        let event_type = |name: &str, description: &str| EventTypeOut {
+            name: name.to_owned(),
+            description: description.to_owned(),
+            ..Default::default()
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/v1/event-type/user.created"))
+            .respond_with(json_response(200, &event_type("user.created", "")))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/event-type/user.signup"))
+            .respond_with(json_response(
+                200,
+                &event_type("user.signup", "A user signed up."),
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/api/v1/event-type/user.signup"))
+            .and(body_json(serde_json::json!({
+                "archived": true,
+                "deprecated": true,
+                "description": "A user signed up.\n\nDeprecated in favour of `user.created`.",
+            })))
+            .respond_with(json_response(200, &event_type("user.signup", "")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let svix = svix_client(&server);
+        svix.event_type()
+            .deprecate("user.signup".to_owned(), "user.created".to_owned())
+            .await
+            .unwrap();
+        let err = svix
+            .event_type()
+            .deprecate("user.signup".to_owned(), "user.missing".to_owned())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::error::Error::Http(e) if e.status == 404));
+    }
+
+    #[cfg(all(
+        feature = "wiremock",
+        feature = "api-endpoint",
+        feature = "api-event-type"
+    ))]
+    #[tokio::test]
+    async fn test_list_with_archived_event_types() {
+        use wiremock::{
+            matchers::{method, path, query_param, query_param_is_missing},
+            Mock, MockServer,
+        };
+
+        use crate::{
+            api::{EndpointOut, EventTypeOut},
+            testing::wiremock::{list_response, svix_client},
+        };
+
+        let server = MockServer::start().await;
+        let event_type = |name: &str, archived: bool| EventTypeOut {
+            name: name.to_owned(),
+            archived: Some(archived),
+            ..Default::default()
+        };
+        let endpoint = |id: &str, filter_types: Option<&[&str]>| EndpointOut {
+            id: id.to_owned(),
+            filter_types: filter_types.map(|types| types.iter().map(|t| t.to_string()).collect()),
+            ..Default::default()
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/v1/event-type"))
+            .and(query_param("include_archived", "true"))
+            .and(query_param_is_missing("iterator"))
+            .respond_with(list_response(
+                &[
+                    event_type("user.signup", true),
+                    event_type("user.created", false),
+                ],
+                Some("et_2"),
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/event-type"))
+            .and(query_param("iterator", "et_2"))
+            .respond_with(list_response(&[event_type("invoice.paid", true)], None))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/app/app_1/endpoint"))
+            .and(query_param_is_missing("iterator"))
+            .respond_with(list_response(
+                &[
+                    endpoint("ep_1", Some(&["user.created"])),
+                    endpoint("ep_2", Some(&["user.created", "user.signup"])),
+                ],
+                Some("ep_2"),
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/app/app_1/endpoint"))
+            .and(query_param("iterator", "ep_2"))
+            .respond_with(list_response(
+                &[
+                    endpoint("ep_3", None),
+                    endpoint("ep_4", Some(&["invoice.paid"])),
+                ],
+                None,
+            ))
+            .mount(&server)
+            .await;
+
+        let svix = svix_client(&server);
+        let endpoints = svix
+            .endpoint()
+            .list_with_archived_event_types("app_1".to_owned())
+            .await
+            .unwrap();
+        let ids: Vec<_> = endpoints.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["ep_2", "ep_4"]);
+    }
+
+    #[cfg(all(feature = "wiremock", feature = "api-endpoint"))]
+    #[tokio::test]
+    async fn test_endpoint_clone_to() {
+        use wiremock::{
+            matchers::{body_json, body_partial_json, method, path},
+            Mock, MockServer,
+        };
+
+        use crate::{
+            api::{EndpointHeadersOut, EndpointOut, EndpointTransformationOut},
+            testing::wiremock::{json_response, svix_client},
+        };
+
+        let endpoint = |id: &str, disabled: bool| EndpointOut {
+            id: id.to_owned(),
+            url: "https://example.com/webhook".to_owned(),
+            description: "Orders".to_owned(),
+            disabled: Some(disabled),
+            version: 1,
+            ..Default::default()
+        };
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/app/app_1/endpoint/ep_1"))
+            .respond_with(json_response(200, &endpoint("ep_1", false)))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/app/app_1/endpoint/ep_1/headers"))
+            .respond_with(json_response(
+                200,
+                &EndpointHeadersOut::new(
+                    [("X-Tenant".to_owned(), "acme".to_owned())].into(),
+                    vec!["Authorization".to_owned()],
+                ),
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/app/app_1/endpoint/ep_1/transformation"))
+            .respond_with(json_response(200, &EndpointTransformationOut::default()))
+            .mount(&server)
+            .await;
+        // The copy is created disabled, and only enabled once its headers
+        // are set
+        Mock::given(method("POST"))
+            .and(path("/api/v1/app/app_1/endpoint"))
+            .and(body_partial_json(serde_json::json!({ "disabled": true })))
+            .respond_with(json_response(201, &endpoint("ep_2", true)))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/app/app_1/endpoint/ep_2/headers"))
+            .and(body_json(
+                serde_json::json!({ "headers": { "X-Tenant": "acme" } }),
+            ))
+            .respond_with(json_response(204, &serde_json::json!({})))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/api/v1/app/app_1/endpoint/ep_2"))
+            .and(body_json(serde_json::json!({ "disabled": false })))
+            .respond_with(json_response(200, &endpoint("ep_2", false)))
+            .expect(1)
+            .mount(&server)
+            .await;
+        // Once the headers can't be set, the copy is deleted again
+        Mock::given(method("DELETE"))
+            .and(path("/api/v1/app/app_1/endpoint/ep_2"))
+            .respond_with(json_response(204, &serde_json::json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let svix = svix_client(&server);
+        let clone = svix
+            .endpoint()
+            .clone_to(
+                "app_1".to_owned(),
+                "ep_1".to_owned(),
+                "https://example.org/webhook".to_owned(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(clone.endpoint, endpoint("ep_2", false));
+        assert_eq!(clone.sensitive_headers, ["Authorization"]);
+
+        svix.endpoint()
+            .clone_to(
+                "app_1".to_owned(),
+                "ep_1".to_owned(),
+                "https://example.org/webhook".to_owned(),
+            )
+            .await
+            .unwrap_err();
+    }
+
+    #[cfg(all(feature = "wiremock", feature = "api-message"))]
+    #[tokio::test]
+    async fn test_list_by_tags() {
+        use wiremock::{
+            matchers::{method, path, query_param, query_param_is_missing},
+            Mock, MockServer,
+        };
+
+        use crate::{
+            api::MessageOut,
+            testing::wiremock::{list_response, svix_client},
+        };
+
+        let message = |id: &str, timestamp: &str| MessageOut {
+            id: id.to_owned(),
+            event_type: "user.signup".to_owned(),
+            timestamp: timestamp.parse().unwrap(),
+            ..Default::default()
+        };
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/app/app_1/msg"))
+            .and(query_param("tag", "a"))
+            .and(query_param_is_missing("iterator"))
+            .respond_with(list_response(
+                &[message("msg_1", "2024-01-01T12:00:00Z")],
+                Some("msg_1"),
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/app/app_1/msg"))
+            .and(query_param("tag", "a"))
+            .and(query_param("iterator", "msg_1"))
+            .respond_with(list_response(
+                &[message("msg_3", "2024-01-01T14:00:00Z")],
+                None,
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/app/app_1/msg"))
+            .and(query_param("tag", "b"))
+            .respond_with(list_response(
+                &[
+                    message("msg_2", "2024-01-01T13:00:00Z"),
+                    message("msg_1", "2024-01-01T12:00:00Z"),
+                ],
+                None,
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let svix = svix_client(&server);
+        let messages = svix
+            .message()
+            .list_by_tags(
+                "app_1".to_owned(),
+                vec!["a".to_owned(), "b".to_owned()],
+                None,
+            )
+            .await
+            .unwrap();
+        let ids: Vec<_> = messages.iter().map(|msg| msg.id.as_str()).collect();
+        assert_eq!(ids, ["msg_3", "msg_2", "msg_1"]);
+    }
+
+    #[cfg(all(
+        feature = "wiremock",
+        feature = "api-endpoint",
+        feature = "api-message",
+        feature = "api-message-attempt"
+    ))]
+    #[tokio::test]
+    async fn test_list_by_status() {
+        use wiremock::{
+            matchers::{method, path, query_param},
+            Mock, MockServer,
+        };
+
+        use crate::{
+            api::{EndpointMessageOut, EndpointOut, MessageStatus},
+            testing::wiremock::{list_response, svix_client},
+        };
+
+        let message = |id: &str, timestamp: &str| EndpointMessageOut {
+            id: id.to_owned(),
+            event_type: "user.signup".to_owned(),
+            status: MessageStatus::Fail,
+            timestamp: timestamp.parse().unwrap(),
+            ..Default::default()
+        };
+        let endpoint = |id: &str| EndpointOut {
+            id: id.to_owned(),
+            url: "https://example.com".to_owned(),
+            ..Default::default()
+        };
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/app/app_1/endpoint"))
+            .respond_with(list_response(&[endpoint("ep_1"), endpoint("ep_2")], None))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/app/app_1/endpoint/ep_1/msg"))
+            .and(query_param("status", "2"))
+            .respond_with(list_response(
+                &[
+                    message("msg_1", "2024-01-01T12:00:00Z"),
+                    message("msg_3", "2024-01-01T14:00:00Z"),
+                ],
+                None,
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/app/app_1/endpoint/ep_2/msg"))
+            .and(query_param("status", "2"))
+            .respond_with(list_response(
+                &[
+                    message("msg_3", "2024-01-01T14:00:00Z"),
+                    message("msg_2", "2024-01-01T13:00:00Z"),
+                ],
+                None,
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let svix = svix_client(&server);
+        let messages = svix
+            .message()
+            .list_by_status("app_1".to_owned(), MessageStatus::Fail, None)
+            .await
+            .unwrap();
+        let ids: Vec<_> = messages.iter().map(|msg| msg.id.as_str()).collect();
+        assert_eq!(ids, ["msg_3", "msg_2", "msg_1"]);
+    }
+
+    #[cfg(all(feature = "wiremock", feature = "api-message-attempt"))]
+    #[tokio::test]
+    async fn test_export_ndjson_prefetch() {
+        use wiremock::{
+            matchers::{method, path, query_param, query_param_is_missing},
+            Mock, MockServer,
+        };
+
+        use crate::{
+            api::{MessageExportOptions, MessageOut},
+            testing::wiremock::{list_response, svix_client},
+        };
+
+        let server = MockServer::start().await;
+        let messages: Vec<_> = (1..=5)
+            .map(|i| MessageOut {
+                id: format!("msg_{i}"),
+                event_type: "user.signup".to_owned(),
+                ..Default::default()
+            })
+            .collect();
+        Mock::given(method("GET"))
+            .and(path("/api/v1/app/app_1/msg"))
+            .and(query_param_is_missing("iterator"))
+            .respond_with(list_response(&messages[..2], Some("msg_2")))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/app/app_1/msg"))
+            .and(query_param("iterator", "msg_2"))
+            .respond_with(list_response(&messages[2..4], Some("msg_4")))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/app/app_1/msg"))
+            .and(query_param("iterator", "msg_4"))
+            .respond_with(list_response(&messages[4..], None))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let svix = svix_client(&server);
+        let mut out = Vec::new();
+        let count = svix
+            .message()
+            .export_ndjson(
+                "app_1".to_owned(),
+                Some(MessageExportOptions {
+                    prefetch: true,
+                    ..Default::default()
+                }),
+                &mut out,
+            )
+            .await
+            .unwrap();
+        assert_eq!(count, 5);
+        let ids: Vec<_> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<MessageOut>(line).unwrap().id)
+            .collect();
+        assert_eq!(ids, ["msg_1", "msg_2", "msg_3", "msg_4", "msg_5"]);
     }
 }
@@ -0,0 +1,176 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Conversion of AsyncAPI documents to the OpenAPI documents accepted by the
+//! event type importer.
+
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value};
+
+use crate::error::{Error, Result};
+
+/// Converts an AsyncAPI 2.x or 3.x document to an OpenAPI document with a
+/// webhook per message.
+///
+/// The event type name is the message's `name`, falling back to its key in
+/// the document (or the channel name for anonymous messages). The payload
+/// schema becomes the webhook's request body, and `components.schemas` is
+/// carried over so references into it keep working.
+pub(super) fn to_openapi(spec: &Value) -> Result<HashMap<String, Value>> {
+    let version = spec
+        .get("asyncapi")
+        .and_then(Value::as_str)
+        .ok_or_else(|| Error::Generic("not an AsyncAPI document".to_owned()))?;
+
+    let mut messages = Vec::new();
+    let channels = spec.get("channels").and_then(Value::as_object);
+    for (channel_name, channel) in channels.into_iter().flatten() {
+        if version.starts_with("2.") {
+            for operation in ["subscribe", "publish"] {
+                if let Some(message) = channel.pointer(&format!("/{operation}/message")) {
+                    match message.get("oneOf").and_then(Value::as_array) {
+                        Some(one_of) => {
+                            messages.extend(one_of.iter().map(|m| (channel_name.as_str(), m)))
+                        }
+                        None => messages.push((channel_name.as_str(), message)),
+                    }
+                }
+            }
+        } else {
+            let channel_messages = channel.get("messages").and_then(Value::as_object);
+            messages.extend(
+                channel_messages
+                    .into_iter()
+                    .flatten()
+                    .map(|(key, message)| (key.as_str(), message)),
+            );
+        }
+    }
+
+    let mut webhooks = Map::new();
+    for (fallback_name, message) in messages {
+        let (ref_name, message) = resolve(spec, message)?;
+        let name = message
+            .get("name")
+            .and_then(Value::as_str)
+            .or(ref_name)
+            .unwrap_or(fallback_name);
+        let description = message
+            .get("description")
+            .or_else(|| message.get("summary"))
+            .cloned()
+            .unwrap_or_else(|| json!(""));
+        let payload = message
+            .get("payload")
+            .map(|payload| resolve(spec, payload).map(|(_, payload)| payload.clone()))
+            .transpose()?
+            .unwrap_or_else(|| json!({}));
+
+        webhooks.insert(
+            name.to_owned(),
+            json!({
+                "post": {
+                    "description": description,
+                    "requestBody": {
+                        "content": {
+                            "application/json": { "schema": payload }
+                        }
+                    }
+                }
+            }),
+        );
+    }
+
+    let schemas = spec
+        .pointer("/components/schemas")
+        .cloned()
+        .unwrap_or_else(|| json!({}));
+    Ok(HashMap::from([
+        ("openapi".to_owned(), json!("3.1.0")),
+        (
+            "info".to_owned(),
+            spec.get("info").cloned().unwrap_or_else(|| json!({})),
+        ),
+        ("webhooks".to_owned(), Value::Object(webhooks)),
+        ("components".to_owned(), json!({ "schemas": schemas })),
+    ]))
+}
+
+/// Follows a local `$ref`, returning the last path segment of the reference
+/// along with the referenced value.
+fn resolve<'a>(spec: &'a Value, value: &'a Value) -> Result<(Option<&'a str>, &'a Value)> {
+    let Some(reference) = value.get("$ref").and_then(Value::as_str) else {
+        return Ok((None, value));
+    };
+    let target = reference
+        .strip_prefix('#')
+        .and_then(|pointer| spec.pointer(pointer))
+        .ok_or_else(|| Error::Generic(format!("unresolvable reference {reference:?}")))?;
+    Ok((reference.rsplit('/').next(), target))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::to_openapi;
+
+    #[test]
+    fn test_to_openapi() {
+        let user_signup = json!({
+            "type": "object",
+            "properties": { "email": { "type": "string" } }
+        });
+        let expected_schema = |spec: &std::collections::HashMap<String, serde_json::Value>| {
+            spec["webhooks"]["user.signup"]["post"]["requestBody"]["content"]["application/json"]
+                ["schema"]
+                .clone()
+        };
+
+        let v2 = to_openapi(&json!({
+            "asyncapi": "2.6.0",
+            "info": { "title": "Users", "version": "1.0.0" },
+            "channels": {
+                "user/signedup": {
+                    "subscribe": {
+                        "message": { "$ref": "#/components/messages/user.signup" }
+                    }
+                }
+            },
+            "components": {
+                "messages": {
+                    "user.signup": {
+                        "summary": "A user signed up.",
+                        "payload": { "$ref": "#/components/schemas/UserSignup" }
+                    }
+                },
+                "schemas": { "UserSignup": user_signup }
+            }
+        }))
+        .unwrap();
+        assert_eq!(
+            v2["webhooks"]["user.signup"]["post"]["description"],
+            "A user signed up."
+        );
+        assert_eq!(expected_schema(&v2), user_signup);
+        assert_eq!(v2["components"]["schemas"]["UserSignup"], user_signup);
+
+        let v3 = to_openapi(&json!({
+            "asyncapi": "3.0.0",
+            "info": { "title": "Users", "version": "1.0.0" },
+            "channels": {
+                "userSignedUp": {
+                    "address": "user/signedup",
+                    "messages": {
+                        "signup": { "name": "user.signup", "payload": user_signup }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+        assert_eq!(expected_schema(&v3), user_signup);
+
+        assert!(to_openapi(&json!({ "openapi": "3.1.0" })).is_err());
+    }
+}
@@ -0,0 +1,153 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Helpers for sending many requests at once.
+//!
+//! All requests go through the client's connection pool, with a bounded
+//! number of them in flight, so fetching thousands of objects doesn't spawn
+//! thousands of tasks or open thousands of connections.
+
+use std::future::Future;
+
+use futures_util::StreamExt as _;
+
+#[cfg(feature = "api-application")]
+use super::{Application, ApplicationOut};
+#[cfg(feature = "api-endpoint")]
+use super::{Endpoint, EndpointOut};
+use super::{Message, MessageOut};
+use crate::{error::Result, Configuration};
+
+const DEFAULT_CONCURRENCY: usize = 8;
+
+/// Fans out requests with bounded concurrency, returned by
+/// [`Svix::batch`](super::Svix::batch).
+///
+/// The results are in the same order as the inputs, and one failed request
+/// doesn't stop the others.
+pub struct Batch<'a> {
+    cfg: &'a Configuration,
+    concurrency: usize,
+}
+
+impl<'a> Batch<'a> {
+    pub(super) fn new(cfg: &'a Configuration) -> Self {
+        Self {
+            cfg,
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// Sets how many requests are in flight at the same time.
+    ///
+    /// Default: 8.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Runs the given futures, a few at a time, and returns their outputs in
+    /// order.
+    ///
+    /// This works with any of the API methods, e.g.
+    /// `batch.join(ids.iter().map(|id| endpoint_api.get_secret(app_id.clone(), id.clone())))`.
+    pub async fn join<F: Future>(&self, futures: impl IntoIterator<Item = F>) -> Vec<F::Output> {
+        futures_util::stream::iter(futures)
+            .buffered(self.concurrency)
+            .collect()
+            .await
+    }
+
+    /// Gets the applications with the given IDs or UIDs.
+    #[cfg(feature = "api-application")]
+    pub async fn get_applications(&self, app_ids: Vec<String>) -> Vec<Result<ApplicationOut>> {
+        let application = Application::new(self.cfg);
+        self.join(app_ids.into_iter().map(|app_id| application.get(app_id)))
+            .await
+    }
+
+    /// Gets the application's endpoints with the given IDs or UIDs.
+    #[cfg(feature = "api-endpoint")]
+    pub async fn get_endpoints(
+        &self,
+        app_id: String,
+        endpoint_ids: Vec<String>,
+    ) -> Vec<Result<EndpointOut>> {
+        let endpoint = Endpoint::new(self.cfg);
+        self.join(
+            endpoint_ids
+                .into_iter()
+                .map(|endpoint_id| endpoint.get(app_id.clone(), endpoint_id)),
+        )
+        .await
+    }
+
+    /// Gets the application's messages with the given IDs or event IDs.
+    pub async fn get_messages(
+        &self,
+        app_id: String,
+        msg_ids: Vec<String>,
+    ) -> Vec<Result<MessageOut>> {
+        let message = Message::new(self.cfg);
+        self.join(
+            msg_ids
+                .into_iter()
+                .map(|msg_id| message.get(app_id.clone(), msg_id)),
+        )
+        .await
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use crate::{
+        api::{ApplicationIn, MessageIn},
+        error::Error,
+        testing::MockSvixServer,
+    };
+
+    #[tokio::test]
+    async fn test_get_messages() {
+        let server = MockSvixServer::start().await.unwrap();
+        let svix = server.client();
+        let app = svix
+            .application()
+            .create(ApplicationIn::new("My app".to_owned()), None)
+            .await
+            .unwrap();
+        let mut ids = Vec::new();
+        for i in 0..5 {
+            let msg = svix
+                .message()
+                .create(
+                    app.id.clone(),
+                    MessageIn::new(
+                        "user.signup".parse().unwrap(),
+                        serde_json::json!({ "i": i }),
+                    ),
+                    None,
+                )
+                .await
+                .unwrap();
+            ids.push(msg.id);
+        }
+        ids.insert(2, "msg_missing".to_owned());
+
+        let results = svix
+            .batch()
+            .with_concurrency(2)
+            .get_messages(app.id, ids.clone())
+            .await;
+        assert_eq!(results.len(), ids.len());
+        for (id, result) in ids.iter().zip(&results) {
+            match result {
+                Ok(msg) => assert_eq!(&msg.id, id),
+                Err(Error::Http(e)) => {
+                    assert_eq!(id, "msg_missing");
+                    assert_eq!(e.status, 404);
+                }
+                Err(e) => panic!("unexpected error: {e}"),
+            }
+        }
+    }
+}
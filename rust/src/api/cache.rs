@@ -0,0 +1,144 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Client-side caching of responses with cache validators.
+//!
+//! With [`SvixOptions::cache`](super::SvixOptions::cache) set, responses to
+//! `GET` requests which have an `ETag` or `Last-Modified` header are stored,
+//! and sent again as `If-None-Match` or `If-Modified-Since`. When nothing
+//! changed, the server responds with an empty `304 Not Modified` and the
+//! stored body is used instead, which saves bandwidth for clients polling
+//! applications or endpoints.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use hyper::body::Bytes;
+
+/// How many responses a [`MemoryCacheStore`] holds by default.
+const DEFAULT_CAPACITY: usize = 64;
+
+/// A response stored by a [`CacheStore`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct CachedResponse {
+    /// The `ETag` header of the response.
+    pub etag: Option<String>,
+    /// The `Last-Modified` header of the response.
+    pub last_modified: Option<String>,
+    /// The JSON body of the response.
+    pub body: Bytes,
+}
+
+/// Where cached responses are stored.
+///
+/// Keys are made of the URL of the request and a fingerprint of the token
+/// it was sent with, so a store can be shared by clients with different
+/// tokens.
+pub trait CacheStore: Send + Sync {
+    fn get(&self, key: &str) -> Option<CachedResponse>;
+
+    fn put(&self, key: String, response: CachedResponse);
+}
+
+/// An in-memory [`CacheStore`], which is cleared when it's full.
+pub struct MemoryCacheStore {
+    capacity: usize,
+    entries: Mutex<HashMap<String, CachedResponse>>,
+}
+
+impl MemoryCacheStore {
+    /// Creates a store holding up to `capacity` responses.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Mutex::default(),
+        }
+    }
+}
+
+impl Default for MemoryCacheStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl CacheStore for MemoryCacheStore {
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        self.entries.lock().unwrap().get(key).cloned()
+    }
+
+    fn put(&self, key: String, response: CachedResponse) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            entries.clear();
+        }
+        entries.insert(key, response);
+    }
+}
+
+#[cfg(all(test, feature = "wiremock", feature = "api-application"))]
+mod tests {
+    use std::sync::Arc;
+
+    use wiremock::{
+        matchers::{header_exists, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::MemoryCacheStore;
+    use crate::{
+        api::{ApplicationOut, Svix, SvixOptions},
+        testing::wiremock::json_response,
+    };
+
+    #[tokio::test]
+    async fn test_conditional_get() {
+        let server = MockServer::start().await;
+        let app = ApplicationOut {
+            id: "app_1".to_owned(),
+            name: "My app".to_owned(),
+            ..Default::default()
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/v1/app/app_1"))
+            .and(header_exists("if-modified-since"))
+            .respond_with(ResponseTemplate::new(304))
+            .with_priority(1)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/app/app_1"))
+            .respond_with(
+                json_response(200, &app)
+                    .insert_header("last-modified", "Wed, 21 Oct 2015 07:28:00 GMT"),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let store = Arc::new(MemoryCacheStore::default());
+        let svix = Svix::new(
+            "testsk_a".to_owned(),
+            Some(SvixOptions {
+                server_url: Some(server.uri()),
+                cache: Some(store),
+                ..Default::default()
+            }),
+        );
+        for _ in 0..2 {
+            assert_eq!(
+                svix.application().get("app_1".to_owned()).await.unwrap(),
+                app
+            );
+        }
+
+        // Another token doesn't get the responses cached for the first one
+        let other = svix.with_token("testsk_b".to_owned());
+        for _ in 0..2 {
+            assert_eq!(
+                other.application().get("app_1".to_owned()).await.unwrap(),
+                app
+            );
+        }
+    }
+}
@@ -0,0 +1,103 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Client-side validation of endpoint URLs, so an endpoint the server would
+//! reject (or deliver to insecurely) fails before the request is sent.
+
+use url::{Host, Url};
+
+use crate::{
+    error::{Error, Result},
+    models::{EndpointIn, EndpointOut, EndpointUpdate},
+};
+
+/// Parses an endpoint URL, checking that it uses `https` and has no
+/// fragment.
+///
+/// `http` is allowed for `localhost` and loopback addresses, and for any
+/// host with `allow_http`, e.g. for self-hosted servers delivering within a
+/// private network.
+pub(crate) fn parse_endpoint_url(value: &str, allow_http: bool) -> Result<Url> {
+    let url = Url::parse(value).map_err(|e| invalid(value, &e.to_string()))?;
+    match url.scheme() {
+        "https" => {}
+        "http" if allow_http || is_local(&url) => {}
+        "http" => {
+            return Err(invalid(
+                value,
+                "must use https, http is only allowed for localhost \
+                 (or with `SvixOptions::allow_http_endpoints`)",
+            ))
+        }
+        scheme => return Err(invalid(value, &format!("unsupported scheme `{scheme}`"))),
+    }
+    if url.fragment().is_some() {
+        return Err(invalid(value, "must not have a fragment"));
+    }
+    Ok(url)
+}
+
+fn is_local(url: &Url) -> bool {
+    match url.host() {
+        Some(Host::Domain(domain)) => domain == "localhost" || domain.ends_with(".localhost"),
+        Some(Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+fn invalid(url: &str, reason: &str) -> Error {
+    Error::Generic(format!("invalid endpoint URL `{url}`: {reason}"))
+}
+
+impl EndpointIn {
+    /// The endpoint's URL, parsed.
+    pub fn parsed_url(&self) -> Result<Url> {
+        Url::parse(&self.url).map_err(|e| invalid(&self.url, &e.to_string()))
+    }
+}
+
+impl EndpointUpdate {
+    /// The endpoint's URL, parsed.
+    pub fn parsed_url(&self) -> Result<Url> {
+        Url::parse(&self.url).map_err(|e| invalid(&self.url, &e.to_string()))
+    }
+}
+
+impl EndpointOut {
+    /// The endpoint's URL, parsed.
+    pub fn parsed_url(&self) -> Result<Url> {
+        Url::parse(&self.url).map_err(|e| invalid(&self.url, &e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_endpoint_url;
+
+    #[test]
+    fn test_parse_endpoint_url() {
+        for url in [
+            "https://example.com/webhooks",
+            "http://localhost:8080/webhooks",
+            "http://127.0.0.1/webhooks",
+            "http://[::1]/webhooks",
+        ] {
+            assert!(parse_endpoint_url(url, false).is_ok(), "{url}");
+        }
+        assert!(parse_endpoint_url("http://internal.example/webhooks", true).is_ok());
+
+        for (url, reason) in [
+            ("example.com/webhooks", "relative URL without a base"),
+            ("http://example.com/webhooks", "must use https"),
+            ("ftp://example.com/webhooks", "unsupported scheme `ftp`"),
+            (
+                "https://example.com/webhooks#events",
+                "must not have a fragment",
+            ),
+        ] {
+            let err = parse_endpoint_url(url, false).unwrap_err().to_string();
+            assert!(err.contains(reason), "{url}: {err}");
+        }
+    }
+}
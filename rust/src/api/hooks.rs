@@ -0,0 +1,436 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Callbacks invoked around every request the client sends.
+//!
+//! With [`SvixOptions::on_request_start`](super::SvixOptions::on_request_start)
+//! and [`SvixOptions::on_request_end`](super::SvixOptions::on_request_end)
+//! set, the callbacks get structured details of each request, for logging
+//! or metrics:
+//!
+//! ```ignore
+//! let options = SvixOptions {
+//!     on_request_end: Some(Arc::new(|end: &RequestEnd<'_>| {
+//!         tracing::info!(endpoint = end.endpoint, status = ?end.status, elapsed = ?end.elapsed);
+//!     })),
+//!     ..Default::default()
+//! };
+//! ```
+//!
+//! [`SvixOptions::on_audit`](super::SvixOptions::on_audit) is called for
+//! every request which changes something (`POST`, `PUT`, `PATCH` and
+//! `DELETE`), with the operation and the IDs of the resources it changed,
+//! for an audit trail of changes to the webhook configuration. Request and
+//! response bodies are never passed to it, so secrets like endpoint signing
+//! secrets don't end up in the trail.
+
+use std::{sync::Arc, time::Duration};
+
+use http1::HeaderMap;
+
+/// Called before a request is sent.
+pub type RequestStartHook = Arc<dyn Fn(&RequestStart<'_>) + Send + Sync>;
+
+/// Called once a request finished, successfully or not.
+pub type RequestEndHook = Arc<dyn Fn(&RequestEnd<'_>) + Send + Sync>;
+
+/// Called once a request which changes something finished, successfully or
+/// not.
+pub type AuditHook = Arc<dyn Fn(&AuditEvent<'_>) + Send + Sync>;
+
+/// A request which is about to be sent.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct RequestStart<'a> {
+    /// The HTTP method, e.g. `GET`.
+    pub method: &'a str,
+    /// The API endpoint, as the path template from the API reference, e.g.
+    /// `/api/v1/app/{app_id}`.
+    pub endpoint: &'a str,
+    /// Which attempt at sending the request this is, starting at 1.
+    pub attempt: u32,
+    /// The request's idempotency key, if it has one.
+    pub idempotency_key: Option<&'a str>,
+}
+
+/// A request which finished.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct RequestEnd<'a> {
+    /// The HTTP method, e.g. `GET`.
+    pub method: &'a str,
+    /// The API endpoint, as the path template from the API reference, e.g.
+    /// `/api/v1/app/{app_id}`.
+    pub endpoint: &'a str,
+    /// Which attempt at sending the request this was, starting at 1.
+    pub attempt: u32,
+    /// The request's idempotency key, if it has one.
+    pub idempotency_key: Option<&'a str>,
+    /// The status of the response, or `None` if there was none, e.g. because
+    /// the connection failed or the request timed out.
+    pub status: Option<u16>,
+    /// How long it took from sending the request until the response body was
+    /// received.
+    pub elapsed: Duration,
+    /// The ID the API assigned to the request, from the `svix-req-id`
+    /// response header, for asking Svix support about it.
+    pub request_id: Option<&'a str>,
+    /// The rate limit quota left after the request, if the API reported it.
+    pub rate_limit: Option<RateLimit>,
+    /// Where the time went, with [`SvixOptions::debug`](super::SvixOptions::debug)
+    /// set and if there was a response.
+    pub timings: Option<RequestTimings>,
+}
+
+/// How long the stages of a request took.
+///
+/// The connection stages are only set when the request opened a new
+/// connection, rather than reusing one from the pool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RequestTimings {
+    /// Resolving the server's name, unless it's an IP address.
+    pub dns: Option<Duration>,
+    /// Opening the TCP connection.
+    pub connect: Option<Duration>,
+    /// The TLS handshake, for `https` URLs.
+    pub tls: Option<Duration>,
+    /// From sending the request (including opening the connection) until
+    /// the response headers were received.
+    pub time_to_first_byte: Duration,
+    /// Receiving the response body.
+    pub body: Duration,
+}
+
+/// A request which changed something, or tried to.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct AuditEvent<'a> {
+    /// The operation ID from the API reference, e.g. `v1.endpoint.update`.
+    pub operation: Option<&'a str>,
+    /// The HTTP method, e.g. `DELETE`.
+    pub method: &'a str,
+    /// The API endpoint, as the path template from the API reference, e.g.
+    /// `/api/v1/app/{app_id}/endpoint/{endpoint_id}`.
+    pub endpoint: &'a str,
+    /// The IDs in the request's path, by the name of their parameter, e.g.
+    /// `[("app_id", "app_1"), ("endpoint_id", "ep_1")]`.
+    pub resource_ids: &'a [(&'a str, &'a str)],
+    /// The ID of the resource in the response, e.g. of the endpoint which
+    /// was created.
+    pub response_id: Option<&'a str>,
+    /// Whether the request succeeded.
+    pub outcome: AuditOutcome,
+    /// The status of the response, or `None` if there was none.
+    pub status: Option<u16>,
+    /// The ID the API assigned to the request, from the `svix-req-id`
+    /// response header.
+    pub request_id: Option<&'a str>,
+    /// The request's idempotency key, if it has one.
+    pub idempotency_key: Option<&'a str>,
+}
+
+/// Whether an audited request succeeded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AuditOutcome {
+    Succeeded,
+    /// The API rejected the request, or there was no response. Requests
+    /// without a response may still have been applied.
+    Failed,
+}
+
+/// The rate limit quota the API reported with a response, from its
+/// `x-ratelimit-*` headers.
+///
+/// Senders can use it to slow down before they're limited, see also
+/// [`Svix::rate_limit`](super::Svix::rate_limit).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RateLimit {
+    /// How many requests are allowed per window.
+    pub limit: Option<u64>,
+    /// How many requests are left in the current window.
+    pub remaining: u64,
+    /// How long until the quota is reset.
+    pub reset: Option<Duration>,
+}
+
+impl RateLimit {
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header = |name: &str| -> Option<u64> { headers.get(name)?.to_str().ok()?.parse().ok() };
+        Some(Self {
+            limit: header("x-ratelimit-limit"),
+            remaining: header("x-ratelimit-remaining")?,
+            reset: header("x-ratelimit-reset")
+                .or_else(|| header("retry-after"))
+                .map(Duration::from_secs),
+        })
+    }
+}
+
+#[cfg(all(test, feature = "wiremock", feature = "api-application"))]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::RateLimit;
+    use crate::{
+        api::{ApplicationOut, Svix, SvixOptions},
+        testing::wiremock::json_response,
+    };
+
+    #[tokio::test]
+    async fn test_request_hooks() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/app/app_1"))
+            .respond_with(
+                json_response(200, &ApplicationOut::default())
+                    .insert_header("x-ratelimit-limit", "100")
+                    .insert_header("x-ratelimit-remaining", "42")
+                    .insert_header("x-ratelimit-reset", "10"),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/app/app_2"))
+            .respond_with(ResponseTemplate::new(404).insert_header("svix-req-id", "req_1"))
+            .mount(&server)
+            .await;
+
+        let started = Arc::new(Mutex::new(Vec::new()));
+        let ended = Arc::new(Mutex::new(Vec::new()));
+        let svix = Svix::new(
+            "testsk_hooks".to_owned(),
+            Some(SvixOptions {
+                server_url: Some(server.uri()),
+                on_request_start: Some(Arc::new({
+                    let started = started.clone();
+                    move |start| {
+                        started
+                            .lock()
+                            .unwrap()
+                            .push((start.method.to_owned(), start.endpoint.to_owned()));
+                    }
+                })),
+                on_request_end: Some(Arc::new({
+                    let ended = ended.clone();
+                    move |end| {
+                        ended.lock().unwrap().push((
+                            end.attempt,
+                            end.status,
+                            end.request_id.map(ToOwned::to_owned),
+                            end.rate_limit,
+                        ))
+                    }
+                })),
+                ..Default::default()
+            }),
+        );
+        assert_eq!(svix.rate_limit(), None);
+        svix.application().get("app_1".to_owned()).await.unwrap();
+        let rate_limit = RateLimit {
+            limit: Some(100),
+            remaining: 42,
+            reset: Some(Duration::from_secs(10)),
+        };
+        assert_eq!(svix.rate_limit(), Some(rate_limit));
+        svix.application()
+            .get("app_2".to_owned())
+            .await
+            .unwrap_err();
+
+        let endpoint = ("GET".to_owned(), "/api/v1/app/{app_id}".to_owned());
+        assert_eq!(*started.lock().unwrap(), [endpoint.clone(), endpoint]);
+        assert_eq!(
+            *ended.lock().unwrap(),
+            [
+                (1, Some(200), None, Some(rate_limit)),
+                (1, Some(404), Some("req_1".to_owned()), None)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key() {
+        use crate::{
+            api::{ApplicationIn, PostOptions},
+            error::Error,
+        };
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/app"))
+            .respond_with(ResponseTemplate::new(409))
+            .mount(&server)
+            .await;
+
+        let keys = Arc::new(Mutex::new(Vec::new()));
+        let svix = Svix::new(
+            "testsk_hooks".to_owned(),
+            Some(SvixOptions {
+                server_url: Some(server.uri()),
+                on_request_end: Some(Arc::new({
+                    let keys = keys.clone();
+                    move |end| {
+                        keys.lock()
+                            .unwrap()
+                            .push(end.idempotency_key.map(ToOwned::to_owned))
+                    }
+                })),
+                ..Default::default()
+            }),
+        );
+        let err = svix
+            .application()
+            .create(
+                ApplicationIn::new("My app".to_owned()),
+                Some(PostOptions {
+                    idempotency_key: Some("key_1".to_owned()),
+                }),
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(*keys.lock().unwrap(), [Some("key_1".to_owned())]);
+        assert!(err.to_string().ends_with("(idempotency key: key_1)"));
+        let Error::Http(e) = err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(e.idempotency_key.as_deref(), Some("key_1"));
+    }
+
+    #[cfg(all(feature = "api-endpoint", feature = "api-poller"))]
+    #[tokio::test]
+    async fn test_audit() {
+        use crate::api::{AuditOutcome, EndpointIn, EndpointOut, PollingEndpointConsumerSeekIn};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/app/app_1/endpoint"))
+            .respond_with(json_response(
+                201,
+                &EndpointOut {
+                    id: "ep_1".to_owned(),
+                    ..Default::default()
+                },
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/app/app_1/endpoint/ep_1"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/api/v1/app/app_1/endpoint/ep_1"))
+            .respond_with(ResponseTemplate::new(404).insert_header("svix-req-id", "req_1"))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(
+                "/api/v1/app/app_1/poller/poll_1/consumer/consumer_1/seek",
+            ))
+            .respond_with(json_response(
+                200,
+                &serde_json::json!({ "iterator": "it_1" }),
+            ))
+            .mount(&server)
+            .await;
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let svix = Svix::new(
+            "testsk_audit".to_owned(),
+            Some(SvixOptions {
+                server_url: Some(server.uri()),
+                on_audit: Some(Arc::new({
+                    let events = events.clone();
+                    move |event| {
+                        let resource_ids: Vec<_> = event
+                            .resource_ids
+                            .iter()
+                            .map(|(name, id)| format!("{name}={id}"))
+                            .collect();
+                        events.lock().unwrap().push((
+                            event.operation.map(ToOwned::to_owned),
+                            resource_ids,
+                            event.response_id.map(ToOwned::to_owned),
+                            event.outcome,
+                            event.request_id.map(ToOwned::to_owned),
+                        ))
+                    }
+                })),
+                ..Default::default()
+            }),
+        );
+        svix.endpoint()
+            .create(
+                "app_1".to_owned(),
+                EndpointIn::new("https://example.com".to_owned()),
+                None,
+            )
+            .await
+            .unwrap();
+        svix.endpoint()
+            .get("app_1".to_owned(), "ep_1".to_owned())
+            .await
+            .unwrap_err();
+        svix.endpoint()
+            .delete("app_1".to_owned(), "ep_1".to_owned())
+            .await
+            .unwrap_err();
+        // Requests which aren't generated from the spec are audited too
+        svix.poller()
+            .consumer_seek(
+                "app_1".to_owned(),
+                "poll_1".to_owned(),
+                "consumer_1".to_owned(),
+                PollingEndpointConsumerSeekIn {
+                    after: Default::default(),
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        // Reading the endpoint isn't audited
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                (
+                    Some("v1.endpoint.create".to_owned()),
+                    vec!["app_id=app_1".to_owned()],
+                    Some("ep_1".to_owned()),
+                    AuditOutcome::Succeeded,
+                    None,
+                ),
+                (
+                    Some("v1.endpoint.delete".to_owned()),
+                    vec!["app_id=app_1".to_owned(), "endpoint_id=ep_1".to_owned()],
+                    None,
+                    AuditOutcome::Failed,
+                    Some("req_1".to_owned()),
+                ),
+                (
+                    Some("v1.message.poller.consumer-seek".to_owned()),
+                    vec![
+                        "app_id=app_1".to_owned(),
+                        "consumer_id=consumer_1".to_owned(),
+                        "sink_id=poll_1".to_owned(),
+                    ],
+                    None,
+                    AuditOutcome::Succeeded,
+                    None,
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,84 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Event type schemas built from the types the payloads are serialized from,
+//! so the event catalog can't drift from what's actually sent:
+//!
+//! ```ignore
+//! #[derive(Serialize, JsonSchema)]
+//! struct UserSignup {
+//!     email: String,
+//! }
+//!
+//! let event_type_in = EventTypeIn::new("A user signed up".to_owned(), "user.signup".parse()?)
+//!     .with_schema::<UserSignup>();
+//! ```
+
+use std::collections::HashMap;
+
+use schemars::{generate::SchemaSettings, JsonSchema};
+use serde_json::Value;
+
+use crate::models::{EventTypeIn, EventTypeUpdate};
+
+/// The schemas of an event type whose payloads are `T`s, as version `1`.
+///
+/// Svix expects draft 7 JSON schemas, so they're generated with those
+/// settings rather than `schemars`' default.
+pub fn event_type_schemas<T: JsonSchema>() -> HashMap<String, Value> {
+    let schema = SchemaSettings::draft07()
+        .into_generator()
+        .into_root_schema_for::<T>();
+    [("1".to_owned(), schema.to_value())].into()
+}
+
+impl EventTypeIn {
+    /// Sets the event type's schemas to the one of `T`, see
+    /// [`event_type_schemas`].
+    pub fn with_schema<T: JsonSchema>(self) -> Self {
+        Self {
+            schemas: Some(event_type_schemas::<T>()),
+            ..self
+        }
+    }
+}
+
+impl EventTypeUpdate {
+    /// Sets the event type's schemas to the one of `T`, see
+    /// [`event_type_schemas`].
+    pub fn with_schema<T: JsonSchema>(self) -> Self {
+        Self {
+            schemas: Some(event_type_schemas::<T>()),
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use schemars::JsonSchema;
+
+    use crate::models::EventTypeIn;
+
+    #[test]
+    fn test_with_schema() {
+        /// A user signed up.
+        #[derive(JsonSchema)]
+        #[allow(dead_code)]
+        struct UserSignup {
+            email: String,
+            referrer: Option<String>,
+        }
+
+        let event_type_in = EventTypeIn::new(
+            "A user signed up".to_owned(),
+            "user.signup".parse().unwrap(),
+        )
+        .with_schema::<UserSignup>();
+        let schema = &event_type_in.schemas.unwrap()["1"];
+        assert_eq!(schema["$schema"], "http://json-schema.org/draft-07/schema#");
+        assert_eq!(schema["title"], "UserSignup");
+        assert_eq!(schema["required"], serde_json::json!(["email"]));
+        assert_eq!(schema["properties"]["email"]["type"], "string");
+    }
+}
@@ -0,0 +1,263 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Names which the API restricts, checked when they're constructed so
+//! invalid ones fail before a request is sent.
+
+use std::{fmt, ops::Deref, str::FromStr};
+
+use crate::error::{Error, Result};
+
+/// The longest event type name the API accepts.
+const MAX_EVENT_TYPE_NAME_LEN: usize = 256;
+
+/// The longest channel or tag the API accepts.
+const MAX_CHANNEL_LEN: usize = 128;
+
+/// The name of an event type, e.g. `user.signup`.
+///
+/// Names are 1 to 256 ASCII letters, digits, `-`, `_` and `.`. They're
+/// checked when constructed (including when deserialized):
+///
+/// ```
+/// use svix::api::EventTypeName;
+///
+/// assert!(EventTypeName::new("user.signup").is_ok());
+/// assert!(EventTypeName::new("user signup").is_err());
+/// ```
+///
+/// The default name is empty, which isn't valid, so that models containing
+/// names can implement `Default`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct EventTypeName(String);
+
+impl EventTypeName {
+    pub fn new(name: impl Into<String>) -> Result<Self> {
+        let name = name.into();
+        check(
+            "event type name",
+            &name,
+            MAX_EVENT_TYPE_NAME_LEN,
+            &['-', '_', '.'],
+        )?;
+        Ok(Self(name))
+    }
+}
+
+/// A channel a message is sent on, e.g. `project_123`.
+///
+/// Channels are 1 to 128 ASCII letters, digits, `-`, `_`, `.` and `:`, and
+/// a message can have at most [`Channel::MAX_PER_MESSAGE`] of them.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Channel(String);
+
+impl Channel {
+    /// The most channels a single message can have.
+    pub const MAX_PER_MESSAGE: usize = 10;
+
+    pub fn new(name: impl Into<String>) -> Result<Self> {
+        let name = name.into();
+        check("channel", &name, MAX_CHANNEL_LEN, &['-', '_', '.', ':'])?;
+        Ok(Self(name))
+    }
+}
+
+/// A tag of a message, which messages can be listed by.
+///
+/// Tags are 1 to 128 ASCII letters, digits, `-`, `_` and `.`, and a message
+/// can have at most [`Tag::MAX_PER_MESSAGE`] of them.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Tag(String);
+
+impl Tag {
+    /// The most tags a single message can have.
+    pub const MAX_PER_MESSAGE: usize = 5;
+
+    pub fn new(name: impl Into<String>) -> Result<Self> {
+        let name = name.into();
+        check("tag", &name, MAX_CHANNEL_LEN, &['-', '_', '.'])?;
+        Ok(Self(name))
+    }
+}
+
+/// Checks that `name` is 1 to `max_len` ASCII letters, digits and
+/// `punctuation`.
+fn check(kind: &str, name: &str, max_len: usize, punctuation: &[char]) -> Result<()> {
+    let invalid = |reason: &str| Error::Generic(format!("invalid {kind} {name:?}: {reason}"));
+    if name.is_empty() {
+        return Err(invalid("must not be empty"));
+    }
+    if name.len() > max_len {
+        return Err(invalid(&format!(
+            "must be at most {max_len} characters long"
+        )));
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || punctuation.contains(c)))
+    {
+        let allowed: Vec<_> = punctuation.iter().map(|c| format!("{c:?}")).collect();
+        return Err(invalid(&format!(
+            "{c:?} isn't allowed, only letters, digits and {} are",
+            allowed.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that `message_in` doesn't have more channels or tags than the API
+/// accepts.
+#[cfg(feature = "api-message")]
+pub(crate) fn check_message_in(message_in: &crate::models::MessageIn) -> Result<()> {
+    let too_many = |what: &str, count: usize, max: usize| {
+        Error::Generic(format!(
+            "invalid message: has {count} {what}, at most {max} are allowed"
+        ))
+    };
+    let channels = message_in.channels.as_ref().map_or(0, Vec::len);
+    if channels > Channel::MAX_PER_MESSAGE {
+        return Err(too_many("channels", channels, Channel::MAX_PER_MESSAGE));
+    }
+    let tags = message_in.tags.as_ref().map_or(0, Vec::len);
+    if tags > Tag::MAX_PER_MESSAGE {
+        return Err(too_many("tags", tags, Tag::MAX_PER_MESSAGE));
+    }
+    Ok(())
+}
+
+/// Implements the string conversions and comparisons shared by all names.
+macro_rules! impl_name {
+    ($($name:ident),*) => {$(
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self> {
+                Self::new(s)
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = Error;
+
+            fn try_from(name: String) -> Result<Self> {
+                Self::new(name)
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = Error;
+
+            fn try_from(name: &str) -> Result<Self> {
+                Self::new(name)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(name: $name) -> Self {
+                name.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+    )*};
+}
+
+impl_name!(EventTypeName, Channel, Tag);
+
+#[cfg(test)]
+mod tests {
+    use super::{Channel, EventTypeName, Tag};
+
+    #[test]
+    fn test_event_type_name() {
+        for name in ["user.signup", "invoice-paid_v2", &"a".repeat(256)] {
+            assert_eq!(EventTypeName::new(name).unwrap(), name);
+        }
+        for (name, reason) in [
+            ("", "must not be empty"),
+            ("user signup", "' ' isn't allowed"),
+            ("user/signup", "'/' isn't allowed"),
+            (&"a".repeat(257), "at most 256 characters"),
+        ] {
+            let err = EventTypeName::new(name).unwrap_err().to_string();
+            assert!(err.contains(reason), "{name}: {err}");
+        }
+
+        let name: EventTypeName = serde_json::from_str(r#""user.signup""#).unwrap();
+        assert_eq!(serde_json::to_string(&name).unwrap(), r#""user.signup""#);
+        assert!(serde_json::from_str::<EventTypeName>(r#""user signup""#).is_err());
+    }
+
+    #[test]
+    fn test_channels_and_tags() {
+        assert_eq!(Channel::new("project:123").unwrap(), "project:123");
+        assert!(Channel::new("a".repeat(129)).is_err());
+        assert_eq!(Tag::new("user_1.v2").unwrap(), "user_1.v2");
+        let err = Tag::new("project:123").unwrap_err().to_string();
+        assert!(
+            err.contains("invalid tag") && err.contains("':' isn't allowed"),
+            "{err}"
+        );
+    }
+
+    #[cfg(feature = "api-message")]
+    #[test]
+    fn test_check_message_in() {
+        use super::check_message_in;
+        use crate::models::MessageIn;
+
+        let channel = Channel::new("project:123").unwrap();
+        let msg_in = MessageIn::builder()
+            .event_type(EventTypeName::new("user.signup").unwrap())
+            .payload(serde_json::json!({}))
+            .channels(vec![channel; Channel::MAX_PER_MESSAGE])
+            .tags(vec![Tag::new("user_1").unwrap()])
+            .build();
+        assert!(check_message_in(&msg_in).is_ok());
+        let msg_in = MessageIn {
+            tags: Some(vec![Tag::new("user_1").unwrap(); Tag::MAX_PER_MESSAGE + 1]),
+            ..msg_in
+        };
+        let err = check_message_in(&msg_in).unwrap_err().to_string();
+        assert!(err.contains("has 6 tags, at most 5"), "{err}");
+    }
+}
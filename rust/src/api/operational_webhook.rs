@@ -0,0 +1,73 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! The catalog of operational webhook event types.
+//!
+//! The API doesn't list these, they're only described by the `x-webhooks`
+//! section of the OpenAPI spec. `operational_webhook_schemas.json` holds the
+//! payload schemas from there, with all references resolved.
+
+/// An event type sent to operational webhook endpoints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OperationalWebhookEventType {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+impl OperationalWebhookEventType {
+    /// Returns the JSON schema of this event type's payload.
+    pub fn schema(&self) -> serde_json::Value {
+        let mut schemas: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(include_str!("operational_webhook_schemas.json"))
+                .expect("operational webhook schemas are valid JSON");
+        schemas
+            .remove(self.name)
+            .expect("every operational webhook event type has a schema")
+    }
+}
+
+/// All operational webhook event types, see
+/// [`OperationalWebhookEndpoint::event_types`](super::OperationalWebhookEndpoint::event_types).
+pub const OPERATIONAL_WEBHOOK_EVENT_TYPES: &[OperationalWebhookEventType] = &[
+    OperationalWebhookEventType {
+        name: "endpoint.created",
+        description: "Sent when an endpoint is created.",
+    },
+    OperationalWebhookEventType {
+        name: "endpoint.deleted",
+        description: "Sent when an endpoint is deleted.",
+    },
+    OperationalWebhookEventType {
+        name: "endpoint.disabled",
+        description: "Sent when an endpoint has been automatically disabled after continuous failures.",
+    },
+    OperationalWebhookEventType {
+        name: "endpoint.updated",
+        description: "Sent when an endpoint is updated.",
+    },
+    OperationalWebhookEventType {
+        name: "message.attempt.exhausted",
+        description: "Sent when a message delivery has failed (all of the retry attempts have been exhausted).",
+    },
+    OperationalWebhookEventType {
+        name: "message.attempt.failing",
+        description: "Sent after a message has been failing for a few times.\nIt's sent on the fourth failure. It complements `message.attempt.exhausted` which is sent after the last failure.",
+    },
+    OperationalWebhookEventType {
+        name: "message.attempt.recovered",
+        description: "Sent on a successful dispatch after an earlier failure op webhook has already been sent.",
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::OPERATIONAL_WEBHOOK_EVENT_TYPES;
+
+    #[test]
+    fn test_event_type_schemas() {
+        for event_type in OPERATIONAL_WEBHOOK_EVENT_TYPES {
+            let schema = event_type.schema();
+            assert_eq!(schema["properties"]["type"]["enum"][0], event_type.name);
+        }
+    }
+}
@@ -0,0 +1,345 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Response bodies that are deserialized by the caller.
+//!
+//! The regular methods deserialize responses into owned models, copying
+//! every string (including message payloads) out of the response body. For
+//! large pages with content, the `*_raw` methods return the body as is, so it
+//! can be deserialized into types borrowing from it instead:
+//!
+//! ```ignore
+//! #[derive(Deserialize)]
+//! struct Attempt<'a> {
+//!     #[serde(borrow)]
+//!     id: &'a str,
+//!     #[serde(rename = "responseStatusCode")]
+//!     status_code: u16,
+//! }
+//!
+//! let raw = svix.message_attempt().list_by_endpoint_raw(app_id, endpoint_id, None).await?;
+//! let page: ListResponse<Attempt<'_>> = raw.parse()?;
+//! ```
+//!
+//! Message payloads are kept as [`RawPayload`]s in the regular models too, so
+//! they're only parsed when the caller asks for them.
+//!
+//! Jobs which keep many list items around, e.g. to aggregate attempts, can
+//! pass the borrowed IDs through an [`Interner`], so the application and
+//! endpoint IDs repeated on every item are only stored once:
+//!
+//! ```ignore
+//! let mut interner = Interner::new();
+//! let endpoint_ids: Vec<Arc<str>> = page.data.iter().map(|a| interner.intern(a.endpoint_id)).collect();
+//! ```
+
+use std::{collections::HashSet, sync::Arc};
+
+use hyper::body::Bytes;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use serde_json::value::RawValue;
+
+use crate::{
+    error::{Error, Result},
+    models::{EndpointMessageOut, MessageAttemptOut, MessageOut, MessageRawPayloadOut},
+};
+
+/// The body of a successful response, which hasn't been deserialized yet.
+#[derive(Clone, Debug)]
+pub struct RawResponse {
+    body: Bytes,
+}
+
+impl RawResponse {
+    #[cfg(any(test, feature = "api-message", feature = "api-message-attempt"))]
+    pub(crate) fn new(body: Bytes) -> Self {
+        Self { body }
+    }
+
+    /// The JSON body of the response.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Deserializes the body, possibly borrowing from it.
+    pub fn parse<'a, T: Deserialize<'a>>(&'a self) -> Result<T> {
+        serde_json::from_slice(&self.body).map_err(Error::generic)
+    }
+}
+
+/// A page of a list response, with items of any type.
+///
+/// Use it with [`RawResponse::parse`] to deserialize list items into types
+/// borrowing from the response body.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct ListResponse<T> {
+    pub data: Vec<T>,
+    pub done: bool,
+    pub iterator: Option<String>,
+    #[serde(rename = "prevIterator", default)]
+    pub prev_iterator: Option<String>,
+}
+
+/// Shares strings which repeat across many list items, as [`Arc<str>`]s.
+#[derive(Clone, Debug, Default)]
+pub struct Interner {
+    strings: HashSet<Arc<str>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shared copy of `s`, which is allocated the first time
+    /// it's interned.
+    pub fn intern(&mut self, s: &str) -> Arc<str> {
+        if let Some(interned) = self.strings.get(s) {
+            return interned.clone();
+        }
+        let interned: Arc<str> = s.into();
+        self.strings.insert(interned.clone());
+        interned
+    }
+
+    /// The number of distinct strings.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+/// A JSON payload which hasn't been parsed yet.
+///
+/// Equality compares the JSON text, so payloads which only differ in
+/// formatting or key order aren't equal; compare the parsed values for that.
+///
+/// Other deserializers than `serde_json` can't hand over the raw text, so
+/// with the `simd-json` feature payloads are parsed and then serialized again.
+#[derive(Clone, Debug, Serialize)]
+#[serde(transparent)]
+pub struct RawPayload(Box<RawValue>);
+
+impl RawPayload {
+    /// The JSON text of the payload.
+    pub fn get(&self) -> &str {
+        self.0.get()
+    }
+
+    /// Deserializes the payload, possibly borrowing from it.
+    pub fn parse<'a, T: Deserialize<'a>>(&'a self) -> Result<T> {
+        serde_json::from_str(self.0.get()).map_err(Error::generic)
+    }
+}
+
+impl Default for RawPayload {
+    fn default() -> Self {
+        serde_json::Value::Object(Default::default()).into()
+    }
+}
+
+impl PartialEq for RawPayload {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl From<serde_json::Value> for RawPayload {
+    fn from(value: serde_json::Value) -> Self {
+        Self(serde_json::value::to_raw_value(&value).expect("JSON values can be serialized"))
+    }
+}
+
+impl<'de> Deserialize<'de> for RawPayload {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[cfg(not(feature = "simd-json"))]
+        return Box::<RawValue>::deserialize(deserializer).map(Self);
+        #[cfg(feature = "simd-json")]
+        return serde_json::Value::deserialize(deserializer).map(Into::into);
+    }
+}
+
+impl MessageOut {
+    /// Deserializes the message's payload into `T`.
+    pub fn payload_as<T: DeserializeOwned>(&self) -> Result<T> {
+        self.payload.parse()
+    }
+}
+
+impl EndpointMessageOut {
+    /// Deserializes the message's payload into `T`.
+    pub fn payload_as<T: DeserializeOwned>(&self) -> Result<T> {
+        self.payload.parse()
+    }
+}
+
+impl MessageRawPayloadOut {
+    /// Deserializes the message's payload into `T`.
+    pub fn payload_as<T: DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_str(&self.payload).map_err(Error::generic)
+    }
+}
+
+impl MessageAttemptOut {
+    /// Deserializes the body the endpoint responded with into `T`, which
+    /// fails if the endpoint didn't respond with JSON.
+    pub fn response_as<T: DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_str(&self.response).map_err(Error::generic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::body::Bytes;
+
+    use super::{Interner, ListResponse, RawPayload, RawResponse};
+    use crate::models::{MessageAttemptOut, MessageOut};
+
+    #[derive(Deserialize)]
+    struct Message<'a> {
+        id: &'a str,
+        #[serde(rename = "eventType")]
+        event_type: &'a str,
+    }
+
+    #[test]
+    fn test_parse_borrowed() {
+        let raw = RawResponse::new(Bytes::from_static(
+            br#"{"data": [{"id": "msg_1", "eventType": "a"}], "done": true, "iterator": "msg_1"}"#,
+        ));
+        let page: ListResponse<Message<'_>> = raw.parse().unwrap();
+        assert!(page.done);
+        assert_eq!(page.prev_iterator, None);
+        assert_eq!(page.data[0].id, "msg_1");
+        assert_eq!(page.data[0].event_type, "a");
+    }
+
+    #[test]
+    fn test_interner() {
+        let raw = RawResponse::new(Bytes::from_static(
+            br#"{"data": [{"id": "msg_1", "eventType": "a"}, {"id": "msg_2", "eventType": "a"}], "done": true, "iterator": "msg_2"}"#,
+        ));
+        let page: ListResponse<Message<'_>> = raw.parse().unwrap();
+        let mut interner = Interner::new();
+        let event_types: Vec<_> = page
+            .data
+            .iter()
+            .map(|msg| interner.intern(msg.event_type))
+            .collect();
+        assert_eq!(&*event_types[0], "a");
+        assert!(std::sync::Arc::ptr_eq(&event_types[0], &event_types[1]));
+        assert_eq!(interner.len(), 1);
+    }
+
+    #[test]
+    fn test_raw_payload() {
+        let json = r#"{"id": "msg_1", "eventType": "a"}"#;
+        let payload: RawPayload = serde_json::from_str(json).unwrap();
+        let message: Message<'_> = payload.parse().unwrap();
+        assert_eq!(message.id, "msg_1");
+        assert_eq!(
+            payload.parse::<serde_json::Value>().unwrap(),
+            serde_json::from_str::<serde_json::Value>(json).unwrap()
+        );
+
+        let reparsed: RawPayload =
+            serde_json::from_str(&serde_json::to_string(&payload).unwrap()).unwrap();
+        assert_eq!(reparsed, payload);
+    }
+
+    #[test]
+    fn test_payload_as() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Signup {
+            email: String,
+        }
+
+        let msg: MessageOut = serde_json::from_value(serde_json::json!({
+            "eventType": "user.signup",
+            "id": "msg_1",
+            "payload": { "email": "test@example.com" },
+            "timestamp": "2024-01-01T00:00:00Z",
+        }))
+        .unwrap();
+        assert_eq!(
+            msg.payload_as::<Signup>().unwrap(),
+            Signup {
+                email: "test@example.com".to_owned()
+            }
+        );
+        assert!(msg.payload_as::<Vec<String>>().is_err());
+
+        let attempt = MessageAttemptOut {
+            response: r#"{"ok": true}"#.to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(
+            attempt.response_as::<serde_json::Value>().unwrap(),
+            serde_json::json!({ "ok": true })
+        );
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_list_raw() {
+        use crate::{
+            api::{ApplicationIn, EndpointIn, MessageIn, MessageListOptions},
+            testing::MockSvixServer,
+        };
+
+        let server = MockSvixServer::start().await.unwrap();
+        let svix = server.client();
+        let app = svix
+            .application()
+            .create(ApplicationIn::new("My app".to_owned()), None)
+            .await
+            .unwrap();
+        svix.endpoint()
+            .create(
+                app.id.clone(),
+                EndpointIn::new("https://example.com".to_owned()),
+                None,
+            )
+            .await
+            .unwrap();
+        let msg = svix
+            .message()
+            .create(
+                app.id.clone(),
+                MessageIn::new(
+                    "user.signup".parse().unwrap(),
+                    serde_json::json!({ "a": 1 }),
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let raw = svix
+            .message()
+            .list_raw(
+                app.id.clone(),
+                Some(MessageListOptions {
+                    event_types: Some(vec!["user.signup".to_owned()]),
+                    ..Default::default()
+                }),
+            )
+            .await
+            .unwrap();
+        let page: ListResponse<Message<'_>> = raw.parse().unwrap();
+        assert_eq!(page.data.len(), 1);
+        assert_eq!(page.data[0].id, msg.id);
+        assert_eq!(page.data[0].event_type, "user.signup");
+
+        let raw = svix
+            .message_attempt()
+            .list_by_msg_raw(app.id, msg.id.clone(), None)
+            .await
+            .unwrap();
+        let page: ListResponse<serde_json::Value> = raw.parse().unwrap();
+        assert_eq!(page.data[0]["msgId"], msg.id.as_str());
+    }
+}
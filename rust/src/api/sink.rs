@@ -0,0 +1,170 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Constructors for the common sink kinds.
+//!
+//! The generated sink models are untagged unions of anonymous variants, which
+//! are awkward to build by hand. These check the required fields up front
+//! instead of leaving it to the server to reject them.
+
+use crate::{
+    error::{Error, Result},
+    models::{
+        self, KafkaSecurityProtocolType, S3Config, SinkHttpConfig, SinkIn, SinkInOneOf,
+        SinkInOneOf1, SinkInOneOf2, SinkInOneOf3, StreamSinkIn, StreamSinkInOneOf2,
+        StreamSinkInOneOf3,
+    },
+};
+
+impl SinkIn {
+    /// A sink publishing to a RabbitMQ exchange.
+    pub fn rabbit_mq(uri: String, routing_key: String) -> Result<Self> {
+        check_url("uri", &uri, &["amqp", "amqps"])?;
+        check_not_empty("routing_key", &routing_key)?;
+        Ok(Self::SinkInOneOf(Box::new(SinkInOneOf::new(
+            routing_key,
+            models::sink_in_one_of::Type::RabbitMq,
+            uri,
+        ))))
+    }
+
+    /// A sink sending to an Amazon SQS queue.
+    pub fn sqs(
+        queue_dsn: String,
+        region: String,
+        access_key: String,
+        secret_key: String,
+    ) -> Result<Self> {
+        check_url("queue_dsn", &queue_dsn, &["http", "https"])?;
+        check_not_empty("region", &region)?;
+        check_not_empty("access_key", &access_key)?;
+        check_not_empty("secret_key", &secret_key)?;
+        Ok(Self::SinkInOneOf1(Box::new(SinkInOneOf1::new(
+            access_key,
+            queue_dsn,
+            region,
+            secret_key,
+            models::sink_in_one_of1::Type::Sqs,
+        ))))
+    }
+
+    /// A sink producing to a Kafka (or Kafka-compatible) topic.
+    ///
+    /// `brokers` is a comma-separated list of `host:port` pairs. Set the SASL
+    /// credentials on the result for [`KafkaSecurityProtocolType::SaslSsl`].
+    pub fn kafka(
+        brokers: String,
+        topic: String,
+        security_protocol: KafkaSecurityProtocolType,
+    ) -> Result<Self> {
+        if brokers.split(',').any(|broker| broker.trim().is_empty()) {
+            return Err(invalid(
+                "brokers",
+                "must be a comma-separated list of brokers",
+            ));
+        }
+        check_not_empty("topic", &topic)?;
+        Ok(Self::SinkInOneOf2(Box::new(SinkInOneOf2::new(
+            brokers,
+            security_protocol,
+            topic,
+            models::sink_in_one_of2::Type::Kafka,
+        ))))
+    }
+
+    /// A sink POSTing messages to an HTTP(S) URL.
+    pub fn http(url: String) -> Result<Self> {
+        check_url("url", &url, &["http", "https"])?;
+        Ok(Self::SinkInOneOf3(Box::new(SinkInOneOf3::new(
+            models::sink_in_one_of3::Type::Http,
+            url,
+        ))))
+    }
+}
+
+impl StreamSinkIn {
+    /// A sink writing batches of events to an Amazon S3 bucket.
+    pub fn amazon_s3(
+        bucket: String,
+        region: String,
+        access_key_id: String,
+        secret_access_key: String,
+    ) -> Result<Self> {
+        check_not_empty("bucket", &bucket)?;
+        check_not_empty("region", &region)?;
+        check_not_empty("access_key_id", &access_key_id)?;
+        check_not_empty("secret_access_key", &secret_access_key)?;
+        Ok(Self::StreamSinkInOneOf3(Box::new(StreamSinkInOneOf3::new(
+            S3Config::new(access_key_id, bucket, region, secret_access_key),
+            models::stream_sink_in_one_of3::Type::AmazonS3,
+        ))))
+    }
+
+    /// A sink POSTing batches of events to an HTTP(S) URL.
+    pub fn http(url: String) -> Result<Self> {
+        check_url("url", &url, &["http", "https"])?;
+        Ok(Self::StreamSinkInOneOf2(Box::new(StreamSinkInOneOf2::new(
+            SinkHttpConfig::new(url),
+            models::stream_sink_in_one_of2::Type::Http,
+        ))))
+    }
+}
+
+fn check_not_empty(field: &str, value: &str) -> Result<()> {
+    if value.trim().is_empty() {
+        return Err(invalid(field, "must not be empty"));
+    }
+    Ok(())
+}
+
+fn check_url(field: &str, value: &str, schemes: &[&str]) -> Result<()> {
+    let url = url::Url::parse(value).map_err(|e| invalid(field, &e.to_string()))?;
+    if !schemes.contains(&url.scheme()) {
+        return Err(invalid(
+            field,
+            &format!("must use one of the schemes {}", schemes.join(", ")),
+        ));
+    }
+    Ok(())
+}
+
+fn invalid(field: &str, reason: &str) -> Error {
+    Error::Generic(format!("invalid sink `{field}`: {reason}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{KafkaSecurityProtocolType, SinkIn, StreamSinkIn};
+
+    #[test]
+    fn test_sink_constructors() {
+        let kafka = SinkIn::kafka(
+            "broker-1:9092,broker-2:9092".to_owned(),
+            "events".to_owned(),
+            KafkaSecurityProtocolType::Plaintext,
+        )
+        .unwrap();
+        assert_eq!(serde_json::to_value(kafka).unwrap()["type"], "kafka");
+        assert!(SinkIn::kafka(
+            "broker-1:9092,".to_owned(),
+            "events".to_owned(),
+            KafkaSecurityProtocolType::Plaintext,
+        )
+        .is_err());
+
+        let s3 = StreamSinkIn::amazon_s3(
+            "bucket".to_owned(),
+            "us-east-1".to_owned(),
+            "key-id".to_owned(),
+            "secret".to_owned(),
+        )
+        .unwrap();
+        let s3 = serde_json::to_value(s3).unwrap();
+        assert_eq!(s3["type"], "amazonS3");
+        assert_eq!(s3["config"]["bucket"], "bucket");
+
+        assert!(StreamSinkIn::http("https://example.com/events".to_owned()).is_ok());
+        assert!(StreamSinkIn::http("ftp://example.com".to_owned()).is_err());
+        assert!(SinkIn::http("not a url".to_owned()).is_err());
+    }
+}
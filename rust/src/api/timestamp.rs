@@ -0,0 +1,222 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! The timestamps of the API, which are RFC 3339 strings on the wire.
+
+use std::{
+    fmt,
+    ops::{Add, Sub},
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::error::{Error, Result};
+
+/// A point in time, e.g. the `timestamp` of a
+/// [`MessageOut`](crate::api::MessageOut) or the `before` of a list.
+///
+/// Timestamps convert to and from [`SystemTime`] and `time::OffsetDateTime`,
+/// and with the `chrono` feature to and from `chrono::DateTime<Utc>`, so they
+/// can be used with either ecosystem. They parse from and display as RFC 3339:
+///
+/// ```
+/// use svix::api::Timestamp;
+///
+/// let timestamp: Timestamp = "2024-01-01T12:00:00Z".parse().unwrap();
+/// assert_eq!(timestamp.unix_timestamp(), 1_704_110_400);
+/// assert_eq!(timestamp.to_string(), "2024-01-01T12:00:00Z");
+/// ```
+///
+/// The default timestamp is the Unix epoch, so that models containing
+/// timestamps can implement `Default`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(OffsetDateTime);
+
+impl Timestamp {
+    pub fn now() -> Self {
+        Self(OffsetDateTime::now_utc())
+    }
+
+    /// The timestamp `secs` seconds after the Unix epoch.
+    pub fn from_unix_timestamp(secs: i64) -> Result<Self> {
+        OffsetDateTime::from_unix_timestamp(secs)
+            .map(Self)
+            .map_err(|e| Error::Generic(format!("invalid timestamp {secs}: {e}")))
+    }
+
+    /// The number of seconds since the Unix epoch.
+    pub fn unix_timestamp(&self) -> i64 {
+        self.0.unix_timestamp()
+    }
+
+    /// The time from `earlier` to this timestamp, or `None` if `earlier` is
+    /// later.
+    pub fn checked_duration_since(&self, earlier: Timestamp) -> Option<Duration> {
+        (self.0 - earlier.0).try_into().ok()
+    }
+}
+
+impl Default for Timestamp {
+    fn default() -> Self {
+        Self(OffsetDateTime::UNIX_EPOCH)
+    }
+}
+
+impl Add<Duration> for Timestamp {
+    type Output = Self;
+
+    fn add(self, duration: Duration) -> Self {
+        Self(self.0 + duration)
+    }
+}
+
+impl Sub<Duration> for Timestamp {
+    type Output = Self;
+
+    fn sub(self, duration: Duration) -> Self {
+        Self(self.0 - duration)
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let formatted = self.0.format(&Rfc3339).map_err(|_| fmt::Error)?;
+        f.write_str(&formatted)
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        OffsetDateTime::parse(s, &Rfc3339)
+            .map(Self)
+            .map_err(|e| Error::Generic(format!("invalid timestamp {s:?}: {e}")))
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'_, str>>::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Self {
+        Self(time.into())
+    }
+}
+
+impl From<Timestamp> for SystemTime {
+    fn from(timestamp: Timestamp) -> Self {
+        timestamp.0.into()
+    }
+}
+
+impl From<OffsetDateTime> for Timestamp {
+    fn from(time: OffsetDateTime) -> Self {
+        Self(time)
+    }
+}
+
+impl From<Timestamp> for OffsetDateTime {
+    fn from(timestamp: Timestamp) -> Self {
+        timestamp.0
+    }
+}
+
+/// Fails for dates beyond the year 9999, which `chrono` supports but the API
+/// doesn't.
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> TryFrom<chrono::DateTime<Tz>> for Timestamp {
+    type Error = Error;
+
+    fn try_from(time: chrono::DateTime<Tz>) -> Result<Self> {
+        let nanos = time.timestamp_nanos_opt().map_or(i128::MAX, i128::from);
+        OffsetDateTime::from_unix_timestamp_nanos(nanos)
+            .map(Self)
+            .map_err(|e| Error::Generic(format!("invalid timestamp {time:?}: {e}")))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<Timestamp> for chrono::DateTime<chrono::Utc> {
+    fn from(timestamp: Timestamp) -> Self {
+        chrono::DateTime::from_timestamp(timestamp.0.unix_timestamp(), timestamp.0.nanosecond())
+            .expect("timestamps are within chrono's range")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Timestamp;
+    use crate::models::MessageOut;
+
+    #[test]
+    fn test_timestamp() {
+        let timestamp: Timestamp = "2024-01-01T13:00:00.5+01:00".parse().unwrap();
+        assert_eq!(timestamp.unix_timestamp(), 1_704_110_400);
+        assert_eq!(
+            serde_json::to_string(&timestamp).unwrap(),
+            r#""2024-01-01T13:00:00.5+01:00""#
+        );
+        assert_eq!(
+            serde_json::from_str::<Timestamp>(r#""2024-01-01T12:00:00.5Z""#).unwrap(),
+            timestamp
+        );
+        assert!("2024-01-01".parse::<Timestamp>().is_err());
+        assert_eq!(Timestamp::default().to_string(), "1970-01-01T00:00:00Z");
+
+        let later = timestamp + Duration::from_secs(90);
+        assert_eq!(
+            later.checked_duration_since(timestamp),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(timestamp.checked_duration_since(later), None);
+    }
+
+    #[test]
+    fn test_output_timestamps() {
+        let json = serde_json::json!({
+            "eventType": "user.signup",
+            "id": "msg_1",
+            "payload": {},
+            "timestamp": "2024-01-01T12:00:00.123456Z",
+        });
+        let msg: MessageOut = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(msg.timestamp.unix_timestamp(), 1_704_110_400);
+        assert_eq!(serde_json::to_value(&msg).unwrap(), json);
+    }
+
+    #[test]
+    fn test_time() {
+        let timestamp: Timestamp = "2024-01-01T12:00:00.5Z".parse().unwrap();
+        let time: time::OffsetDateTime = timestamp.into();
+        assert_eq!(time.unix_timestamp_nanos(), 1_704_110_400_500_000_000);
+        assert_eq!(Timestamp::from(time), timestamp);
+
+        let system: std::time::SystemTime = timestamp.into();
+        assert_eq!(Timestamp::from(system), timestamp);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono() {
+        let timestamp: Timestamp = "2024-01-01T12:00:00.5Z".parse().unwrap();
+        let chrono: chrono::DateTime<chrono::Utc> = timestamp.into();
+        assert_eq!(chrono.to_rfc3339(), "2024-01-01T12:00:00.500+00:00");
+        assert_eq!(Timestamp::try_from(chrono).unwrap(), timestamp);
+    }
+}
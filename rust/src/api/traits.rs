@@ -0,0 +1,419 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Traits covering the core operations of the API wrappers.
+//!
+//! Code that's generic over these (or takes them as trait objects) can be
+//! tested against the mocks in [`crate::testing`] instead of a live server.
+//! With the `testing` feature enabled, mockall generates a `Mock*` type for
+//! every trait, which always has the same methods as the real wrapper.
+
+use async_trait::async_trait;
+
+#[cfg(feature = "api-application")]
+use super::Application;
+#[cfg(feature = "api-endpoint")]
+use super::Endpoint;
+#[cfg(feature = "api-event-type")]
+use super::EventType;
+#[cfg(feature = "api-message")]
+use super::Message;
+#[cfg(feature = "api-message-attempt")]
+use super::MessageAttempt;
+use super::{
+    ApplicationIn, ApplicationListOptions, ApplicationOut, ApplicationPatch, EndpointIn,
+    EndpointListOptions, EndpointOut, EndpointPatch, EndpointSecretOut, EndpointSecretRotateIn,
+    EndpointUpdate, EventTypeIn, EventTypeListOptions, EventTypeOut, EventTypePatch,
+    EventTypeUpdate, ListResponseApplicationOut, ListResponseEndpointOut, ListResponseEventTypeOut,
+    ListResponseMessageAttemptOut, ListResponseMessageOut, MessageAttemptListByEndpointOptions,
+    MessageAttemptListOptions, MessageAttemptOut, MessageIn, MessageListOptions, MessageOut,
+    PostOptions,
+};
+use crate::error::Result;
+
+/// The operations of [`Application`].
+#[cfg_attr(feature = "testing", mockall::automock)]
+#[async_trait]
+pub trait ApplicationApi {
+    async fn list(
+        &self,
+        options: Option<ApplicationListOptions>,
+    ) -> Result<ListResponseApplicationOut>;
+
+    async fn create(
+        &self,
+        application_in: ApplicationIn,
+        options: Option<PostOptions>,
+    ) -> Result<ApplicationOut>;
+
+    async fn get_or_create(
+        &self,
+        application_in: ApplicationIn,
+        options: Option<PostOptions>,
+    ) -> Result<ApplicationOut>;
+
+    async fn get(&self, app_id: String) -> Result<ApplicationOut>;
+
+    async fn update(
+        &self,
+        app_id: String,
+        application_in: ApplicationIn,
+        options: Option<PostOptions>,
+    ) -> Result<ApplicationOut>;
+
+    async fn patch(
+        &self,
+        app_id: String,
+        application_patch: ApplicationPatch,
+        options: Option<PostOptions>,
+    ) -> Result<ApplicationOut>;
+
+    async fn delete(&self, app_id: String) -> Result<()>;
+}
+
+#[cfg(feature = "api-application")]
+#[async_trait]
+impl ApplicationApi for Application<'_> {
+    async fn list(
+        &self,
+        options: Option<ApplicationListOptions>,
+    ) -> Result<ListResponseApplicationOut> {
+        Application::list(self, options).await
+    }
+
+    async fn create(
+        &self,
+        application_in: ApplicationIn,
+        options: Option<PostOptions>,
+    ) -> Result<ApplicationOut> {
+        Application::create(self, application_in, options).await
+    }
+
+    async fn get_or_create(
+        &self,
+        application_in: ApplicationIn,
+        options: Option<PostOptions>,
+    ) -> Result<ApplicationOut> {
+        Application::get_or_create(self, application_in, options).await
+    }
+
+    async fn get(&self, app_id: String) -> Result<ApplicationOut> {
+        Application::get(self, app_id).await
+    }
+
+    async fn update(
+        &self,
+        app_id: String,
+        application_in: ApplicationIn,
+        options: Option<PostOptions>,
+    ) -> Result<ApplicationOut> {
+        Application::update(self, app_id, application_in, options).await
+    }
+
+    async fn patch(
+        &self,
+        app_id: String,
+        application_patch: ApplicationPatch,
+        options: Option<PostOptions>,
+    ) -> Result<ApplicationOut> {
+        Application::patch(self, app_id, application_patch, options).await
+    }
+
+    async fn delete(&self, app_id: String) -> Result<()> {
+        Application::delete(self, app_id).await
+    }
+}
+
+/// The operations of [`Endpoint`].
+#[cfg_attr(feature = "testing", mockall::automock)]
+#[async_trait]
+pub trait EndpointApi {
+    async fn list(
+        &self,
+        app_id: String,
+        options: Option<EndpointListOptions>,
+    ) -> Result<ListResponseEndpointOut>;
+
+    async fn create(
+        &self,
+        app_id: String,
+        endpoint_in: EndpointIn,
+        options: Option<PostOptions>,
+    ) -> Result<EndpointOut>;
+
+    async fn get(&self, app_id: String, endpoint_id: String) -> Result<EndpointOut>;
+
+    async fn update(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        endpoint_update: EndpointUpdate,
+        options: Option<PostOptions>,
+    ) -> Result<EndpointOut>;
+
+    async fn patch(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        endpoint_patch: EndpointPatch,
+        options: Option<PostOptions>,
+    ) -> Result<EndpointOut>;
+
+    async fn delete(&self, app_id: String, endpoint_id: String) -> Result<()>;
+
+    async fn get_secret(&self, app_id: String, endpoint_id: String) -> Result<EndpointSecretOut>;
+
+    async fn rotate_secret(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        endpoint_secret_rotate_in: EndpointSecretRotateIn,
+    ) -> Result<()>;
+}
+
+#[cfg(feature = "api-endpoint")]
+#[async_trait]
+impl EndpointApi for Endpoint<'_> {
+    async fn list(
+        &self,
+        app_id: String,
+        options: Option<EndpointListOptions>,
+    ) -> Result<ListResponseEndpointOut> {
+        Endpoint::list(self, app_id, options).await
+    }
+
+    async fn create(
+        &self,
+        app_id: String,
+        endpoint_in: EndpointIn,
+        options: Option<PostOptions>,
+    ) -> Result<EndpointOut> {
+        Endpoint::create(self, app_id, endpoint_in, options).await
+    }
+
+    async fn get(&self, app_id: String, endpoint_id: String) -> Result<EndpointOut> {
+        Endpoint::get(self, app_id, endpoint_id).await
+    }
+
+    async fn update(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        endpoint_update: EndpointUpdate,
+        options: Option<PostOptions>,
+    ) -> Result<EndpointOut> {
+        Endpoint::update(self, app_id, endpoint_id, endpoint_update, options).await
+    }
+
+    async fn patch(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        endpoint_patch: EndpointPatch,
+        options: Option<PostOptions>,
+    ) -> Result<EndpointOut> {
+        Endpoint::patch(self, app_id, endpoint_id, endpoint_patch, options).await
+    }
+
+    async fn delete(&self, app_id: String, endpoint_id: String) -> Result<()> {
+        Endpoint::delete(self, app_id, endpoint_id).await
+    }
+
+    async fn get_secret(&self, app_id: String, endpoint_id: String) -> Result<EndpointSecretOut> {
+        Endpoint::get_secret(self, app_id, endpoint_id).await
+    }
+
+    async fn rotate_secret(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        endpoint_secret_rotate_in: EndpointSecretRotateIn,
+    ) -> Result<()> {
+        Endpoint::rotate_secret(self, app_id, endpoint_id, endpoint_secret_rotate_in).await
+    }
+}
+
+/// The operations of [`EventType`].
+#[cfg_attr(feature = "testing", mockall::automock)]
+#[async_trait]
+pub trait EventTypeApi {
+    async fn list(&self, options: Option<EventTypeListOptions>)
+        -> Result<ListResponseEventTypeOut>;
+
+    async fn create(
+        &self,
+        event_type_in: EventTypeIn,
+        options: Option<PostOptions>,
+    ) -> Result<EventTypeOut>;
+
+    async fn get(&self, event_type_name: String) -> Result<EventTypeOut>;
+
+    async fn update(
+        &self,
+        event_type_name: String,
+        event_type_update: EventTypeUpdate,
+        options: Option<PostOptions>,
+    ) -> Result<EventTypeOut>;
+
+    async fn patch(
+        &self,
+        event_type_name: String,
+        event_type_patch: EventTypePatch,
+        options: Option<PostOptions>,
+    ) -> Result<EventTypeOut>;
+
+    async fn delete(&self, event_type_name: String) -> Result<()>;
+}
+
+#[cfg(feature = "api-event-type")]
+#[async_trait]
+impl EventTypeApi for EventType<'_> {
+    async fn list(
+        &self,
+        options: Option<EventTypeListOptions>,
+    ) -> Result<ListResponseEventTypeOut> {
+        EventType::list(self, options).await
+    }
+
+    async fn create(
+        &self,
+        event_type_in: EventTypeIn,
+        options: Option<PostOptions>,
+    ) -> Result<EventTypeOut> {
+        EventType::create(self, event_type_in, options).await
+    }
+
+    async fn get(&self, event_type_name: String) -> Result<EventTypeOut> {
+        EventType::get(self, event_type_name).await
+    }
+
+    async fn update(
+        &self,
+        event_type_name: String,
+        event_type_update: EventTypeUpdate,
+        options: Option<PostOptions>,
+    ) -> Result<EventTypeOut> {
+        EventType::update(self, event_type_name, event_type_update, options).await
+    }
+
+    async fn patch(
+        &self,
+        event_type_name: String,
+        event_type_patch: EventTypePatch,
+        options: Option<PostOptions>,
+    ) -> Result<EventTypeOut> {
+        EventType::patch(self, event_type_name, event_type_patch, options).await
+    }
+
+    async fn delete(&self, event_type_name: String) -> Result<()> {
+        EventType::delete(self, event_type_name).await
+    }
+}
+
+/// The operations of [`Message`].
+#[cfg_attr(feature = "testing", mockall::automock)]
+#[async_trait]
+pub trait MessageApi {
+    async fn list(
+        &self,
+        app_id: String,
+        options: Option<MessageListOptions>,
+    ) -> Result<ListResponseMessageOut>;
+
+    async fn create(
+        &self,
+        app_id: String,
+        message_in: MessageIn,
+        options: Option<PostOptions>,
+    ) -> Result<MessageOut>;
+
+    async fn get(&self, app_id: String, msg_id: String) -> Result<MessageOut>;
+}
+
+#[cfg(feature = "api-message")]
+#[async_trait]
+impl MessageApi for Message<'_> {
+    async fn list(
+        &self,
+        app_id: String,
+        options: Option<MessageListOptions>,
+    ) -> Result<ListResponseMessageOut> {
+        Message::list(self, app_id, options).await
+    }
+
+    async fn create(
+        &self,
+        app_id: String,
+        message_in: MessageIn,
+        options: Option<PostOptions>,
+    ) -> Result<MessageOut> {
+        Message::create(self, app_id, message_in, options).await
+    }
+
+    async fn get(&self, app_id: String, msg_id: String) -> Result<MessageOut> {
+        Message::get(self, app_id, msg_id).await
+    }
+}
+
+/// The operations of [`MessageAttempt`].
+#[cfg_attr(feature = "testing", mockall::automock)]
+#[async_trait]
+pub trait MessageAttemptApi {
+    async fn list_by_msg(
+        &self,
+        app_id: String,
+        msg_id: String,
+        options: Option<MessageAttemptListOptions>,
+    ) -> Result<ListResponseMessageAttemptOut>;
+
+    async fn list_by_endpoint(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        options: Option<MessageAttemptListByEndpointOptions>,
+    ) -> Result<ListResponseMessageAttemptOut>;
+
+    async fn get(
+        &self,
+        app_id: String,
+        msg_id: String,
+        attempt_id: String,
+    ) -> Result<MessageAttemptOut>;
+
+    async fn resend(&self, app_id: String, msg_id: String, endpoint_id: String) -> Result<()>;
+}
+
+#[cfg(feature = "api-message-attempt")]
+#[async_trait]
+impl MessageAttemptApi for MessageAttempt<'_> {
+    async fn list_by_msg(
+        &self,
+        app_id: String,
+        msg_id: String,
+        options: Option<MessageAttemptListOptions>,
+    ) -> Result<ListResponseMessageAttemptOut> {
+        MessageAttempt::list_by_msg(self, app_id, msg_id, options).await
+    }
+
+    async fn list_by_endpoint(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        options: Option<MessageAttemptListByEndpointOptions>,
+    ) -> Result<ListResponseMessageAttemptOut> {
+        MessageAttempt::list_by_endpoint(self, app_id, endpoint_id, options).await
+    }
+
+    async fn get(
+        &self,
+        app_id: String,
+        msg_id: String,
+        attempt_id: String,
+    ) -> Result<MessageAttemptOut> {
+        MessageAttempt::get(self, app_id, msg_id, attempt_id).await
+    }
+
+    async fn resend(&self, app_id: String, msg_id: String, endpoint_id: String) -> Result<()> {
+        MessageAttempt::resend(self, app_id, msg_id, endpoint_id).await
+    }
+}
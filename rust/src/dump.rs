@@ -0,0 +1,281 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Dumping the requests sent by the client and their responses to a file,
+//! for attaching to support tickets. Enabled with
+//! [`SvixOptions::debug_dump`](crate::api::SvixOptions::debug_dump) or the
+//! `SVIX_DEBUG_DUMP` environment variable.
+//!
+//! Each line of the file is a JSON object with a request and its response
+//! (or the error if there was none). Credentials are redacted: the
+//! `authorization` header, signature headers, the values of JSON fields like
+//! `secret` or `oneTimeToken` and the login links next to tokens are replaced
+//! by `[REDACTED]`.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+
+use bytes::Bytes;
+use http1::{response::Parts, HeaderMap};
+use serde_json::{json, Map, Value};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::error::Error;
+
+/// The environment variable with the path to dump to, if none is set in the
+/// options.
+const DEBUG_DUMP_ENV: &str = "SVIX_DEBUG_DUMP";
+
+/// JSON fields with any of these in their (lowercased) name have their
+/// values replaced by [`REDACTED`], since they hold credentials, e.g.
+/// `secret` or `oneTimeToken`.
+const REDACTED_FIELDS: &[&str] = &["key", "secret", "token"];
+/// Headers whose values are replaced by [`REDACTED`], on top of the ones
+/// with `signature` in their name.
+const REDACTED_HEADERS: &[&str] = &["authorization", "cookie", "set-cookie"];
+const REDACTED: &str = "[REDACTED]";
+
+/// The file requests are dumped to.
+pub(crate) struct WireDump {
+    file: Mutex<File>,
+}
+
+/// A request which was sent, for [`WireDump::write`].
+pub(crate) struct DumpedRequest<'a> {
+    pub method: &'a str,
+    pub uri: &'a str,
+    pub headers: &'a HeaderMap,
+    pub body: &'a [u8],
+}
+
+impl WireDump {
+    /// Opens the file at `path`, or else at the path in the environment
+    /// variable, if there's one.
+    ///
+    /// Failing to open it is only logged, so clients can always be created.
+    pub(crate) fn from_options(path: Option<PathBuf>) -> Option<Self> {
+        let path = path.or_else(|| std::env::var_os(DEBUG_DUMP_ENV).map(PathBuf::from))?;
+        match Self::open(&path) {
+            Ok(wire_dump) => Some(wire_dump),
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "failed to open the Svix debug dump");
+                None
+            }
+        }
+    }
+
+    /// Opens the file at `path` to append to, creating it if needed.
+    pub(crate) fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Appends a request and its response to the file.
+    ///
+    /// Failing to write is only logged, so dumping never fails requests.
+    pub(crate) fn write(
+        &self,
+        request: &DumpedRequest<'_>,
+        response: Result<&(Parts, Bytes), &Error>,
+        elapsed: Duration,
+    ) {
+        let mut entry = json!({
+            "time": OffsetDateTime::now_utc().format(&Rfc3339).ok(),
+            "elapsed_ms": elapsed.as_millis() as u64,
+            "request": {
+                "method": request.method,
+                "url": request.uri,
+                "headers": redacted_headers(request.headers),
+                "body": redacted_body(request.body),
+            },
+        });
+        match response {
+            Ok((parts, body)) => {
+                entry["response"] = json!({
+                    "status": parts.status.as_u16(),
+                    "headers": redacted_headers(&parts.headers),
+                    "body": redacted_body(body),
+                });
+            }
+            Err(e) => entry["error"] = json!(e.to_string()),
+        }
+
+        let mut line = entry.to_string();
+        line.push('\n');
+        // A single write, so entries of concurrent requests don't interleave
+        if let Err(e) = self.file.lock().unwrap().write_all(line.as_bytes()) {
+            tracing::warn!(error = %e, "failed to write Svix API request to the debug dump");
+        }
+    }
+}
+
+fn redacted_headers(headers: &HeaderMap) -> Map<String, Value> {
+    let mut redacted = Map::new();
+    for (name, value) in headers {
+        let name = name.as_str();
+        let value = if REDACTED_HEADERS.contains(&name) || name.contains("signature") {
+            REDACTED.into()
+        } else {
+            String::from_utf8_lossy(value.as_bytes()).into_owned()
+        };
+        // Repeated headers are joined, like they could be when sent
+        redacted
+            .entry(name)
+            .and_modify(|joined| {
+                if let Value::String(joined) = joined {
+                    *joined += ", ";
+                    *joined += &value;
+                }
+            })
+            .or_insert(Value::String(value));
+    }
+    redacted
+}
+
+/// Parses a request or response body, redacting credentials.
+pub(crate) fn redacted_body(body: &[u8]) -> Option<Value> {
+    if body.is_empty() {
+        return None;
+    }
+    let mut value = serde_json::from_slice(body)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(body).into_owned()));
+    redact(&mut value);
+    Some(value)
+}
+
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            let mut has_token = false;
+            for (key, value) in object.iter_mut() {
+                if is_redacted_field(key) && value.is_string() {
+                    *value = json!(REDACTED);
+                    has_token |= key.to_ascii_lowercase().contains("token");
+                } else {
+                    redact(value);
+                }
+            }
+            // The URL next to a token, e.g. of `AppPortalAccessOut`, is a
+            // login link containing it
+            if has_token {
+                if let Some(url @ Value::String(_)) = object.get_mut("url") {
+                    *url = json!(REDACTED);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+fn is_redacted_field(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    REDACTED_FIELDS.iter().any(|field| key.contains(field))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::redacted_body;
+
+    #[test]
+    fn test_redacted_body() {
+        let body = json!({
+            "oneTimeToken": "ott_1",
+            "portal": {
+                "token": "appsk_1",
+                "url": "https://app.svix.com/login#key=appsk_1",
+            },
+            "endpoints": [{
+                "signingSecret": "whsec_1",
+                "url": "https://example.com",
+            }],
+            "API_KEY": "sk_1",
+            "description": "Not a secret",
+        });
+        assert_eq!(
+            redacted_body(body.to_string().as_bytes()),
+            Some(json!({
+                "oneTimeToken": "[REDACTED]",
+                "portal": {
+                    "token": "[REDACTED]",
+                    "url": "[REDACTED]",
+                },
+                "endpoints": [{
+                    "signingSecret": "[REDACTED]",
+                    "url": "https://example.com",
+                }],
+                "API_KEY": "[REDACTED]",
+                "description": "Not a secret",
+            }))
+        );
+    }
+
+    #[cfg(all(feature = "wiremock", feature = "api-endpoint"))]
+    #[tokio::test]
+    async fn test_wire_dump() {
+        use serde_json::Value;
+        use wiremock::{
+            matchers::{method, path},
+            Mock, MockServer, ResponseTemplate,
+        };
+
+        use crate::api::{EndpointIn, Svix, SvixOptions};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/app/app_1/endpoint"))
+            .respond_with(
+                ResponseTemplate::new(422)
+                    .insert_header("svix-req-id", "req_1")
+                    .set_body_json(json!({ "detail": [] })),
+            )
+            .mount(&server)
+            .await;
+
+        let dump_path =
+            std::env::temp_dir().join(format!("svix-dump-{}.ndjson", std::process::id()));
+        let _ = std::fs::remove_file(&dump_path);
+        let svix = Svix::new(
+            "testsk_dump".to_owned(),
+            Some(SvixOptions {
+                server_url: Some(server.uri()),
+                debug_dump: Some(dump_path.clone()),
+                ..Default::default()
+            }),
+        );
+        let mut endpoint_in = EndpointIn::new("https://example.com".to_owned());
+        endpoint_in.secret = Some("whsec_C2FVsBQIhrscChlQIMV+b5sSYspob7oD".to_owned());
+        svix.endpoint()
+            .create("app_1".to_owned(), endpoint_in, None)
+            .await
+            .unwrap_err();
+
+        let dump = std::fs::read_to_string(&dump_path).unwrap();
+        std::fs::remove_file(&dump_path).unwrap();
+        let entries: Vec<Value> = dump
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let [entry] = &entries[..] else {
+            panic!("unexpected dump: {dump}");
+        };
+        assert_eq!(entry["request"]["method"], "POST");
+        assert_eq!(entry["request"]["headers"]["authorization"], "[REDACTED]");
+        assert_eq!(entry["request"]["body"]["secret"], "[REDACTED]");
+        assert_eq!(entry["request"]["body"]["url"], "https://example.com");
+        assert_eq!(entry["response"]["status"], 422);
+        assert_eq!(entry["response"]["headers"]["svix-req-id"], "req_1");
+        assert_eq!(entry["response"]["body"], json!({ "detail": [] }));
+        assert!(!dump.contains("testsk_dump"));
+        assert!(!dump.contains("whsec_"));
+    }
+}
@@ -3,9 +3,6 @@
 
 use std::fmt;
 
-use http_body_util::BodyExt;
-use hyper::body::Incoming;
-
 use crate::http1_to_02_status_code;
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -26,23 +23,34 @@ impl Error {
         Self::Generic(format!("{err:?}"))
     }
 
-    pub(crate) async fn from_response(status_code: http1::StatusCode, body: Incoming) -> Self {
-        match body.collect().await {
-            Ok(collected) => {
-                let bytes = collected.to_bytes();
-                if status_code == http1::StatusCode::UNPROCESSABLE_ENTITY {
-                    Self::Validation(HttpErrorContent {
-                        status: http02::StatusCode::UNPROCESSABLE_ENTITY,
-                        payload: serde_json::from_slice(&bytes).ok(),
-                    })
-                } else {
-                    Error::Http(HttpErrorContent {
-                        status: http1_to_02_status_code(status_code),
-                        payload: serde_json::from_slice(&bytes).ok(),
-                    })
-                }
-            }
-            Err(e) => Self::Generic(e.to_string()),
+    pub(crate) fn from_response(status_code: http1::StatusCode, body: &[u8]) -> Self {
+        if status_code == http1::StatusCode::UNPROCESSABLE_ENTITY {
+            Self::Validation(HttpErrorContent {
+                status: http02::StatusCode::UNPROCESSABLE_ENTITY,
+                payload: serde_json::from_slice(body).ok(),
+                idempotency_key: None,
+            })
+        } else {
+            Error::Http(HttpErrorContent {
+                status: http1_to_02_status_code(status_code),
+                payload: serde_json::from_slice(body).ok(),
+                idempotency_key: None,
+            })
+        }
+    }
+
+    /// Adds the idempotency key of the failed request to the error.
+    pub(crate) fn with_idempotency_key(self, key: &str) -> Self {
+        match self {
+            Self::Generic(s) => Self::Generic(format!("{s} (idempotency key: {key})")),
+            Self::Http(e) => Self::Http(HttpErrorContent {
+                idempotency_key: Some(key.to_owned()),
+                ..e
+            }),
+            Self::Validation(e) => Self::Validation(HttpErrorContent {
+                idempotency_key: Some(key.to_owned()),
+                ..e
+            }),
         }
     }
 }
@@ -58,8 +66,14 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Error::Generic(s) => s.fmt(f),
-            Error::Http(e) => format!("Http error (status={}) {:?}", e.status, e.payload).fmt(f),
-            Error::Validation(e) => format!("Validation error {:?}", e.payload).fmt(f),
+            Error::Http(e) => {
+                format!("Http error (status={}) {:?}", e.status, e.payload).fmt(f)?;
+                e.fmt_idempotency_key(f)
+            }
+            Error::Validation(e) => {
+                format!("Validation error {:?}", e.payload).fmt(f)?;
+                e.fmt_idempotency_key(f)
+            }
         }
     }
 }
@@ -70,4 +84,15 @@ impl std::error::Error for Error {}
 pub struct HttpErrorContent<T> {
     pub status: http02::StatusCode,
     pub payload: Option<T>,
+    /// The idempotency key the request was sent with, if any.
+    pub idempotency_key: Option<String>,
+}
+
+impl<T> HttpErrorContent<T> {
+    fn fmt_idempotency_key(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.idempotency_key {
+            Some(key) => write!(f, " (idempotency key: {key})"),
+            None => Ok(()),
+        }
+    }
 }
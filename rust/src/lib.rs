@@ -9,17 +9,26 @@
 #![warn(clippy::all)]
 #![forbid(unsafe_code)]
 
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
 use hyper::body::Bytes;
-use hyper_util::client::legacy::{connect::HttpConnector, Client as HyperClient};
+use hyper_util::client::legacy::Client as HyperClient;
 
 #[macro_use]
 extern crate serde_derive;
 
 pub mod api;
+mod dump;
 pub mod error;
+#[cfg(feature = "opentelemetry")]
+mod otel;
 mod request;
+#[cfg(feature = "sentry")]
+mod sentry;
+#[cfg(feature = "metrics")]
+mod telemetry;
+pub mod testing;
+mod timing;
 pub mod webhooks;
 
 #[rustfmt::skip]
@@ -31,30 +40,57 @@ mod models;
 
 pub struct Configuration {
     pub base_path: String,
+    pub bearer_access_token: Option<String>,
+    /// The settings which don't depend on the token, shared by all clients
+    /// created with [`api::Svix::with_token`].
+    pub shared: Arc<SharedConfiguration>,
+    /// Where responses are cached when no store is set in the options.
+    pub(crate) response_cache: api::MemoryCacheStore,
+    /// The rate limit quota reported with the last response.
+    pub(crate) rate_limit: std::sync::Mutex<Option<api::RateLimit>>,
+}
+
+pub struct SharedConfiguration {
     pub user_agent: Option<String>,
     pub client: HyperClient<Connector, http_body_util::Full<Bytes>>,
-    pub bearer_access_token: Option<String>,
     pub timeout: Option<Duration>,
+    pub cache: Option<Arc<dyn api::CacheStore>>,
+    pub on_request_start: Option<api::RequestStartHook>,
+    pub on_request_end: Option<api::RequestEndHook>,
+    pub on_audit: Option<api::AuditHook>,
+    pub slow_request_threshold: Option<Duration>,
+    pub allow_http_endpoints: bool,
+    pub debug: bool,
+    pub(crate) wire_dump: Option<dump::WireDump>,
+    #[cfg(feature = "opentelemetry")]
+    pub(crate) request_duration: opentelemetry::metrics::Histogram<f64>,
 }
 
+// The HTTP connector, which times its stages for `SvixOptions::debug`.
+type HttpConnector = timing::TimedConnector<
+    hyper_util::client::legacy::connect::HttpConnector<timing::TimedResolver>,
+>;
+
 // If no TLS backend is enabled, use plain http connector.
 #[cfg(not(any(feature = "native-tls", feature = "rustls-tls")))]
 type Connector = HttpConnector;
 
 // If only native TLS is enabled, use that.
 #[cfg(all(feature = "native-tls", not(feature = "rustls-tls")))]
-type Connector = hyper_tls::HttpsConnector<HttpConnector>;
+type Connector = timing::TimedConnector<hyper_tls::HttpsConnector<HttpConnector>>;
 
 // If rustls is enabled, use that.
 #[cfg(feature = "rustls-tls")]
-type Connector = hyper_rustls::HttpsConnector<HttpConnector>;
+type Connector = timing::TimedConnector<hyper_rustls::HttpsConnector<HttpConnector>>;
 
 fn default_connector() -> Connector {
     #[cfg(not(any(feature = "native-tls", feature = "rustls-tls")))]
-    return hyper_util::client::legacy::connect::HttpConnector::new();
+    return timing::http_connector(true);
 
     #[cfg(all(feature = "native-tls", not(feature = "rustls-tls")))]
-    return hyper_tls::HttpsConnector::new();
+    return timing::tls_connector(hyper_tls::HttpsConnector::new_with_connector(
+        timing::http_connector(false),
+    ));
 
     #[cfg(feature = "rustls-tls")]
     {
@@ -69,7 +105,7 @@ fn default_connector() -> Connector {
         #[cfg(feature = "http2")]
         let builder = builder.enable_http2();
 
-        builder.build()
+        timing::tls_connector(builder.wrap_connector(timing::http_connector(false)))
     }
 }
 
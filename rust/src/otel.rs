@@ -0,0 +1,204 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! OpenTelemetry instrumentation of the requests sent by the client, enabled
+//! with the `opentelemetry` feature.
+//!
+//! Every request gets a client span from the global tracer provider,
+//! following the HTTP semantic conventions, and its context is propagated to
+//! the server with the global propagator. Request durations are recorded as
+//! `http.client.request.duration`, with the meter from
+//! [`SvixOptions::meter`](crate::api::SvixOptions::meter) or else the global
+//! meter provider.
+
+use std::{collections::HashMap, time::Duration};
+
+use opentelemetry::{
+    global,
+    metrics::{Histogram, Meter},
+    trace::{SpanKind, Status, TraceContextExt as _, Tracer as _},
+    Context, KeyValue,
+};
+
+const INSTRUMENTATION_NAME: &str = "svix";
+
+/// The bucket boundaries recommended by the semantic conventions, in seconds.
+const DURATION_BOUNDARIES: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.075, 0.1, 0.25, 0.5, 0.75, 1.0, 2.5, 5.0, 7.5, 10.0,
+];
+
+/// The `http.client.request.duration` histogram.
+pub(crate) fn request_duration(meter: Option<Meter>) -> Histogram<f64> {
+    meter
+        .unwrap_or_else(|| global::meter(INSTRUMENTATION_NAME))
+        .f64_histogram("http.client.request.duration")
+        .with_unit("s")
+        .with_description("Duration of HTTP client requests.")
+        .with_boundaries(DURATION_BOUNDARIES.to_vec())
+        .build()
+}
+
+/// The client span of a request, and the attributes it shares with the
+/// duration metric.
+pub(crate) struct RequestSpan {
+    cx: Context,
+    attributes: Vec<KeyValue>,
+}
+
+impl RequestSpan {
+    /// Starts the span as a child of the current context, and adds the
+    /// headers propagating it to `headers`.
+    pub(crate) fn start(
+        method: &str,
+        endpoint: &str,
+        uri: &str,
+        headers: &mut HashMap<String, String>,
+    ) -> Self {
+        let mut attributes = vec![
+            KeyValue::new("http.request.method", method.to_owned()),
+            KeyValue::new("url.template", endpoint.to_owned()),
+        ];
+        if let Ok(uri) = uri.parse::<http1::Uri>() {
+            if let Some(host) = uri.host() {
+                attributes.push(KeyValue::new("server.address", host.to_owned()));
+            }
+            let port = uri.port_u16().or_else(|| match uri.scheme_str() {
+                Some("https") => Some(443),
+                Some("http") => Some(80),
+                _ => None,
+            });
+            if let Some(port) = port {
+                attributes.push(KeyValue::new("server.port", i64::from(port)));
+            }
+        }
+
+        let tracer = global::tracer(INSTRUMENTATION_NAME);
+        let span = tracer
+            .span_builder(format!("{method} {endpoint}"))
+            .with_kind(SpanKind::Client)
+            .with_attributes(
+                attributes
+                    .iter()
+                    .cloned()
+                    .chain([KeyValue::new("url.full", uri.to_owned())]),
+            )
+            .start(&tracer);
+        let cx = Context::current_with_span(span);
+        global::get_text_map_propagator(|propagator| propagator.inject_context(&cx, headers));
+
+        Self { cx, attributes }
+    }
+
+    /// Ends the span and records the request's duration.
+    pub(crate) fn end(
+        mut self,
+        request_duration: &Histogram<f64>,
+        status: Option<u16>,
+        elapsed: Duration,
+    ) {
+        if let Some(status) = status {
+            self.attributes.push(KeyValue::new(
+                "http.response.status_code",
+                i64::from(status),
+            ));
+        }
+        let error_type = match status {
+            Some(status) if status >= 400 => Some(status.to_string()),
+            Some(_) => None,
+            None => Some("_OTHER".to_owned()),
+        };
+        if let Some(error_type) = &error_type {
+            self.attributes
+                .push(KeyValue::new("error.type", error_type.clone()));
+        }
+
+        request_duration.record(elapsed.as_secs_f64(), &self.attributes);
+
+        let span = self.cx.span();
+        if let Some(status) = status {
+            span.set_attribute(KeyValue::new(
+                "http.response.status_code",
+                i64::from(status),
+            ));
+        }
+        if let Some(error_type) = error_type {
+            span.set_attribute(KeyValue::new("error.type", error_type.clone()));
+            span.set_status(Status::error(error_type));
+        }
+        span.end();
+    }
+}
+
+#[cfg(all(test, feature = "wiremock", feature = "api-application"))]
+mod tests {
+    use opentelemetry::{global, metrics::MeterProvider as _, trace::SpanKind, KeyValue};
+    use opentelemetry_sdk::{
+        metrics::{InMemoryMetricExporter, SdkMeterProvider},
+        propagation::TraceContextPropagator,
+        trace::{InMemorySpanExporter, SdkTracerProvider},
+    };
+    use wiremock::{
+        matchers::{header_exists, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::api::{Svix, SvixOptions};
+
+    #[tokio::test]
+    async fn test_request_span() {
+        let span_exporter = InMemorySpanExporter::default();
+        global::set_tracer_provider(
+            SdkTracerProvider::builder()
+                .with_simple_exporter(span_exporter.clone())
+                .build(),
+        );
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        let metric_exporter = InMemoryMetricExporter::default();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_periodic_exporter(metric_exporter.clone())
+            .build();
+
+        let server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/api/v1/app/app_1"))
+            .and(header_exists("traceparent"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let svix = Svix::new(
+            "testsk_otel".to_owned(),
+            Some(SvixOptions {
+                server_url: Some(server.uri()),
+                meter: Some(meter_provider.meter("test")),
+                ..Default::default()
+            }),
+        );
+        svix.application()
+            .delete("app_1".to_owned())
+            .await
+            .unwrap_err();
+
+        let spans = span_exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        let span = &spans[0];
+        assert_eq!(span.name, "DELETE /api/v1/app/{app_id}");
+        assert_eq!(span.span_kind, SpanKind::Client);
+        for attribute in [
+            KeyValue::new("http.request.method", "DELETE"),
+            KeyValue::new("server.address", "127.0.0.1"),
+            KeyValue::new("http.response.status_code", 404),
+            KeyValue::new("error.type", "404"),
+        ] {
+            assert!(span.attributes.contains(&attribute), "{attribute:?}");
+        }
+
+        meter_provider.force_flush().unwrap();
+        let metrics = metric_exporter.get_finished_metrics().unwrap();
+        assert!(metrics
+            .iter()
+            .flat_map(|resource| resource.scope_metrics())
+            .flat_map(|scope| scope.metrics())
+            .any(|metric| metric.name() == "http.client.request.duration"));
+    }
+}
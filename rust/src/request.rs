@@ -1,13 +1,32 @@
 // Modified version of the file openapi-generator would usually put in
 // apis/request.rs
 
-use std::collections::HashMap;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    time::{Duration, Instant},
+};
 
-use http1::header::{HeaderValue, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, USER_AGENT};
+use bytes::Bytes;
+use http1::{
+    header::{
+        HeaderValue, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE,
+        IF_NONE_MATCH, LAST_MODIFIED, USER_AGENT,
+    },
+    HeaderMap, StatusCode,
+};
 use http_body_util::{BodyExt as _, Full};
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Serialize};
 
-use crate::{error::Error, Configuration};
+use crate::{
+    api::{
+        AuditEvent, AuditHook, AuditOutcome, CacheStore, CachedResponse, RateLimit, RequestEnd,
+        RequestStart, RequestTimings,
+    },
+    dump::DumpedRequest,
+    error::Error,
+    timing, Configuration,
+};
 
 #[allow(dead_code)]
 pub(crate) enum Auth {
@@ -20,6 +39,8 @@ pub(crate) enum Auth {
 /// OpenAPI definition does not include an authorization scheme.
 pub(crate) struct Request {
     auth: Option<Auth>,
+    /// The operation ID from the API reference, for generated requests.
+    operation: Option<&'static str>,
     method: http1::Method,
     path: String,
     query_params: HashMap<String, String>,
@@ -28,7 +49,38 @@ pub(crate) struct Request {
     form_params: HashMap<String, String>,
     header_params: HashMap<String, String>,
     // TODO: multiple body params are possible technically, but not supported here.
-    serialized_body: Option<String>,
+    serialized_body: Option<Bytes>,
+}
+
+/// The capacity the body serialization buffer starts with.
+const BODY_BUFFER_CAPACITY: usize = 8 * 1024;
+/// Buffers which grew beyond this for a large body aren't reused.
+const MAX_BODY_BUFFER_CAPACITY: usize = 1024 * 1024;
+
+thread_local! {
+    static BODY_BUFFER: RefCell<Vec<u8>> = RefCell::new(Vec::with_capacity(BODY_BUFFER_CAPACITY));
+}
+
+/// The header with the ID the API assigned to a request.
+const REQUEST_ID_HEADER: &str = "svix-req-id";
+
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// What [`Request::send`] learned about the response, even if it's an error.
+#[derive(Default)]
+struct ResponseInfo {
+    headers: Option<HeaderMap>,
+    /// How long it took until the response headers were received.
+    time_to_first_byte: Option<Duration>,
+    /// How long receiving the body took.
+    body: Duration,
+}
+
+/// A successful response, or a `304 Not Modified` one.
+struct Response {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
 }
 
 #[allow(dead_code)]
@@ -36,6 +88,7 @@ impl Request {
     pub fn new(method: http1::Method, path: String) -> Self {
         Request {
             auth: None,
+            operation: None,
             method,
             path,
             query_params: HashMap::new(),
@@ -47,8 +100,13 @@ impl Request {
         }
     }
 
-    pub fn with_body_param<T: serde::Serialize>(mut self, param: T) -> Self {
-        self.serialized_body = Some(serde_json::to_string(&param).unwrap());
+    pub fn with_operation(mut self, operation: &'static str) -> Self {
+        self.operation = Some(operation);
+        self
+    }
+
+    pub fn with_body_param<T: Serialize>(mut self, param: T) -> Self {
+        self.serialized_body = Some(serialize_body(&param));
         self
     }
 
@@ -86,31 +144,286 @@ impl Request {
     }
 
     pub async fn execute<T: DeserializeOwned>(self, conf: &Configuration) -> Result<T, Error> {
-        let mut path = self.path;
-        for (k, v) in self.path_params {
-            // replace {id} with the value of the id path param
-            path = path.replace(&format!("{{{k}}}"), &v);
+        if self.no_return_type {
+            self.execute_raw(conf).await?;
+            // This is a hack; if there's no_ret_type, U is (), but serde_json gives an
+            // error when deserializing "" into (), so deserialize 'null' into it
+            // instead.
+            // An alternate option would be to require U: Default, and then return
+            // U::default() here instead since () implements that, but then we'd
+            // need to impl default for all models.
+            return Ok(serde_json::from_str("null").expect("serde null value"));
         }
 
-        let mut uri = format!("{}{}", conf.base_path, path);
+        if self.method == http1::Method::GET && conf.shared.cache.is_some() {
+            return self.execute_cached(conf).await;
+        }
 
-        // Work around rustc issue - we need to make sure that `query_string` is
-        // not captured by the outer `async` generator. Using
-        // `drop(query_string)` is insufficient, so we create a new scope
-        {
-            let mut query_string = ::url::form_urlencoded::Serializer::new("".to_owned());
-            for (key, val) in self.query_params {
-                query_string.append_pair(&key, &val);
+        let bytes = self.execute_raw(conf).await?;
+        from_body(bytes)
+    }
+
+    /// Like [`execute`](Self::execute), but sends the cache validators of
+    /// the last response to the same URL, if it's cached, and uses the cached
+    /// body when the server responds with `304 Not Modified`.
+    ///
+    /// Responses are cached in the store from the options, or if there's none
+    /// in the configuration's own store.
+    pub async fn execute_cached<T: DeserializeOwned>(
+        mut self,
+        conf: &Configuration,
+    ) -> Result<T, Error> {
+        let store: &dyn CacheStore = match &conf.shared.cache {
+            Some(store) => &**store,
+            None => &conf.response_cache,
+        };
+        let key = self.cache_key(conf);
+        let cached = store.get(&key);
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.etag {
+                self.header_params
+                    .insert(IF_NONE_MATCH.to_string(), etag.clone());
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                self.header_params
+                    .insert(IF_MODIFIED_SINCE.to_string(), last_modified.clone());
             }
+        }
 
-            let query_string_str = query_string.finish();
-            if !query_string_str.is_empty() {
-                uri += "?";
-                uri += &query_string_str;
+        let response = self.execute_response(conf).await?;
+        let body = match cached {
+            Some(cached) if response.status == StatusCode::NOT_MODIFIED => cached.body,
+            _ => {
+                let header = |name| {
+                    response
+                        .headers
+                        .get(name)
+                        .and_then(|value: &HeaderValue| value.to_str().ok())
+                        .map(ToOwned::to_owned)
+                };
+                let etag = header(ETAG);
+                let last_modified = header(LAST_MODIFIED);
+                if etag.is_some() || last_modified.is_some() {
+                    store.put(
+                        key,
+                        CachedResponse {
+                            etag,
+                            last_modified,
+                            body: response.body.clone(),
+                        },
+                    );
+                }
+                response.body
             }
+        };
+        from_body(body)
+    }
+
+    /// Sends the request, returning the body of a successful response as is.
+    ///
+    /// The body is collected into a single buffer, which callers can
+    /// deserialize borrowed data from.
+    pub async fn execute_raw(self, conf: &Configuration) -> Result<Bytes, Error> {
+        self.execute_response(conf)
+            .await
+            .map(|response| response.body)
+    }
+
+    /// The URL of the request, with the path and query parameters filled in.
+    fn uri(&self, conf: &Configuration) -> String {
+        let mut path = self.path.clone();
+        for (k, v) in &self.path_params {
+            // replace {id} with the value of the id path param
+            path = path.replace(&format!("{{{k}}}"), v);
+        }
+
+        let mut uri = format!("{}{}", conf.base_path, path);
+
+        // Sorted, so the same request always has the same URL
+        let mut query_params: Vec<_> = self.query_params.iter().collect();
+        query_params.sort();
+        let mut query_string = form_urlencoded::Serializer::new("".to_owned());
+        for (key, val) in query_params {
+            query_string.append_pair(key, val);
+        }
+
+        let query_string_str = query_string.finish();
+        if !query_string_str.is_empty() {
+            uri += "?";
+            uri += &query_string_str;
+        }
+        uri
+    }
+
+    /// The key of the request's response in a [`CacheStore`].
+    ///
+    /// Keys are stable across builds, so they keep working with stores that
+    /// persist responses or share them between processes.
+    pub(crate) fn cache_key(&self, conf: &Configuration) -> String {
+        let token = conf.bearer_access_token.as_deref().unwrap_or_default();
+        let fingerprint = hmac_sha256::Hash::hash(token.as_bytes());
+        let fingerprint: String = fingerprint[..8]
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        format!("{fingerprint} {}", self.uri(conf))
+    }
+
+    async fn execute_response(
+        #[allow(unused_mut)] mut self,
+        conf: &Configuration,
+    ) -> Result<Response, Error> {
+        let method = self.method.clone();
+        let endpoint = self.path.clone();
+        // The client doesn't retry requests itself
+        let attempt = 1;
+        let idempotency_key = self.header_params.get(IDEMPOTENCY_KEY_HEADER).cloned();
+        #[cfg(feature = "sentry")]
+        let body_size = self.serialized_body.as_ref().map(Bytes::len);
+        let audit = conf
+            .shared
+            .on_audit
+            .as_ref()
+            .filter(|_| {
+                matches!(
+                    method,
+                    http1::Method::POST
+                        | http1::Method::PUT
+                        | http1::Method::PATCH
+                        | http1::Method::DELETE
+                )
+            })
+            .map(|on_audit| {
+                let mut resource_ids: Vec<_> = self.path_params.clone().into_iter().collect();
+                resource_ids.sort();
+                (on_audit, self.operation, resource_ids)
+            });
+
+        if let Some(on_request_start) = &conf.shared.on_request_start {
+            on_request_start(&RequestStart {
+                method: method.as_str(),
+                endpoint: &endpoint,
+                attempt,
+                idempotency_key: idempotency_key.as_deref(),
+            });
+        }
+        #[cfg(feature = "opentelemetry")]
+        let span = crate::otel::RequestSpan::start(
+            method.as_str(),
+            &endpoint,
+            &self.uri(conf),
+            &mut self.header_params,
+        );
+        #[cfg(feature = "metrics")]
+        let in_flight = crate::telemetry::InFlight::start();
+        let start = Instant::now();
+        let mut info = ResponseInfo::default();
+        let (result, connect) = if conf.shared.debug {
+            let (result, connect) = timing::record_connect(self.send(conf, &mut info)).await;
+            (result, Some(connect))
+        } else {
+            (self.send(conf, &mut info).await, None)
+        };
+        let elapsed = start.elapsed();
+        #[cfg(feature = "metrics")]
+        drop(in_flight);
+
+        let status = match &result {
+            Ok(response) => Some(response.status.as_u16()),
+            Err(Error::Http(e)) => Some(e.status.as_u16()),
+            Err(Error::Validation(e)) => Some(e.status.as_u16()),
+            Err(Error::Generic(_)) => None,
+        };
+        let request_id = info
+            .headers
+            .as_ref()
+            .and_then(|headers| headers.get(REQUEST_ID_HEADER)?.to_str().ok());
+        let rate_limit = info.headers.as_ref().and_then(RateLimit::from_headers);
+        let timings = connect
+            .zip(info.time_to_first_byte)
+            .map(|(connect, time_to_first_byte)| RequestTimings {
+                dns: connect.dns,
+                connect: connect
+                    .tcp
+                    .map(|tcp| tcp.saturating_sub(connect.dns.unwrap_or_default())),
+                tls: connect
+                    .total
+                    .zip(connect.tcp)
+                    .filter(|_| conf.base_path.starts_with("https:"))
+                    .map(|(total, tcp)| total.saturating_sub(tcp)),
+                time_to_first_byte,
+                body: info.body,
+            });
+        if let Some(rate_limit) = rate_limit {
+            *conf.rate_limit.lock().unwrap() = Some(rate_limit);
+            #[cfg(feature = "metrics")]
+            crate::telemetry::rate_limit(&rate_limit);
+        }
+        #[cfg(feature = "metrics")]
+        crate::telemetry::request_finished(method.as_str(), &endpoint, status, elapsed);
+        #[cfg(feature = "opentelemetry")]
+        span.end(&conf.shared.request_duration, status, elapsed);
+
+        let end = RequestEnd {
+            method: method.as_str(),
+            endpoint: &endpoint,
+            attempt,
+            idempotency_key: idempotency_key.as_deref(),
+            status,
+            elapsed,
+            request_id,
+            rate_limit,
+            timings,
+        };
+        if let Some(timings) = &end.timings {
+            tracing::debug!(
+                method = end.method,
+                endpoint = end.endpoint,
+                status = end.status,
+                dns = ?timings.dns,
+                connect = ?timings.connect,
+                tls = ?timings.tls,
+                time_to_first_byte = ?timings.time_to_first_byte,
+                body = ?timings.body,
+                request_id = end.request_id,
+                "Svix API request timings"
+            );
+        }
+        if conf
+            .shared
+            .slow_request_threshold
+            .is_some_and(|threshold| elapsed > threshold)
+        {
+            tracing::warn!(
+                method = end.method,
+                endpoint = end.endpoint,
+                status = end.status,
+                elapsed = ?end.elapsed,
+                request_id = end.request_id,
+                idempotency_key = end.idempotency_key,
+                "slow Svix API request"
+            );
+        }
+        #[cfg(feature = "sentry")]
+        crate::sentry::request_finished(&end, body_size);
+        if let Some(on_request_end) = &conf.shared.on_request_end {
+            on_request_end(&end);
+        }
+        if let Some((on_audit, operation, resource_ids)) = audit {
+            audit_request(on_audit, operation, &resource_ids, &end, &result);
         }
+        match idempotency_key {
+            Some(key) => result.map_err(|e| e.with_idempotency_key(&key)),
+            None => result,
+        }
+    }
 
-        let mut req_builder = http1::Request::builder().uri(uri).method(self.method);
+    /// Sends the request, filling in `info` as the response is received.
+    async fn send(self, conf: &Configuration, info: &mut ResponseInfo) -> Result<Response, Error> {
+        let uri = self.uri(conf);
+        let mut req_builder = http1::Request::builder()
+            .uri(&uri)
+            .method(self.method.clone());
 
         // Detect the authorization type if it hasn't been set.
         let auth = self.auth.unwrap_or_else(|| {
@@ -129,7 +442,7 @@ impl Request {
             Auth::None => {}
         }
 
-        if let Some(user_agent) = &conf.user_agent {
+        if let Some(user_agent) = &conf.shared.user_agent {
             req_builder = req_builder.header(
                 USER_AGENT,
                 HeaderValue::from_str(user_agent).map_err(Error::generic)?,
@@ -141,57 +454,198 @@ impl Request {
         }
 
         let req_headers = req_builder.headers_mut().unwrap();
-        let request = if !self.form_params.is_empty() {
+        let body = if !self.form_params.is_empty() {
             req_headers.insert(
                 CONTENT_TYPE,
                 HeaderValue::from_static("application/x-www-form-urlencoded"),
             );
-            let mut enc = ::url::form_urlencoded::Serializer::new(String::new());
+            let mut enc = form_urlencoded::Serializer::new(String::new());
             for (k, v) in self.form_params {
                 enc.append_pair(&k, &v);
             }
-            req_builder
-                .body(Full::from(enc.finish()))
-                .map_err(Error::generic)?
+            Bytes::from(enc.finish())
         } else if let Some(body) = self.serialized_body {
             req_headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
             req_headers.insert(CONTENT_LENGTH, body.len().into());
-            req_builder.body(Full::from(body)).map_err(Error::generic)?
+            body
         } else {
-            req_builder.body(Full::default()).map_err(Error::generic)?
+            Bytes::new()
         };
+        let request = req_builder
+            .body(Full::from(body.clone()))
+            .map_err(Error::generic)?;
+        let request_headers = conf
+            .shared
+            .wire_dump
+            .as_ref()
+            .map(|_| request.headers().clone());
 
+        let start = Instant::now();
         let execute_request = async {
-            let response = conf.client.request(request).await.map_err(Error::generic)?;
-
-            let status = response.status();
-            if !status.is_success() {
-                Err(Error::from_response(status, response.into_body()).await)
-            } else if self.no_return_type {
-                // This is a hack; if there's no_ret_type, U is (), but serde_json gives an
-                // error when deserializing "" into (), so deserialize 'null' into it
-                // instead.
-                // An alternate option would be to require U: Default, and then return
-                // U::default() here instead since () implements that, but then we'd
-                // need to impl default for all models.
-                Ok(serde_json::from_str("null").expect("serde null value"))
-            } else {
-                let bytes = response
-                    .into_body()
-                    .collect()
-                    .await
-                    .map_err(Error::generic)?
-                    .to_bytes();
-                Ok(serde_json::from_slice(&bytes).map_err(Error::generic)?)
-            }
+            let response = conf
+                .shared
+                .client
+                .request(request)
+                .await
+                .map_err(Error::generic)?;
+
+            let received = Instant::now();
+            info.time_to_first_byte = Some(received - start);
+            info.headers = Some(response.headers().clone());
+
+            let (parts, body) = response.into_parts();
+            let body = body.collect().await.map_err(Error::generic);
+            info.body = received.elapsed();
+            Ok((parts, body?.to_bytes()))
         };
 
-        if let Some(duration) = conf.timeout {
+        let result = if let Some(duration) = conf.shared.timeout {
             tokio::time::timeout(duration, execute_request)
                 .await
-                .map_err(Error::generic)?
+                .map_err(Error::generic)
+                .and_then(|result| result)
         } else {
             execute_request.await
+        };
+        if let Some((wire_dump, headers)) = conf.shared.wire_dump.as_ref().zip(request_headers) {
+            let request = DumpedRequest {
+                method: self.method.as_str(),
+                uri: &uri,
+                headers: &headers,
+                body: &body,
+            };
+            wire_dump.write(&request, result.as_ref(), start.elapsed());
+        }
+
+        let (parts, body) = result?;
+        let status = parts.status;
+        if !status.is_success() && status != StatusCode::NOT_MODIFIED {
+            Err(Error::from_response(status, &body))
+        } else {
+            Ok(Response {
+                status,
+                headers: parts.headers,
+                body,
+            })
         }
     }
 }
+
+/// Passes a request which changed something to the audit hook.
+fn audit_request(
+    on_audit: &AuditHook,
+    operation: Option<&str>,
+    resource_ids: &[(String, String)],
+    end: &RequestEnd<'_>,
+    result: &Result<Response, Error>,
+) {
+    #[derive(Deserialize)]
+    struct ResponseId {
+        id: String,
+    }
+
+    let response_id = result
+        .as_ref()
+        .ok()
+        .and_then(|response| serde_json::from_slice::<ResponseId>(&response.body).ok());
+    let resource_ids: Vec<_> = resource_ids
+        .iter()
+        .map(|(name, id)| (name.as_str(), id.as_str()))
+        .collect();
+    on_audit(&AuditEvent {
+        operation,
+        method: end.method,
+        endpoint: end.endpoint,
+        resource_ids: &resource_ids,
+        response_id: response_id.as_ref().map(|response| response.id.as_str()),
+        outcome: match result {
+            Ok(_) => AuditOutcome::Succeeded,
+            Err(_) => AuditOutcome::Failed,
+        },
+        status: end.status,
+        request_id: end.request_id,
+        idempotency_key: end.idempotency_key,
+    });
+}
+
+/// Serializes a request body, using the current thread's buffer.
+///
+/// The body is serialized into the buffer, which only grows once for large
+/// bodies instead of for every body, and then copied into an allocation of
+/// its own size. The buffer is never shared with the body, so it's reused
+/// for the next body right away, however many requests are in flight.
+fn serialize_body<T: Serialize>(value: &T) -> Bytes {
+    BODY_BUFFER.with_borrow_mut(|buf| {
+        buf.clear();
+        serde_json::to_writer(&mut *buf, value).unwrap();
+        let body = Bytes::copy_from_slice(buf);
+        if buf.capacity() > MAX_BODY_BUFFER_CAPACITY {
+            *buf = Vec::with_capacity(BODY_BUFFER_CAPACITY);
+        }
+        body
+    })
+}
+
+/// Deserializes a response body, using simd-json when the `simd-json` feature
+/// is enabled.
+fn from_body<T: DeserializeOwned>(body: Bytes) -> Result<T, Error> {
+    #[cfg(feature = "simd-json")]
+    {
+        // simd-json parses in place, converting only copies the body if its
+        // buffer is shared.
+        let mut body = Vec::from(body);
+        simd_json::serde::from_slice(&mut body).map_err(Error::generic)
+    }
+
+    #[cfg(not(feature = "simd-json"))]
+    serde_json::from_slice(&body).map_err(Error::generic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{serialize_body, BODY_BUFFER, MAX_BODY_BUFFER_CAPACITY};
+
+    #[test]
+    fn test_body_buffer_reuse() {
+        let buffer = || BODY_BUFFER.with_borrow(|buf| buf.as_ptr());
+        let first = serialize_body(&serde_json::json!({ "eventType": "a" }));
+        assert_eq!(&first[..], br#"{"eventType":"a"}"#);
+        let ptr = buffer();
+
+        // The buffer is reused while the first body is still in use, and the
+        // bodies only take up their own size
+        let second = serialize_body(&"b");
+        assert_eq!(buffer(), ptr);
+        assert_ne!(second.as_ptr(), first.as_ptr());
+        assert_eq!(Vec::from(second).capacity(), 3);
+
+        let large = "x".repeat(2 * MAX_BODY_BUFFER_CAPACITY);
+        assert_eq!(serialize_body(&large).len(), large.len() + 2);
+        assert!(BODY_BUFFER.with_borrow(|buf| buf.capacity()) <= MAX_BODY_BUFFER_CAPACITY);
+    }
+
+    #[test]
+    fn test_concurrent_bodies() {
+        // Like concurrent requests, many bodies are alive at once on several
+        // threads
+        std::thread::scope(|scope| {
+            for thread in 0..4 {
+                scope.spawn(move || {
+                    let bodies: Vec<_> = (0..256)
+                        .map(|i| {
+                            (
+                                i,
+                                serialize_body(&serde_json::json!({ "thread": thread, "i": i })),
+                            )
+                        })
+                        .collect();
+                    for (i, body) in bodies {
+                        let expected = format!(r#"{{"i":{i},"thread":{thread}}}"#);
+                        assert_eq!(body, expected.as_bytes());
+                        assert_eq!(Vec::from(body).capacity(), expected.len());
+                    }
+                });
+            }
+        });
+    }
+}
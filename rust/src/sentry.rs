@@ -0,0 +1,127 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Sentry breadcrumbs and events for the requests sent by the client,
+//! enabled with the `sentry` feature.
+//!
+//! Every request leaves a breadcrumb on the current hub, and requests which
+//! failed without a response, with a server error or because they were rate
+//! limited are captured as events. Errors the caller likely expects, like
+//! `404 Not Found`, are only breadcrumbs. Request bodies are never sent to
+//! Sentry, only their size.
+
+use sentry_core::protocol::{Breadcrumb, Event, Level, Map};
+
+use crate::api::RequestEnd;
+
+pub(crate) fn request_finished(end: &RequestEnd<'_>, body_size: Option<usize>) {
+    let mut data = Map::new();
+    data.insert("method".to_owned(), end.method.into());
+    data.insert("endpoint".to_owned(), end.endpoint.into());
+    if let Some(status) = end.status {
+        data.insert("status_code".to_owned(), status.into());
+    }
+    data.insert(
+        "duration_ms".to_owned(),
+        (end.elapsed.as_millis() as u64).into(),
+    );
+    if let Some(request_id) = end.request_id {
+        data.insert("request_id".to_owned(), request_id.into());
+    }
+    if let Some(key) = end.idempotency_key {
+        data.insert("idempotency_key".to_owned(), key.into());
+    }
+    if let Some(body_size) = body_size {
+        data.insert("body_size".to_owned(), body_size.into());
+    }
+
+    let failed = match end.status {
+        Some(status) => status >= 500 || status == 429,
+        None => true,
+    };
+    let level = match end.status {
+        Some(status) if status < 400 => Level::Info,
+        _ => Level::Error,
+    };
+    sentry_core::add_breadcrumb(Breadcrumb {
+        ty: "http".to_owned(),
+        category: Some("svix".to_owned()),
+        level,
+        data: data.clone(),
+        ..Default::default()
+    });
+
+    if failed {
+        let status = end
+            .status
+            .map_or_else(|| "no response".to_owned(), |status| status.to_string());
+        let mut tags = Map::new();
+        tags.insert("svix.endpoint".to_owned(), end.endpoint.to_owned());
+        tags.insert("svix.status_code".to_owned(), status.clone());
+        sentry_core::capture_event(Event {
+            message: Some(format!(
+                "Svix API request failed: {} {} ({status})",
+                end.method, end.endpoint
+            )),
+            level: Level::Error,
+            tags,
+            extra: data,
+            ..Default::default()
+        });
+    }
+}
+
+#[cfg(all(test, feature = "wiremock", feature = "api-application"))]
+mod tests {
+    use sentry_core::protocol::Value;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::testing::wiremock::svix_client;
+
+    #[test]
+    fn test_request_events() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        // The test hub only applies to this thread, which the current-thread
+        // runtime polls the requests on
+        let events = sentry_core::test::with_captured_events(|| {
+            runtime.block_on(async {
+                let server = MockServer::start().await;
+                Mock::given(method("GET"))
+                    .and(path("/api/v1/app/app_1"))
+                    .respond_with(ResponseTemplate::new(404))
+                    .mount(&server)
+                    .await;
+                Mock::given(method("GET"))
+                    .and(path("/api/v1/app/app_2"))
+                    .respond_with(ResponseTemplate::new(503).insert_header("svix-req-id", "req_2"))
+                    .mount(&server)
+                    .await;
+
+                let svix = svix_client(&server);
+                for app_id in ["app_1", "app_2"] {
+                    svix.application().get(app_id.to_owned()).await.unwrap_err();
+                }
+            })
+        });
+
+        // Only the server error is captured, after the breadcrumb of the 404
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.tags["svix.endpoint"], "/api/v1/app/{app_id}");
+        assert_eq!(event.tags["svix.status_code"], "503");
+        assert_eq!(event.extra["request_id"], Value::from("req_2"));
+        let statuses: Vec<_> = event
+            .breadcrumbs
+            .iter()
+            .map(|breadcrumb| breadcrumb.data["status_code"].clone())
+            .collect();
+        assert_eq!(statuses, [Value::from(404), Value::from(503)]);
+    }
+}
@@ -0,0 +1,138 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Metrics of the requests sent by the client, recorded with the `metrics`
+//! facade when the `metrics` feature is enabled:
+//!
+//! - `svix_requests_total`: counter of finished requests, by `method`,
+//!   `endpoint` and `status` (`none` when there was no response).
+//! - `svix_request_duration_seconds`: histogram of request durations, by
+//!   `method` and `endpoint`.
+//! - `svix_requests_in_flight`: gauge of requests which haven't finished.
+//! - `svix_rate_limit_remaining`: gauge of the rate limit quota left, as
+//!   reported by the API.
+//!
+//! The `endpoint` label is the path template from the API reference, e.g.
+//! `/api/v1/app/{app_id}`, so it doesn't grow with the number of IDs.
+
+use std::time::Duration;
+
+use crate::api::RateLimit;
+
+const REQUESTS_TOTAL: &str = "svix_requests_total";
+const REQUEST_DURATION_SECONDS: &str = "svix_request_duration_seconds";
+const REQUESTS_IN_FLIGHT: &str = "svix_requests_in_flight";
+const RATE_LIMIT_REMAINING: &str = "svix_rate_limit_remaining";
+
+/// Counts a request as in flight until it's dropped, so requests which are
+/// cancelled are counted too.
+pub(crate) struct InFlight(());
+
+impl InFlight {
+    pub(crate) fn start() -> Self {
+        metrics::gauge!(REQUESTS_IN_FLIGHT).increment(1.0);
+        Self(())
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        metrics::gauge!(REQUESTS_IN_FLIGHT).decrement(1.0);
+    }
+}
+
+pub(crate) fn request_finished(
+    method: &str,
+    endpoint: &str,
+    status: Option<u16>,
+    elapsed: Duration,
+) {
+    let status = status.map_or_else(|| "none".to_owned(), |status| status.to_string());
+    metrics::counter!(
+        REQUESTS_TOTAL,
+        "method" => method.to_owned(),
+        "endpoint" => endpoint.to_owned(),
+        "status" => status,
+    )
+    .increment(1);
+    metrics::histogram!(
+        REQUEST_DURATION_SECONDS,
+        "method" => method.to_owned(),
+        "endpoint" => endpoint.to_owned(),
+    )
+    .record(elapsed);
+}
+
+pub(crate) fn rate_limit(rate_limit: &RateLimit) {
+    metrics::gauge!(RATE_LIMIT_REMAINING).set(rate_limit.remaining as f64);
+}
+
+#[cfg(all(test, feature = "wiremock", feature = "api-application"))]
+mod tests {
+    use metrics_util::{
+        debugging::{DebugValue, DebuggingRecorder},
+        MetricKind,
+    };
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::testing::wiremock::svix_client;
+
+    #[test]
+    fn test_request_metrics() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        // The local recorder only applies to this thread, which the
+        // current-thread runtime polls the requests on
+        metrics::with_local_recorder(&recorder, || {
+            runtime.block_on(async {
+                let server = MockServer::start().await;
+                Mock::given(method("DELETE"))
+                    .and(path("/api/v1/app/app_1"))
+                    .respond_with(ResponseTemplate::new(204))
+                    .mount(&server)
+                    .await;
+                svix_client(&server)
+                    .application()
+                    .delete("app_1".to_owned())
+                    .await
+                    .unwrap();
+            })
+        });
+
+        let metrics = snapshotter.snapshot().into_vec();
+        let find = |kind, name: &str| {
+            metrics
+                .iter()
+                .find(|(key, ..)| key.kind() == kind && key.key().name() == name)
+                .unwrap_or_else(|| panic!("{name} wasn't recorded"))
+        };
+
+        let (key, _, _, value) = find(MetricKind::Counter, "svix_requests_total");
+        let labels: Vec<_> = key
+            .key()
+            .labels()
+            .map(|label| (label.key(), label.value()))
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                ("method", "DELETE"),
+                ("endpoint", "/api/v1/app/{app_id}"),
+                ("status", "204"),
+            ]
+        );
+        assert_eq!(value, &DebugValue::Counter(1));
+        let (_, _, _, value) = find(MetricKind::Gauge, "svix_requests_in_flight");
+        assert_eq!(value, &DebugValue::Gauge(0.0.into()));
+        let (_, _, _, value) = find(MetricKind::Histogram, "svix_request_duration_seconds");
+        assert!(matches!(value, DebugValue::Histogram(durations) if durations.len() == 1));
+    }
+}
@@ -0,0 +1,285 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Utilities for testing code that interacts with Svix.
+
+use http1::HeaderMap;
+
+#[cfg(feature = "proptest")]
+pub mod arbitrary;
+#[cfg(feature = "testcontainers")]
+mod container;
+#[cfg(feature = "testing")]
+pub mod contract;
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
+#[cfg(feature = "testing")]
+mod mock_server;
+#[cfg(feature = "testing")]
+mod receiver;
+#[cfg(feature = "testing")]
+mod vcr;
+#[cfg(feature = "wiremock")]
+pub mod wiremock;
+
+#[cfg(feature = "testcontainers")]
+pub use self::container::SvixServerContainer;
+#[cfg(feature = "testing")]
+pub use self::{
+    mock_server::MockSvixServer,
+    receiver::{ReceivedWebhook, TestReceiver},
+    vcr::VcrServer,
+};
+#[cfg(feature = "testing")]
+pub use crate::api::traits::{
+    MockApplicationApi, MockEndpointApi, MockEventTypeApi, MockMessageApi, MockMessageAttemptApi,
+};
+use crate::webhooks::{
+    unix_timestamp_now, Webhook, WebhookError, SVIX_MSG_SIGNATURE_KEY, SVIX_MSG_TIMESTAMP_KEY,
+    TOLERANCE_IN_SECONDS, UNBRANDED_MSG_ID_KEY, UNBRANDED_MSG_SIGNATURE_KEY,
+    UNBRANDED_MSG_TIMESTAMP_KEY,
+};
+
+/// Produces signed webhook headers, for testing webhook receivers.
+///
+/// The headers are signed exactly like the ones sent by Svix, so they can be
+/// checked by [`Webhook::verify`]. Message ids and timestamps are chosen by the
+/// caller, which makes it possible to test the handling of stale or tampered
+/// webhooks too.
+pub struct SvixTestSigner {
+    webhook: Webhook,
+}
+
+impl SvixTestSigner {
+    /// Creates a signer using the given endpoint secret.
+    pub fn new(secret: &str) -> Result<Self, WebhookError> {
+        Ok(Self {
+            webhook: Webhook::new(secret)?,
+        })
+    }
+
+    /// Returns the `svix-*` headers for the given message id, timestamp and
+    /// payload.
+    pub fn headers(
+        &self,
+        msg_id: &str,
+        timestamp: i64,
+        payload: &[u8],
+    ) -> Result<HeaderMap, WebhookError> {
+        self.webhook.sign_headers(msg_id, timestamp, payload)
+    }
+
+    /// Returns the `webhook-*` headers for the given message id, timestamp and
+    /// payload.
+    pub fn unbranded_headers(
+        &self,
+        msg_id: &str,
+        timestamp: i64,
+        payload: &[u8],
+    ) -> Result<HeaderMap, WebhookError> {
+        self.webhook.signed_headers(
+            [
+                UNBRANDED_MSG_ID_KEY,
+                UNBRANDED_MSG_SIGNATURE_KEY,
+                UNBRANDED_MSG_TIMESTAMP_KEY,
+            ],
+            msg_id,
+            timestamp,
+            payload,
+        )
+    }
+
+    /// Returns the signature of the given message, as found in the
+    /// `svix-signature` header.
+    pub fn signature(
+        &self,
+        msg_id: &str,
+        timestamp: i64,
+        payload: &[u8],
+    ) -> Result<String, WebhookError> {
+        self.webhook.sign(msg_id, timestamp, payload)
+    }
+
+    /// Returns the `svix-*` headers for a webhook sent right now, in the way
+    /// described by `case`.
+    pub fn headers_for(
+        &self,
+        case: TestWebhookCase,
+        msg_id: &str,
+        payload: &[u8],
+    ) -> Result<HeaderMap, WebhookError> {
+        let timestamp = match case {
+            TestWebhookCase::Expired => Self::stale_timestamp(),
+            TestWebhookCase::FromTheFuture => Self::future_timestamp(),
+            _ => Self::now(),
+        };
+        let mut headers = match case {
+            TestWebhookCase::WrongSecret => {
+                let other = Webhook::new("whsec_dGhpcyBpcyBub3QgdGhlIHJpZ2h0IHNlY3JldA==")?;
+                other.sign_headers(msg_id, timestamp, payload)?
+            }
+            TestWebhookCase::TamperedPayload => {
+                let mut tampered = payload.to_vec();
+                tampered.push(b' ');
+                self.headers(msg_id, timestamp, &tampered)?
+            }
+            _ => self.headers(msg_id, timestamp, payload)?,
+        };
+
+        match case {
+            TestWebhookCase::UnknownSignatureVersion => {
+                let signature = self.signature(msg_id, timestamp, payload)?;
+                let (_, encoded) = signature.split_once(',').expect("signatures are versioned");
+                headers.insert(
+                    SVIX_MSG_SIGNATURE_KEY,
+                    format!("v2,{encoded}").parse().expect("valid header value"),
+                );
+            }
+            TestWebhookCase::MalformedTimestamp => {
+                headers.insert(SVIX_MSG_TIMESTAMP_KEY, "yesterday".parse().unwrap());
+            }
+            TestWebhookCase::MissingSignature => {
+                headers.remove(SVIX_MSG_SIGNATURE_KEY);
+            }
+            _ => {}
+        }
+        Ok(headers)
+    }
+
+    /// Returns the `svix-*` headers for a webhook sent right now.
+    pub fn headers_now(&self, msg_id: &str, payload: &[u8]) -> Result<HeaderMap, WebhookError> {
+        self.headers(msg_id, Self::now(), payload)
+    }
+
+    /// A timestamp just old enough to be rejected by [`Webhook::verify`].
+    pub fn stale_timestamp() -> i64 {
+        Self::now() - TOLERANCE_IN_SECONDS - 1
+    }
+
+    /// A timestamp just far enough in the future to be rejected by
+    /// [`Webhook::verify`].
+    pub fn future_timestamp() -> i64 {
+        Self::now() + TOLERANCE_IN_SECONDS + 1
+    }
+
+    fn now() -> i64 {
+        unix_timestamp_now()
+    }
+}
+
+/// The ways of sending a webhook covered by [`SvixTestSigner::headers_for`].
+///
+/// Iterating over [`TestWebhookCase::ALL`] checks that a receiver accepts
+/// valid webhooks, and rejects every kind of invalid one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TestWebhookCase {
+    /// A correctly signed webhook.
+    Valid,
+    /// Signed correctly, but with a timestamp outside of the tolerance.
+    Expired,
+    /// Signed correctly, but with a timestamp too far in the future.
+    FromTheFuture,
+    /// Signed with a different secret.
+    WrongSecret,
+    /// The payload was modified after it was signed.
+    TamperedPayload,
+    /// Only signed with a signature version the receiver doesn't know.
+    UnknownSignatureVersion,
+    /// The timestamp header isn't a Unix timestamp.
+    MalformedTimestamp,
+    /// The signature header is missing.
+    MissingSignature,
+}
+
+impl TestWebhookCase {
+    pub const ALL: &'static [Self] = &[
+        Self::Valid,
+        Self::Expired,
+        Self::FromTheFuture,
+        Self::WrongSecret,
+        Self::TamperedPayload,
+        Self::UnknownSignatureVersion,
+        Self::MalformedTimestamp,
+        Self::MissingSignature,
+    ];
+
+    /// Whether a receiver should accept the webhook.
+    pub fn should_verify(self) -> bool {
+        self == Self::Valid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SvixTestSigner, TestWebhookCase};
+    use crate::webhooks::{Webhook, WebhookError};
+
+    const SECRET: &str = "whsec_C2FVsBQIhrscChlQIMV+b5sSYspob7oD";
+    const MSG_ID: &str = "msg_27UH4WbU6Z5A5EzD8u03UvzRbpk";
+    const PAYLOAD: &[u8] = br#"{"email":"test@example.com","username":"test_user"}"#;
+
+    #[test]
+    fn test_signer_headers() {
+        let signer = SvixTestSigner::new(SECRET).unwrap();
+        let wh = Webhook::new(SECRET).unwrap();
+
+        wh.verify(PAYLOAD, &signer.headers_now(MSG_ID, PAYLOAD).unwrap())
+            .unwrap();
+
+        let headers = signer
+            .unbranded_headers(MSG_ID, SvixTestSigner::stale_timestamp(), PAYLOAD)
+            .unwrap();
+        assert!(matches!(
+            wh.verify(PAYLOAD, &headers),
+            Err(WebhookError::TimestampTooOldError)
+        ));
+
+        let headers = signer
+            .headers(MSG_ID, SvixTestSigner::future_timestamp(), PAYLOAD)
+            .unwrap();
+        assert!(matches!(
+            wh.verify(PAYLOAD, &headers),
+            Err(WebhookError::FutureTimestampError)
+        ));
+    }
+
+    #[test]
+    fn test_signer_cases() {
+        let signer = SvixTestSigner::new(SECRET).unwrap();
+        let wh = Webhook::new(SECRET).unwrap();
+
+        for &case in TestWebhookCase::ALL {
+            let headers = signer.headers_for(case, MSG_ID, PAYLOAD).unwrap();
+            assert_eq!(
+                wh.verify(PAYLOAD, &headers).is_ok(),
+                case.should_verify(),
+                "{case:?}"
+            );
+        }
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_mock_api() {
+        use super::MockApplicationApi;
+        use crate::api::{ApplicationApi, ApplicationOut};
+
+        async fn app_name(api: &impl ApplicationApi, app_id: &str) -> String {
+            api.get(app_id.to_owned()).await.unwrap().name
+        }
+
+        let mut mock = MockApplicationApi::new();
+        mock.expect_get()
+            .withf(|app_id| app_id == "app_1")
+            .returning(|app_id| {
+                Ok(ApplicationOut::new(
+                    Default::default(),
+                    app_id,
+                    Default::default(),
+                    "My app".to_owned(),
+                    Default::default(),
+                ))
+            });
+        assert_eq!(app_name(&mock, "app_1").await, "My app");
+    }
+}
@@ -0,0 +1,423 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! [`proptest`] strategies for the main models of the API.
+//!
+//! With the `proptest` feature enabled, the models implement [`Arbitrary`],
+//! so `any::<MessageOut>()` generates messages like the ones returned by the
+//! API: IDs have the right prefix, names and channels match the patterns
+//! the API enforces, timestamps are RFC 3339 and payloads are JSON objects of
+//! a bounded size.
+
+use std::collections::HashMap;
+
+use proptest::{
+    arbitrary::{any, Arbitrary},
+    collection::{btree_set, hash_map, vec},
+    option, prop_oneof,
+    strategy::{BoxedStrategy, Just, Strategy},
+};
+use serde_json::Value;
+
+use crate::api::{
+    ApplicationIn, ApplicationOut, EndpointIn, EndpointOut, EventTypeName, EventTypeOut,
+    MessageAttemptOut, MessageAttemptTriggerType, MessageIn, MessageOut, MessageStatus, Timestamp,
+};
+
+/// IDs with the given prefix, e.g. `msg_1srOrx2ZWZBpBUvZwXKQmoEYga2`.
+pub fn id(prefix: &'static str) -> impl Strategy<Value = String> {
+    "[0-9A-Za-z]{27}".prop_map(move |ksuid| format!("{prefix}_{ksuid}"))
+}
+
+/// Timestamps between 2020 and 2040, in whole seconds.
+pub fn timestamp() -> impl Strategy<Value = Timestamp> {
+    (1_577_836_800i64..2_208_988_800)
+        .prop_map(|secs| Timestamp::from_unix_timestamp(secs).expect("the range is valid"))
+}
+
+/// JSON object payloads, of at most a few kilobytes.
+pub fn payload() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        "[ -~]{0,32}".prop_map(Value::from),
+    ];
+    let value = leaf.prop_recursive(3, 32, 8, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..8).prop_map(Value::from),
+            hash_map("[a-zA-Z_]{1,12}", inner, 0..8)
+                .prop_map(|fields| Value::Object(fields.into_iter().collect())),
+        ]
+    });
+    hash_map("[a-zA-Z_]{1,12}", value, 0..8)
+        .prop_map(|fields| Value::Object(fields.into_iter().collect()))
+}
+
+/// UIDs, event IDs and the like.
+fn uid() -> impl Strategy<Value = String> {
+    "[a-zA-Z0-9._-]{1,64}"
+}
+
+fn event_type_name() -> impl Strategy<Value = String> {
+    "[a-z]{1,12}(\\.[a-z_]{1,12}){0,2}"
+}
+
+fn channels() -> impl Strategy<Value = Vec<String>> {
+    btree_set("[a-zA-Z0-9._:-]{1,32}", 0..=10).prop_map(|set| set.into_iter().collect())
+}
+
+fn tags() -> impl Strategy<Value = Vec<String>> {
+    btree_set("[a-zA-Z0-9._-]{1,32}", 0..=5).prop_map(|set| set.into_iter().collect())
+}
+
+fn checked<T: TryFrom<String>>(names: Vec<String>) -> Vec<T>
+where
+    T::Error: std::fmt::Debug,
+{
+    names
+        .into_iter()
+        .map(|name| T::try_from(name).expect("generated names are valid"))
+        .collect()
+}
+
+fn strings<T: Into<String>>(names: Vec<T>) -> Vec<String> {
+    names.into_iter().map(Into::into).collect()
+}
+
+fn filter_types() -> impl Strategy<Value = Vec<String>> {
+    btree_set(event_type_name(), 1..=5).prop_map(|set| set.into_iter().collect())
+}
+
+fn url() -> impl Strategy<Value = String> {
+    "https://[a-z]{1,16}\\.example\\.com/[a-z0-9/]{0,24}"
+}
+
+fn metadata() -> impl Strategy<Value = HashMap<String, String>> {
+    hash_map("[a-zA-Z_]{1,12}", "[ -~]{0,24}", 0..4)
+}
+
+fn rate_limit() -> impl Strategy<Value = Option<i32>> {
+    option::of(1..=i32::from(u16::MAX))
+}
+
+impl Arbitrary for ApplicationIn {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            option::of(metadata()),
+            "[ -~]{1,64}",
+            rate_limit(),
+            option::of(uid()),
+        )
+            .prop_map(|(metadata, name, rate_limit, uid)| Self {
+                metadata,
+                name,
+                rate_limit,
+                uid,
+                #[cfg(feature = "unknown-fields")]
+                extra: Default::default(),
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for ApplicationOut {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (any::<ApplicationIn>(), id("app"), timestamp(), timestamp())
+            .prop_map(|(app_in, id, created_at, updated_at)| Self {
+                created_at,
+                id,
+                metadata: app_in.metadata.unwrap_or_default(),
+                name: app_in.name,
+                rate_limit: app_in.rate_limit,
+                uid: app_in.uid,
+                updated_at,
+                #[cfg(feature = "unknown-fields")]
+                extra: Default::default(),
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for EndpointIn {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            option::of(channels()),
+            option::of("[ -~]{0,64}"),
+            option::of(any::<bool>()),
+            option::of(filter_types()),
+            option::of(metadata()),
+            rate_limit(),
+            option::of("whsec_[a-zA-Z0-9+/]{32}"),
+            option::of(uid()),
+            url(),
+            option::of(1..=i32::from(u16::MAX)),
+        )
+            .prop_map(
+                |(
+                    channels,
+                    description,
+                    disabled,
+                    filter_types,
+                    metadata,
+                    rate_limit,
+                    secret,
+                    uid,
+                    url,
+                    version,
+                )| Self {
+                    channels,
+                    description,
+                    disabled,
+                    filter_types,
+                    metadata,
+                    rate_limit,
+                    secret,
+                    uid,
+                    url,
+                    version,
+                    #[cfg(feature = "unknown-fields")]
+                    extra: Default::default(),
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for EndpointOut {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (any::<EndpointIn>(), id("ep"), timestamp(), timestamp())
+            .prop_map(|(endpoint_in, id, created_at, updated_at)| Self {
+                channels: endpoint_in.channels,
+                created_at,
+                description: endpoint_in.description.unwrap_or_default(),
+                disabled: endpoint_in.disabled,
+                filter_types: endpoint_in.filter_types,
+                id,
+                metadata: endpoint_in.metadata.unwrap_or_default(),
+                rate_limit: endpoint_in.rate_limit,
+                uid: endpoint_in.uid,
+                updated_at,
+                url: endpoint_in.url,
+                version: endpoint_in.version.unwrap_or(1),
+                #[cfg(feature = "unknown-fields")]
+                extra: Default::default(),
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for EventTypeOut {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            option::of(any::<bool>()),
+            timestamp(),
+            any::<bool>(),
+            "[ -~]{0,64}",
+            option::of(uid()),
+            option::of(uid()),
+            event_type_name(),
+            timestamp(),
+        )
+            .prop_map(
+                |(
+                    archived,
+                    created_at,
+                    deprecated,
+                    description,
+                    feature_flag,
+                    group_name,
+                    name,
+                    updated_at,
+                )| Self {
+                    archived,
+                    created_at,
+                    deprecated,
+                    description,
+                    feature_flag,
+                    group_name,
+                    name,
+                    schemas: None,
+                    updated_at,
+                    #[cfg(feature = "unknown-fields")]
+                    extra: Default::default(),
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for MessageIn {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            option::of(any::<ApplicationIn>()),
+            option::of(channels().prop_map(checked)),
+            option::of(uid()),
+            event_type_name(),
+            payload(),
+            option::of(1..=2160i64),
+            option::of(tags().prop_map(checked)),
+        )
+            .prop_map(
+                |(application, channels, event_id, event_type, payload, retention, tags)| Self {
+                    application: application.map(Box::new),
+                    channels,
+                    event_id,
+                    event_type: EventTypeName::new(event_type).expect("generated names are valid"),
+                    payload,
+                    payload_retention_hours: retention,
+                    payload_retention_period: None,
+                    tags,
+                    transformations_params: None,
+                    #[cfg(feature = "unknown-fields")]
+                    extra: Default::default(),
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for MessageOut {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (any::<MessageIn>(), id("msg"), timestamp())
+            .prop_map(|(msg_in, id, timestamp)| Self {
+                channels: msg_in.channels.map(strings),
+                event_id: msg_in.event_id,
+                event_type: msg_in.event_type.into(),
+                id,
+                payload: msg_in.payload.into(),
+                tags: msg_in.tags.map(strings),
+                timestamp,
+                #[cfg(feature = "unknown-fields")]
+                extra: Default::default(),
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for MessageStatus {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![
+            Just(Self::Success),
+            Just(Self::Pending),
+            Just(Self::Fail),
+            Just(Self::Sending),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for MessageAttemptTriggerType {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![Just(Self::Scheduled), Just(Self::Manual)].boxed()
+    }
+}
+
+impl Arbitrary for MessageAttemptOut {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            id("ep"),
+            id("atmpt"),
+            option::of(any::<MessageOut>()),
+            "[ -~]{0,128}",
+            0..30_000i64,
+            100..600i32,
+            any::<MessageStatus>(),
+            timestamp(),
+            any::<MessageAttemptTriggerType>(),
+            url(),
+        )
+            .prop_flat_map(
+                |(
+                    endpoint_id,
+                    id,
+                    msg,
+                    response,
+                    response_duration_ms,
+                    response_status_code,
+                    status,
+                    timestamp,
+                    trigger_type,
+                    url,
+                )| {
+                    // The message ID matches the included message, if any
+                    let msg_id = match &msg {
+                        Some(msg) => Just(msg.id.clone()).boxed(),
+                        None => self::id("msg").boxed(),
+                    };
+                    msg_id.prop_map(move |msg_id| Self {
+                        endpoint_id: endpoint_id.clone(),
+                        id: id.clone(),
+                        msg: msg.clone().map(Box::new),
+                        msg_id,
+                        response: response.clone(),
+                        response_duration_ms,
+                        response_status_code,
+                        status,
+                        timestamp,
+                        trigger_type,
+                        url: url.clone(),
+                        #[cfg(feature = "unknown-fields")]
+                        extra: Default::default(),
+                    })
+                },
+            )
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::api::{EndpointOut, MessageAttemptOut, MessageOut};
+
+    proptest! {
+        #[test]
+        fn test_arbitrary_models(
+            msg in any::<MessageOut>(),
+            endpoint in any::<EndpointOut>(),
+            attempt in any::<MessageAttemptOut>(),
+        ) {
+            prop_assert!(msg.id.starts_with("msg_"));
+            prop_assert!(msg.payload.parse::<serde_json::Value>().unwrap().is_object());
+            prop_assert!(msg.channels.as_ref().is_none_or(|channels| channels.len() <= 10));
+            prop_assert!(endpoint.id.starts_with("ep_"));
+            prop_assert!(url::Url::parse(&endpoint.url).is_ok());
+            prop_assert!(attempt.msg.as_ref().is_none_or(|msg| msg.id == attempt.msg_id));
+
+            let json = serde_json::to_string(&msg).unwrap();
+            prop_assert_eq!(serde_json::from_str::<MessageOut>(&json).unwrap(), msg);
+        }
+    }
+}
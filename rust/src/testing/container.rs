@@ -0,0 +1,182 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Running a self-hosted svix-server in Docker with testcontainers.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use testcontainers::{
+    core::{IntoContainerPort as _, WaitFor},
+    runners::AsyncRunner as _,
+    ContainerAsync, GenericImage, ImageExt as _,
+};
+
+use crate::{
+    api::{Svix, SvixOptions},
+    error::{Error, Result},
+    request::Request,
+};
+
+const SERVER_PORT: u16 = 8071;
+const JWT_SECRET: &str = "svix-testcontainers-jwt-secret";
+/// The organization svix-server uses when none is configured.
+const DEFAULT_ORG_ID: &str = "org_23rb8YdGqMT0qIzpgGwdXfHirMu";
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A svix-server running in Docker, along with the Postgres database it uses.
+///
+/// Messages are queued in memory, so no Redis is needed. The containers are
+/// removed when this is dropped.
+pub struct SvixServerContainer {
+    server: ContainerAsync<GenericImage>,
+    _postgres: ContainerAsync<GenericImage>,
+    server_url: String,
+}
+
+impl SvixServerContainer {
+    /// Starts the latest svix-server release, and waits until it's ready.
+    pub async fn start() -> Result<Self> {
+        Self::start_with_tag("latest").await
+    }
+
+    /// Starts the given tag of the `svix/svix-server` image, and waits until
+    /// it's ready.
+    pub async fn start_with_tag(tag: &str) -> Result<Self> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(Error::generic)?
+            .as_nanos();
+        let network = format!("svix-test-{}-{nanos}", std::process::id());
+        let postgres_host = format!("{network}-postgres");
+
+        let postgres = GenericImage::new("postgres", "16-alpine")
+            .with_wait_for(WaitFor::message_on_stderr(
+                "database system is ready to accept connections",
+            ))
+            .with_network(&network)
+            .with_container_name(&postgres_host)
+            .with_env_var("POSTGRES_PASSWORD", "postgres")
+            .start()
+            .await
+            .map_err(Error::generic)?;
+
+        let server = GenericImage::new("svix/svix-server", tag)
+            .with_exposed_port(SERVER_PORT.tcp())
+            .with_network(&network)
+            .with_env_var(
+                "SVIX_DB_DSN",
+                format!("postgresql://postgres:postgres@{postgres_host}/postgres"),
+            )
+            .with_env_var("SVIX_JWT_SECRET", JWT_SECRET)
+            .with_env_var("SVIX_QUEUE_TYPE", "memory")
+            .with_env_var("SVIX_CACHE_TYPE", "memory")
+            .with_env_var("WAIT_FOR", "true")
+            .start()
+            .await
+            .map_err(Error::generic)?;
+
+        let host = server.get_host().await.map_err(Error::generic)?;
+        let port = server
+            .get_host_port_ipv4(SERVER_PORT)
+            .await
+            .map_err(Error::generic)?;
+        let container = Self {
+            server,
+            _postgres: postgres,
+            server_url: format!("http://{host}:{port}"),
+        };
+        container.wait_until_healthy().await?;
+        Ok(container)
+    }
+
+    /// The URL to pass as [`SvixOptions::server_url`].
+    pub fn server_url(&self) -> &str {
+        &self.server_url
+    }
+
+    /// Returns a token for the server's default organization.
+    pub fn token(&self) -> String {
+        org_token(JWT_SECRET, DEFAULT_ORG_ID)
+    }
+
+    /// Returns a client for the server's default organization.
+    pub fn client(&self) -> Svix {
+        Svix::new(
+            self.token(),
+            Some(SvixOptions {
+                server_url: Some(self.server_url.clone()),
+                ..Default::default()
+            }),
+        )
+    }
+
+    /// The ID of the svix-server container, e.g. for reading its logs.
+    pub fn container_id(&self) -> &str {
+        self.server.id()
+    }
+
+    async fn wait_until_healthy(&self) -> Result<()> {
+        let svix = self.client();
+        let health = || {
+            Request::new(http1::Method::GET, "/api/v1/health".to_owned())
+                .with_operation("v1.health.get")
+                .returns_nothing()
+                .execute::<()>(svix.cfg())
+        };
+        tokio::time::timeout(STARTUP_TIMEOUT, async {
+            while health().await.is_err() {
+                tokio::time::sleep(Duration::from_millis(250)).await;
+            }
+        })
+        .await
+        .map_err(|_| Error::Generic("svix-server didn't become healthy in time".to_owned()))
+    }
+}
+
+/// Returns an HS256 JWT for `org_id`, like `svix-server jwt generate` does.
+fn org_token(secret: &str, org_id: &str) -> String {
+    let encode = |data: &[u8]| base64::encode_config(data, base64::URL_SAFE_NO_PAD);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("the clock is after the Unix epoch")
+        .as_secs();
+    let header = serde_json::json!({ "alg": "HS256", "typ": "JWT" });
+    let claims = serde_json::json!({
+        "iat": now,
+        "nbf": now,
+        "exp": now + 24 * 60 * 60,
+        "iss": "svix-server",
+        "sub": org_id,
+    });
+
+    let message = format!(
+        "{}.{}",
+        encode(header.to_string().as_bytes()),
+        encode(claims.to_string().as_bytes())
+    );
+    let signature = hmac_sha256::HMAC::mac(message.as_bytes(), secret.as_bytes());
+    format!("{message}.{}", encode(&signature))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SvixServerContainer;
+    use crate::api::ApplicationIn;
+
+    // Requires Docker, opt-in with `cargo test --ignored`
+    #[ignore]
+    #[tokio::test]
+    async fn test_container() {
+        let container = SvixServerContainer::start().await.unwrap();
+        let svix = container.client();
+        let app = svix
+            .application()
+            .create(ApplicationIn::new("app".to_owned()), None)
+            .await
+            .unwrap();
+        assert_eq!(svix.application().get(app.id.clone()).await.unwrap(), app);
+
+        #[cfg(feature = "testing")]
+        crate::testing::contract::run(&svix).await.unwrap();
+    }
+}
@@ -0,0 +1,271 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Checks of the API behavior this crate and [`MockSvixServer`] rely on.
+//!
+//! [`run`] works against any server, so the same checks can be run against
+//! the mock server and a real one (e.g. [`SvixServerContainer`]), proving
+//! that the mock behaves like the real thing.
+//!
+//! [`MockSvixServer`]: super::MockSvixServer
+//! [`SvixServerContainer`]: super::SvixServerContainer
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    api::{
+        ApplicationIn, ApplicationPatch, EndpointIn, EndpointListOptions, EventTypeName, MessageIn,
+        MessageListOptions, Ordering, Svix,
+    },
+    error::{Error, Result},
+};
+
+/// Runs the contract checks with `svix`, returning the first violation.
+///
+/// The checks create an application with a few endpoints and messages, and
+/// delete it again when they pass. Messages are sent to `example.com`
+/// endpoints, so use an environment where that's fine.
+pub async fn run(svix: &Svix) -> Result<()> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(Error::generic)?
+        .as_nanos();
+    let uid = format!("contract-{nanos}");
+
+    let app_id = applications(svix, &uid).await?;
+    endpoints(svix, &app_id).await?;
+    messages(svix, &app_id).await?;
+
+    svix.application().delete(app_id.clone()).await?;
+    expect_status(svix.application().get(app_id).await, 404, "get deleted app")
+}
+
+async fn applications(svix: &Svix, uid: &str) -> Result<String> {
+    let app_in = ApplicationIn {
+        uid: Some(uid.to_owned()),
+        ..ApplicationIn::new("Contract".to_owned())
+    };
+    let app = svix.application().create(app_in.clone(), None).await?;
+    check(app.id.starts_with("app_"), "app IDs start with app_")?;
+    check(app.uid.as_deref() == Some(uid), "app uid is set")?;
+    check(
+        svix.application().get(uid.to_owned()).await? == app,
+        "apps can be fetched by uid",
+    )?;
+    check(
+        svix.application()
+            .get_or_create(app_in.clone(), None)
+            .await?
+            == app,
+        "get_or_create returns the existing app",
+    )?;
+    expect_status(
+        svix.application().create(app_in, None).await,
+        409,
+        "create app with a taken uid",
+    )?;
+
+    let updated = svix
+        .application()
+        .update(
+            app.id.clone(),
+            ApplicationIn {
+                uid: Some(uid.to_owned()),
+                ..ApplicationIn::new("Contract (updated)".to_owned())
+            },
+            None,
+        )
+        .await?;
+    check(updated.name == "Contract (updated)", "update sets the name")?;
+    let patched = svix
+        .application()
+        .patch(
+            app.id.clone(),
+            ApplicationPatch {
+                metadata: Some([("key".to_owned(), "value".to_owned())].into()),
+                ..Default::default()
+            },
+            None,
+        )
+        .await?;
+    check(
+        patched.name == updated.name && patched.metadata["key"] == "value",
+        "patch only changes the given fields",
+    )?;
+
+    expect_status(
+        svix.application().get(format!("{uid}-missing")).await,
+        404,
+        "get missing app",
+    )?;
+    Ok(app.id)
+}
+
+async fn endpoints(svix: &Svix, app_id: &str) -> Result<()> {
+    let mut ids = Vec::new();
+    for i in 0..3 {
+        let endpoint = svix
+            .endpoint()
+            .create(
+                app_id.to_owned(),
+                EndpointIn::new(format!("https://example.com/webhook/{i}")),
+                None,
+            )
+            .await?;
+        check(
+            endpoint.id.starts_with("ep_"),
+            "endpoint IDs start with ep_",
+        )?;
+        ids.push(endpoint.id);
+    }
+    // Checked by the client, before it's sent
+    check(
+        matches!(
+            svix.endpoint()
+                .create(
+                    app_id.to_owned(),
+                    EndpointIn::new("not a url".to_owned()),
+                    None,
+                )
+                .await,
+            Err(Error::Generic(_))
+        ),
+        "endpoints with an invalid URL are rejected",
+    )?;
+
+    let endpoint_api = svix.endpoint();
+    let list = |iterator| {
+        endpoint_api.list(
+            app_id.to_owned(),
+            Some(EndpointListOptions {
+                iterator,
+                limit: Some(2),
+                order: Some(Ordering::Ascending),
+                ..Default::default()
+            }),
+        )
+    };
+    let first = list(None).await?;
+    let first_ids: Vec<_> = first.data.iter().map(|e| e.id.clone()).collect();
+    check(
+        first_ids == ids[..2] && !first.done,
+        "the first page holds the oldest endpoints",
+    )?;
+    let second = list(first.iterator).await?;
+    let second_ids: Vec<_> = second.data.iter().map(|e| e.id.clone()).collect();
+    check(
+        second_ids == ids[2..] && second.done,
+        "the last page holds the rest",
+    )?;
+    let prev = list(second.prev_iterator).await?;
+    let prev_ids: Vec<_> = prev.data.iter().map(|e| e.id.clone()).collect();
+    check(prev_ids == first_ids, "prevIterator goes back a page")?;
+
+    let secret = svix
+        .endpoint()
+        .get_secret(app_id.to_owned(), ids[0].clone())
+        .await?;
+    check(
+        secret.key.starts_with("whsec_"),
+        "endpoint secrets start with whsec_",
+    )?;
+
+    svix.endpoint()
+        .delete(app_id.to_owned(), ids[2].clone())
+        .await?;
+    expect_status(
+        svix.endpoint().get(app_id.to_owned(), ids[2].clone()).await,
+        404,
+        "get deleted endpoint",
+    )
+}
+
+async fn messages(svix: &Svix, app_id: &str) -> Result<()> {
+    let msg_in = MessageIn {
+        event_id: Some("contract-event".to_owned()),
+        ..MessageIn::new(
+            EventTypeName::new("contract.test")?,
+            serde_json::json!({ "check": "messages" }),
+        )
+    };
+    let msg = svix
+        .message()
+        .create(app_id.to_owned(), msg_in.clone(), None)
+        .await?;
+    check(msg.id.starts_with("msg_"), "message IDs start with msg_")?;
+    check(
+        msg.payload.parse::<serde_json::Value>()? == msg_in.payload,
+        "messages keep their payload",
+    )?;
+    check(
+        svix.message()
+            .get(app_id.to_owned(), "contract-event".to_owned())
+            .await?
+            .id
+            == msg.id,
+        "messages can be fetched by event ID",
+    )?;
+    expect_status(
+        svix.message().create(app_id.to_owned(), msg_in, None).await,
+        409,
+        "create message with a taken event ID",
+    )?;
+
+    let messages = svix
+        .message()
+        .list(app_id.to_owned(), Some(MessageListOptions::default()))
+        .await?;
+    check(
+        messages.data.first().map(|m| &m.id) == Some(&msg.id),
+        "messages are listed newest first",
+    )?;
+
+    let attempts = svix
+        .message_attempt()
+        .list_by_msg(app_id.to_owned(), msg.id.clone(), None)
+        .await?;
+    check(
+        attempts.data.iter().all(|attempt| attempt.msg_id == msg.id),
+        "attempts are filtered by message",
+    )
+}
+
+fn check(condition: bool, what: &str) -> Result<()> {
+    if condition {
+        Ok(())
+    } else {
+        Err(Error::Generic(format!("contract violated: {what}")))
+    }
+}
+
+/// Checks that `result` is an HTTP error with the given status.
+fn expect_status<T>(result: Result<T>, status: u16, what: &str) -> Result<()> {
+    let actual = match &result {
+        Err(Error::Http(e)) => e.status,
+        Err(Error::Validation(e)) => e.status,
+        Err(_) => return result.map(|_| ()),
+        Ok(_) => {
+            return Err(Error::Generic(format!(
+                "contract violated: {what} succeeded instead of failing with {status}"
+            )))
+        }
+    };
+    if actual.as_u16() == status {
+        Ok(())
+    } else {
+        Err(Error::Generic(format!(
+            "contract violated: {what} failed with {actual} instead of {status}"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::MockSvixServer;
+
+    #[tokio::test]
+    async fn test_contract() {
+        let server = MockSvixServer::start().await.unwrap();
+        super::run(&server.client()).await.unwrap();
+    }
+}
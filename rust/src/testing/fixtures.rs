@@ -0,0 +1,94 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Realistic JSON samples of the API's models, as serialized by the server.
+//!
+//! These are meant for testing code that parses or transforms data coming
+//! from Svix, e.g. webhook handlers for operational webhooks. They're kept in
+//! sync with the models of this crate, so every fixture deserializes into the
+//! model it's named after.
+
+/// An [`ApplicationOut`](crate::api::ApplicationOut).
+pub const APPLICATION_OUT: &str = include_str!("fixtures/application_out.json");
+
+/// An [`EndpointOut`](crate::api::EndpointOut).
+pub const ENDPOINT_OUT: &str = include_str!("fixtures/endpoint_out.json");
+
+/// An [`EndpointSecretOut`](crate::api::EndpointSecretOut).
+pub const ENDPOINT_SECRET_OUT: &str = include_str!("fixtures/endpoint_secret_out.json");
+
+/// An [`EventTypeOut`](crate::api::EventTypeOut), with a JSON schema.
+pub const EVENT_TYPE_OUT: &str = include_str!("fixtures/event_type_out.json");
+
+/// A [`MessageOut`](crate::api::MessageOut).
+pub const MESSAGE_OUT: &str = include_str!("fixtures/message_out.json");
+
+/// A successful [`MessageAttemptOut`](crate::api::MessageAttemptOut),
+/// including its message.
+pub const MESSAGE_ATTEMPT_OUT: &str = include_str!("fixtures/message_attempt_out.json");
+
+/// A failed [`MessageAttemptOut`](crate::api::MessageAttemptOut).
+pub const MESSAGE_ATTEMPT_OUT_FAILED: &str =
+    include_str!("fixtures/message_attempt_out_failed.json");
+
+/// An `endpoint.created` operational webhook.
+pub const ENDPOINT_CREATED_EVENT: &str = include_str!("fixtures/endpoint_created_event.json");
+
+/// An `endpoint.deleted` operational webhook.
+pub const ENDPOINT_DELETED_EVENT: &str = include_str!("fixtures/endpoint_deleted_event.json");
+
+/// An `endpoint.disabled` operational webhook.
+pub const ENDPOINT_DISABLED_EVENT: &str = include_str!("fixtures/endpoint_disabled_event.json");
+
+/// An `endpoint.updated` operational webhook.
+pub const ENDPOINT_UPDATED_EVENT: &str = include_str!("fixtures/endpoint_updated_event.json");
+
+/// A `message.attempt.exhausted` operational webhook.
+pub const MESSAGE_ATTEMPT_EXHAUSTED_EVENT: &str =
+    include_str!("fixtures/message_attempt_exhausted_event.json");
+
+/// A `message.attempt.failing` operational webhook.
+pub const MESSAGE_ATTEMPT_FAILING_EVENT: &str =
+    include_str!("fixtures/message_attempt_failing_event.json");
+
+/// A `message.attempt.recovered` operational webhook.
+pub const MESSAGE_ATTEMPT_RECOVERED_EVENT: &str =
+    include_str!("fixtures/message_attempt_recovered_event.json");
+
+#[cfg(test)]
+mod tests {
+    use serde::{de::DeserializeOwned, Serialize};
+
+    use super::*;
+    use crate::api::{
+        ApplicationOut, EndpointCreatedEvent, EndpointDeletedEvent, EndpointDisabledEvent,
+        EndpointOut, EndpointSecretOut, EndpointUpdatedEvent, EventTypeOut,
+        MessageAttemptExhaustedEvent, MessageAttemptFailingEvent, MessageAttemptOut,
+        MessageAttemptRecoveredEvent, MessageOut,
+    };
+
+    /// Checks that `fixture` parses as `T` without losing any fields.
+    fn assert_round_trips<T: DeserializeOwned + Serialize>(fixture: &str) {
+        let value: serde_json::Value = serde_json::from_str(fixture).unwrap();
+        let model: T = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(serde_json::to_value(model).unwrap(), value);
+    }
+
+    #[test]
+    fn test_fixtures() {
+        assert_round_trips::<ApplicationOut>(APPLICATION_OUT);
+        assert_round_trips::<EndpointOut>(ENDPOINT_OUT);
+        assert_round_trips::<EndpointSecretOut>(ENDPOINT_SECRET_OUT);
+        assert_round_trips::<EventTypeOut>(EVENT_TYPE_OUT);
+        assert_round_trips::<MessageOut>(MESSAGE_OUT);
+        assert_round_trips::<MessageAttemptOut>(MESSAGE_ATTEMPT_OUT);
+        assert_round_trips::<MessageAttemptOut>(MESSAGE_ATTEMPT_OUT_FAILED);
+        assert_round_trips::<EndpointCreatedEvent>(ENDPOINT_CREATED_EVENT);
+        assert_round_trips::<EndpointDeletedEvent>(ENDPOINT_DELETED_EVENT);
+        assert_round_trips::<EndpointDisabledEvent>(ENDPOINT_DISABLED_EVENT);
+        assert_round_trips::<EndpointUpdatedEvent>(ENDPOINT_UPDATED_EVENT);
+        assert_round_trips::<MessageAttemptExhaustedEvent>(MESSAGE_ATTEMPT_EXHAUSTED_EVENT);
+        assert_round_trips::<MessageAttemptFailingEvent>(MESSAGE_ATTEMPT_FAILING_EVENT);
+        assert_round_trips::<MessageAttemptRecoveredEvent>(MESSAGE_ATTEMPT_RECOVERED_EVENT);
+    }
+}
@@ -0,0 +1,969 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! An in-memory stand-in for the Svix API.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::Infallible,
+    future::Future,
+    net::{Ipv4Addr, SocketAddr},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use http1::{header, Method, Request, Response, StatusCode};
+use http_body_util::{BodyExt as _, Full};
+use hyper::{
+    body::{Bytes, Incoming},
+    server::conn::http1 as server_http1,
+    service::service_fn,
+};
+use hyper_util::rt::TokioIo;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::json;
+use tokio::{net::TcpListener, task::JoinHandle};
+
+use crate::api::{
+    ApplicationIn, ApplicationOut, ApplicationPatch, EndpointIn, EndpointOut, EndpointPatch,
+    EndpointSecretOut, EndpointUpdate, MessageAttemptOut, MessageAttemptTriggerType, MessageIn,
+    MessageOut, MessageStatus, Svix, SvixOptions, Timestamp,
+};
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 250;
+
+/// A local HTTP server implementing a subset of the Svix API in memory.
+///
+/// It supports applications, endpoints, messages and message attempts,
+/// including pagination and idempotency keys, so code using [`Svix`] can be
+/// tested end to end without network access. Sent messages aren't actually
+/// delivered, every matching endpoint gets a successful attempt right away.
+///
+/// Retrying a `POST` with the same idempotency key returns the original
+/// response, unless the first attempt failed with a server error. Reusing a
+/// key for a different request fails with a 409.
+///
+/// Any bearer token is accepted. The server shuts down when dropped.
+pub struct MockSvixServer {
+    addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl MockSvixServer {
+    /// Starts a server on a random local port.
+    pub async fn start() -> std::io::Result<Self> {
+        Self::start_with_state(State::default()).await
+    }
+
+    /// Starts a server which returns at most `page_size` items per page of
+    /// list responses, whatever the requested limit.
+    ///
+    /// A small page size makes it easy to test the handling of lists that
+    /// span several pages.
+    pub async fn start_with_page_size(page_size: usize) -> std::io::Result<Self> {
+        assert!(page_size > 0, "the page size must be positive");
+        Self::start_with_state(State {
+            page_size: Some(page_size),
+            ..Default::default()
+        })
+        .await
+    }
+
+    async fn start_with_state(state: State) -> std::io::Result<Self> {
+        let state = Arc::new(Mutex::new(state));
+        let (addr, task) = spawn_server(move |req| {
+            let state = state.clone();
+            async move { handle(&state, req).await.into_response() }
+        })
+        .await?;
+        Ok(Self { addr, task })
+    }
+
+    /// The URL to pass as [`SvixOptions::server_url`].
+    pub fn server_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Returns a client pointed at this server.
+    pub fn client(&self) -> Svix {
+        Svix::new(
+            "testsk_mock".to_owned(),
+            Some(SvixOptions {
+                server_url: Some(self.server_url()),
+                ..Default::default()
+            }),
+        )
+    }
+}
+
+impl Drop for MockSvixServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Starts serving HTTP/1 requests with `handler` on a random local port.
+pub(super) async fn spawn_server<H, F>(handler: H) -> std::io::Result<(SocketAddr, JoinHandle<()>)>
+where
+    H: Fn(Request<Incoming>) -> F + Clone + Send + Sync + 'static,
+    F: Future<Output = Response<Full<Bytes>>> + Send + 'static,
+{
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+    let addr = listener.local_addr()?;
+    let task = tokio::spawn(async move {
+        loop {
+            let Ok((stream, _)) = listener.accept().await else {
+                continue;
+            };
+            let handler = handler.clone();
+            let service = service_fn(move |req| {
+                let response = handler(req);
+                async move { Ok::<_, Infallible>(response.await) }
+            });
+            tokio::spawn(
+                server_http1::Builder::new().serve_connection(TokioIo::new(stream), service),
+            );
+        }
+    });
+    Ok((addr, task))
+}
+
+async fn handle(state: &Mutex<State>, req: Request<Incoming>) -> Reply {
+    let (parts, body) = req.into_parts();
+    let authorized = parts
+        .headers
+        .get(header::AUTHORIZATION)
+        .is_some_and(|value| value.as_bytes().starts_with(b"Bearer "));
+    if !authorized {
+        return Reply::error(
+            StatusCode::UNAUTHORIZED,
+            "authentication_failed",
+            "Invalid token",
+        );
+    }
+    let Ok(body) = body.collect().await else {
+        return Reply::error(StatusCode::BAD_REQUEST, "bad_request", "Invalid body");
+    };
+    let body = body.to_bytes();
+
+    let query: HashMap<String, String> = parts
+        .uri
+        .query()
+        .map(|query| {
+            url::form_urlencoded::parse(query.as_bytes())
+                .into_owned()
+                .collect()
+        })
+        .unwrap_or_default();
+    let segments: Vec<&str> = parts.uri.path().trim_matches('/').split('/').collect();
+    let idempotency_key = parts
+        .headers
+        .get("idempotency-key")
+        .filter(|_| parts.method == Method::POST)
+        .and_then(|value| value.to_str().ok())
+        .map(ToOwned::to_owned);
+
+    let mut state = state.lock().unwrap();
+    if let Some(original) = idempotency_key
+        .as_ref()
+        .and_then(|key| state.idempotency.get(key))
+    {
+        // Like the API, only replay the response when the request is the same
+        if original.path != parts.uri.path() || original.body != body {
+            return Reply::conflict("Idempotency key reused with a different request");
+        }
+        return original.reply.clone();
+    }
+
+    let request = ApiRequest {
+        query,
+        body,
+        page_size: state.page_size,
+    };
+    let reply = state
+        .route(&parts.method, &segments, &request)
+        .unwrap_or_else(|e| e);
+    if let Some(key) = idempotency_key {
+        // Server errors can be retried, anything else is final
+        if !reply.status.is_server_error() {
+            let original = IdempotentRequest {
+                path: parts.uri.path().to_owned(),
+                body: request.body,
+                reply: reply.clone(),
+            };
+            state.idempotency.insert(key, original);
+        }
+    }
+    reply
+}
+
+/// The first request made with an idempotency key.
+struct IdempotentRequest {
+    path: String,
+    body: Bytes,
+    reply: Reply,
+}
+
+#[derive(Clone)]
+struct Reply {
+    status: StatusCode,
+    body: Bytes,
+}
+
+impl Reply {
+    fn json(status: StatusCode, value: &impl Serialize) -> Self {
+        let body = serde_json::to_vec(value).expect("models serialize to JSON");
+        Self {
+            status,
+            body: body.into(),
+        }
+    }
+
+    fn no_content() -> Self {
+        Self {
+            status: StatusCode::NO_CONTENT,
+            body: Bytes::new(),
+        }
+    }
+
+    fn error(status: StatusCode, code: &str, detail: &str) -> Self {
+        Self::json(status, &json!({ "code": code, "detail": detail }))
+    }
+
+    fn not_found() -> Self {
+        Self::error(StatusCode::NOT_FOUND, "not_found", "Entity not found")
+    }
+
+    fn conflict(detail: &str) -> Self {
+        Self::error(StatusCode::CONFLICT, "conflict", detail)
+    }
+
+    fn invalid(detail: &str) -> Self {
+        Self::error(StatusCode::UNPROCESSABLE_ENTITY, "validation", detail)
+    }
+
+    fn into_response(self) -> Response<Full<Bytes>> {
+        let mut response = Response::new(Full::new(self.body));
+        *response.status_mut() = self.status;
+        if self.status != StatusCode::NO_CONTENT {
+            response.headers_mut().insert(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/json"),
+            );
+        }
+        response
+    }
+}
+
+struct ApiRequest {
+    query: HashMap<String, String>,
+    body: Bytes,
+    page_size: Option<usize>,
+}
+
+impl ApiRequest {
+    fn body<T: DeserializeOwned>(&self) -> Result<T, Reply> {
+        serde_json::from_slice(&self.body).map_err(|e| Reply::invalid(&e.to_string()))
+    }
+
+    fn query(&self, name: &str) -> Option<&str> {
+        self.query.get(name).map(String::as_str)
+    }
+
+    fn query_list(&self, name: &str) -> Option<Vec<&str>> {
+        self.query(name).map(|value| value.split(',').collect())
+    }
+
+    fn ascending(&self) -> Result<bool, Reply> {
+        match self.query("order") {
+            None | Some("ascending") => Ok(true),
+            Some("descending") => Ok(false),
+            Some(_) => Err(Reply::invalid("Invalid order")),
+        }
+    }
+
+    /// Returns one page of `items`, which are sorted by ID in the order given
+    /// by `ascending`.
+    ///
+    /// Like the API, an iterator prefixed with `-` (as in `prevIterator`)
+    /// returns the page before the given ID, and `done` is set when there are
+    /// no more items in the direction being paged through.
+    fn page<T: Serialize>(&self, items: Vec<(&str, T)>, ascending: bool) -> Result<Reply, Reply> {
+        let limit = match self.query("limit") {
+            Some(limit) => limit
+                .parse::<usize>()
+                .ok()
+                .filter(|limit| (1..=MAX_LIMIT).contains(limit))
+                .ok_or_else(|| Reply::invalid("Invalid limit"))?,
+            None => DEFAULT_LIMIT,
+        };
+        let limit = self
+            .page_size
+            .map_or(limit, |page_size| limit.min(page_size));
+
+        let (items, done) = match self.query("iterator") {
+            Some(iterator) => match iterator.strip_prefix('-') {
+                Some(before) => {
+                    let mut items: Vec<_> = items
+                        .into_iter()
+                        .filter(|(id, _)| (*id < before) == ascending && *id != before)
+                        .collect();
+                    let done = items.len() <= limit;
+                    items.drain(..items.len().saturating_sub(limit));
+                    (items, done)
+                }
+                None => {
+                    let mut items: Vec<_> = items
+                        .into_iter()
+                        .filter(|(id, _)| (*id > iterator) == ascending && *id != iterator)
+                        .collect();
+                    let done = items.len() <= limit;
+                    items.truncate(limit);
+                    (items, done)
+                }
+            },
+            None => {
+                let mut items = items;
+                let done = items.len() <= limit;
+                items.truncate(limit);
+                (items, done)
+            }
+        };
+
+        let iterator = items.last().map(|(id, _)| id.to_string());
+        let prev_iterator = items.first().map(|(id, _)| format!("-{id}"));
+        let data: Vec<_> = items.into_iter().map(|(_, item)| item).collect();
+        Ok(Reply::json(
+            StatusCode::OK,
+            &json!({
+                "data": data,
+                "done": done,
+                "iterator": iterator,
+                "prevIterator": prev_iterator,
+            }),
+        ))
+    }
+}
+
+#[derive(Default)]
+struct State {
+    page_size: Option<usize>,
+    apps: BTreeMap<String, App>,
+    idempotency: HashMap<String, IdempotentRequest>,
+}
+
+struct App {
+    app: ApplicationOut,
+    endpoints: BTreeMap<String, Endpoint>,
+    messages: BTreeMap<String, MessageOut>,
+    attempts: BTreeMap<String, MessageAttemptOut>,
+}
+
+struct Endpoint {
+    endpoint: EndpointOut,
+    secret: String,
+}
+
+impl State {
+    fn route(
+        &mut self,
+        method: &Method,
+        segments: &[&str],
+        req: &ApiRequest,
+    ) -> Result<Reply, Reply> {
+        let ["api", "v1", "app", rest @ ..] = segments else {
+            return Err(Reply::not_found());
+        };
+        match (method, rest) {
+            (&Method::GET, []) => {
+                let ascending = req.ascending()?;
+                let apps = self.apps.iter().map(|(id, app)| (id.as_str(), &app.app));
+                req.page(sorted(apps, ascending), ascending)
+            }
+            (&Method::POST, []) => self.create_app(req),
+            (&Method::GET, [app_id]) => Ok(Reply::json(StatusCode::OK, &self.app(app_id)?.app)),
+            (&Method::PUT, [app_id]) => {
+                let application_in: ApplicationIn = req.body()?;
+                self.check_app_uid(application_in.uid.as_deref(), app_id)?;
+                let app = &mut self.app(app_id)?.app;
+                app.metadata = application_in.metadata.unwrap_or_default();
+                app.name = application_in.name;
+                app.rate_limit = application_in.rate_limit;
+                app.uid = application_in.uid;
+                app.updated_at = now();
+                Ok(Reply::json(StatusCode::OK, app))
+            }
+            (&Method::PATCH, [app_id]) => {
+                let patch: ApplicationPatch = req.body()?;
+                if let Some(Some(uid)) = &patch.uid {
+                    self.check_app_uid(Some(uid), app_id)?;
+                }
+                let app = &mut self.app(app_id)?.app;
+                if let Some(metadata) = patch.metadata {
+                    app.metadata = metadata;
+                }
+                if let Some(name) = patch.name {
+                    app.name = name;
+                }
+                if let Some(rate_limit) = patch.rate_limit {
+                    app.rate_limit = rate_limit;
+                }
+                if let Some(uid) = patch.uid {
+                    app.uid = uid;
+                }
+                app.updated_at = now();
+                Ok(Reply::json(StatusCode::OK, app))
+            }
+            (&Method::DELETE, [app_id]) => {
+                let id = self.app(app_id)?.app.id.clone();
+                self.apps.remove(&id);
+                Ok(Reply::no_content())
+            }
+            (method, [app_id, "endpoint", rest @ ..]) => {
+                self.route_endpoint(method, app_id, rest, req)
+            }
+            (&Method::GET, [app_id, "msg"]) => {
+                let app = self.app(app_id)?;
+                let event_types = req.query_list("event_types");
+                let messages = app.messages.iter().filter(|(_, msg)| {
+                    event_types
+                        .as_ref()
+                        .is_none_or(|types| types.contains(&msg.event_type.as_str()))
+                        && req
+                            .query("channel")
+                            .is_none_or(|channel| contains(&msg.channels, channel))
+                        && req.query("tag").is_none_or(|tag| contains(&msg.tags, tag))
+                });
+                req.page(
+                    sorted(messages.map(|(id, msg)| (id.as_str(), msg)), false),
+                    false,
+                )
+            }
+            (&Method::POST, [app_id, "msg"]) => self.create_message(app_id, req),
+            (&Method::GET, [app_id, "msg", msg_id]) => {
+                let app = self.app(app_id)?;
+                let msg = app.messages.get(*msg_id).or_else(|| {
+                    app.messages
+                        .values()
+                        .find(|msg| msg.event_id.as_deref() == Some(msg_id))
+                });
+                Ok(Reply::json(
+                    StatusCode::OK,
+                    msg.ok_or_else(Reply::not_found)?,
+                ))
+            }
+            (&Method::GET, [app_id, "attempt", kind @ ("msg" | "endpoint"), id]) => {
+                let app = self.app(app_id)?;
+                let endpoint_id = match *kind {
+                    "endpoint" => Some(app.endpoint(id)?.endpoint.id.clone()),
+                    _ => req.query("endpoint_id").map(ToOwned::to_owned),
+                };
+                let status = req.query("status");
+                let attempts = app.attempts.iter().filter(|(_, attempt)| {
+                    (*kind == "endpoint" || attempt.msg_id == *id)
+                        && endpoint_id
+                            .as_ref()
+                            .is_none_or(|endpoint_id| attempt.endpoint_id == *endpoint_id)
+                        && status.is_none_or(|status| attempt.status.to_string() == status)
+                });
+                req.page(
+                    sorted(attempts.map(|(id, attempt)| (id.as_str(), attempt)), false),
+                    false,
+                )
+            }
+            _ => Err(Reply::not_found()),
+        }
+    }
+
+    fn route_endpoint(
+        &mut self,
+        method: &Method,
+        app_id: &str,
+        segments: &[&str],
+        req: &ApiRequest,
+    ) -> Result<Reply, Reply> {
+        let app = self.app(app_id)?;
+        match (method, segments) {
+            (&Method::GET, []) => {
+                let ascending = req.ascending()?;
+                let endpoints = app
+                    .endpoints
+                    .iter()
+                    .map(|(id, endpoint)| (id.as_str(), &endpoint.endpoint));
+                req.page(sorted(endpoints, ascending), ascending)
+            }
+            (&Method::POST, []) => {
+                let endpoint_in: EndpointIn = req.body()?;
+                check_url(&endpoint_in.url)?;
+                let id = next_id("ep");
+                app.check_endpoint_uid(endpoint_in.uid.as_deref(), &id)?;
+                let now = now();
+                let endpoint = EndpointOut {
+                    channels: endpoint_in.channels,
+                    disabled: endpoint_in.disabled,
+                    filter_types: endpoint_in.filter_types,
+                    rate_limit: endpoint_in.rate_limit,
+                    uid: endpoint_in.uid,
+                    ..EndpointOut::new(
+                        now,
+                        endpoint_in.description.unwrap_or_default(),
+                        id.clone(),
+                        endpoint_in.metadata.unwrap_or_default(),
+                        now,
+                        endpoint_in.url,
+                        endpoint_in.version.unwrap_or(1),
+                    )
+                };
+                let secret = endpoint_in.secret.unwrap_or_else(|| new_secret(&id));
+                let reply = Reply::json(StatusCode::CREATED, &endpoint);
+                app.endpoints.insert(id, Endpoint { endpoint, secret });
+                Ok(reply)
+            }
+            (&Method::GET, [endpoint_id]) => Ok(Reply::json(
+                StatusCode::OK,
+                &app.endpoint(endpoint_id)?.endpoint,
+            )),
+            (&Method::PUT, [endpoint_id]) => {
+                let update: EndpointUpdate = req.body()?;
+                check_url(&update.url)?;
+                let id = app.endpoint(endpoint_id)?.endpoint.id.clone();
+                app.check_endpoint_uid(update.uid.as_deref(), &id)?;
+                let endpoint = &mut app.endpoint(endpoint_id)?.endpoint;
+                endpoint.channels = update.channels;
+                endpoint.description = update.description.unwrap_or_default();
+                endpoint.disabled = update.disabled;
+                endpoint.filter_types = update.filter_types;
+                endpoint.metadata = update.metadata.unwrap_or_default();
+                endpoint.rate_limit = update.rate_limit;
+                endpoint.uid = update.uid;
+                endpoint.url = update.url;
+                endpoint.version = update.version.unwrap_or(endpoint.version);
+                endpoint.updated_at = now();
+                Ok(Reply::json(StatusCode::OK, endpoint))
+            }
+            (&Method::PATCH, [endpoint_id]) => {
+                let patch: EndpointPatch = req.body()?;
+                if let Some(url) = &patch.url {
+                    check_url(url)?;
+                }
+                let id = app.endpoint(endpoint_id)?.endpoint.id.clone();
+                if let Some(Some(uid)) = &patch.uid {
+                    app.check_endpoint_uid(Some(uid), &id)?;
+                }
+                let Endpoint { endpoint, secret } = app.endpoint(endpoint_id)?;
+                if let Some(channels) = patch.channels {
+                    endpoint.channels = channels;
+                }
+                if let Some(description) = patch.description {
+                    endpoint.description = description;
+                }
+                if let Some(disabled) = patch.disabled {
+                    endpoint.disabled = Some(disabled);
+                }
+                if let Some(filter_types) = patch.filter_types {
+                    endpoint.filter_types = filter_types;
+                }
+                if let Some(metadata) = patch.metadata {
+                    endpoint.metadata = metadata;
+                }
+                if let Some(rate_limit) = patch.rate_limit {
+                    endpoint.rate_limit = rate_limit;
+                }
+                if let Some(patched) = patch.secret {
+                    *secret = patched.unwrap_or_else(|| new_secret(&id));
+                }
+                if let Some(uid) = patch.uid {
+                    endpoint.uid = uid;
+                }
+                if let Some(url) = patch.url {
+                    endpoint.url = url;
+                }
+                if let Some(version) = patch.version {
+                    endpoint.version = version;
+                }
+                endpoint.updated_at = now();
+                Ok(Reply::json(StatusCode::OK, endpoint))
+            }
+            (&Method::DELETE, [endpoint_id]) => {
+                let id = app.endpoint(endpoint_id)?.endpoint.id.clone();
+                app.endpoints.remove(&id);
+                Ok(Reply::no_content())
+            }
+            (&Method::GET, [endpoint_id, "secret"]) => Ok(Reply::json(
+                StatusCode::OK,
+                &EndpointSecretOut::new(app.endpoint(endpoint_id)?.secret.clone()),
+            )),
+            _ => Err(Reply::not_found()),
+        }
+    }
+
+    fn create_app(&mut self, req: &ApiRequest) -> Result<Reply, Reply> {
+        let application_in: ApplicationIn = req.body()?;
+        if let Some(uid) = &application_in.uid {
+            if let Ok(app) = self.app(uid) {
+                return match req.query("get_if_exists") {
+                    Some("true") => Ok(Reply::json(StatusCode::OK, &app.app)),
+                    _ => Err(Reply::conflict(
+                        "An application with this uid already exists",
+                    )),
+                };
+            }
+        }
+        let app = self.insert_app(application_in);
+        Ok(Reply::json(StatusCode::CREATED, &app.app))
+    }
+
+    fn insert_app(&mut self, application_in: ApplicationIn) -> &mut App {
+        let id = next_id("app");
+        let now = now();
+        let app = ApplicationOut {
+            rate_limit: application_in.rate_limit,
+            uid: application_in.uid,
+            ..ApplicationOut::new(
+                now,
+                id.clone(),
+                application_in.metadata.unwrap_or_default(),
+                application_in.name,
+                now,
+            )
+        };
+        self.apps.entry(id).or_insert(App {
+            app,
+            endpoints: BTreeMap::new(),
+            messages: BTreeMap::new(),
+            attempts: BTreeMap::new(),
+        })
+    }
+
+    fn create_message(&mut self, app_id: &str, req: &ApiRequest) -> Result<Reply, Reply> {
+        let message_in: MessageIn = req.body()?;
+        let app = match (self.app(app_id).is_ok(), message_in.application) {
+            (true, _) => self.app(app_id)?,
+            (false, Some(application_in)) if application_in.uid.as_deref() == Some(app_id) => {
+                self.insert_app(*application_in)
+            }
+            (false, _) => return Err(Reply::not_found()),
+        };
+        if let Some(event_id) = &message_in.event_id {
+            if app
+                .messages
+                .values()
+                .any(|msg| msg.event_id.as_ref() == Some(event_id))
+            {
+                return Err(Reply::conflict(
+                    "A message with this eventId already exists",
+                ));
+            }
+        }
+
+        let msg_id = next_id("msg");
+        let timestamp = now();
+        let msg = MessageOut {
+            channels: message_in.channels.map(strings),
+            event_id: message_in.event_id,
+            tags: message_in.tags.map(strings),
+            ..MessageOut::new(
+                message_in.event_type.into(),
+                msg_id.clone(),
+                message_in.payload.into(),
+                timestamp,
+            )
+        };
+        let endpoints = app.endpoints.values().filter(|Endpoint { endpoint, .. }| {
+            endpoint.disabled != Some(true)
+                && endpoint
+                    .filter_types
+                    .as_ref()
+                    .is_none_or(|types| types.contains(&msg.event_type))
+                && endpoint.channels.as_ref().is_none_or(|channels| {
+                    channels
+                        .iter()
+                        .any(|channel| contains(&msg.channels, channel))
+                })
+        });
+        for endpoint in endpoints {
+            let attempt_id = next_id("atmpt");
+            let attempt = MessageAttemptOut::new(
+                endpoint.endpoint.id.clone(),
+                attempt_id.clone(),
+                msg_id.clone(),
+                "{}".to_owned(),
+                0,
+                200,
+                MessageStatus::Success,
+                timestamp,
+                MessageAttemptTriggerType::Scheduled,
+                endpoint.endpoint.url.clone(),
+            );
+            app.attempts.insert(attempt_id, attempt);
+        }
+
+        let reply = Reply::json(StatusCode::ACCEPTED, &msg);
+        app.messages.insert(msg_id, msg);
+        Ok(reply)
+    }
+
+    /// Looks up an application by ID or UID.
+    fn app(&mut self, app_id: &str) -> Result<&mut App, Reply> {
+        let id = find(&self.apps, app_id, |app| app.app.uid.as_deref())?;
+        Ok(self.apps.get_mut(&id).expect("ID was just looked up"))
+    }
+
+    fn check_app_uid(&mut self, uid: Option<&str>, app_id: &str) -> Result<(), Reply> {
+        let own_id = self.app(app_id)?.app.id.clone();
+        match uid.map(|uid| self.app(uid)) {
+            Some(Ok(other)) if other.app.id != own_id => Err(Reply::conflict(
+                "An application with this uid already exists",
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl App {
+    /// Looks up an endpoint by ID or UID.
+    fn endpoint(&mut self, endpoint_id: &str) -> Result<&mut Endpoint, Reply> {
+        let id = find(&self.endpoints, endpoint_id, |endpoint| {
+            endpoint.endpoint.uid.as_deref()
+        })?;
+        Ok(self.endpoints.get_mut(&id).expect("ID was just looked up"))
+    }
+
+    fn check_endpoint_uid(&mut self, uid: Option<&str>, endpoint_id: &str) -> Result<(), Reply> {
+        match uid.map(|uid| self.endpoint(uid)) {
+            Some(Ok(other)) if other.endpoint.id != endpoint_id => {
+                Err(Reply::conflict("An endpoint with this uid already exists"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Returns a new ID. Like real ones, IDs sort in the order they were created
+/// in.
+fn next_id(prefix: &str) -> String {
+    static LAST_ID: AtomicU64 = AtomicU64::new(0);
+    let id = LAST_ID.fetch_add(1, Ordering::Relaxed) + 1;
+    format!("{prefix}_{id:027}")
+}
+
+fn find<T>(
+    items: &BTreeMap<String, T>,
+    id_or_uid: &str,
+    uid: impl Fn(&T) -> Option<&str>,
+) -> Result<String, Reply> {
+    if items.contains_key(id_or_uid) {
+        return Ok(id_or_uid.to_owned());
+    }
+    items
+        .iter()
+        .find(|(_, item)| uid(item) == Some(id_or_uid))
+        .map(|(id, _)| id.clone())
+        .ok_or_else(Reply::not_found)
+}
+
+fn sorted<'a, T>(
+    items: impl DoubleEndedIterator<Item = (&'a str, T)>,
+    ascending: bool,
+) -> Vec<(&'a str, T)> {
+    if ascending {
+        items.collect()
+    } else {
+        items.rev().collect()
+    }
+}
+
+fn contains(values: &Option<Vec<String>>, value: &str) -> bool {
+    values.iter().flatten().any(|v| v == value)
+}
+
+fn strings<T: Into<String>>(names: Vec<T>) -> Vec<String> {
+    names.into_iter().map(Into::into).collect()
+}
+
+fn check_url(value: &str) -> Result<(), Reply> {
+    match url::Url::parse(value) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(()),
+        _ => Err(Reply::invalid("Invalid endpoint URL")),
+    }
+}
+
+fn new_secret(endpoint_id: &str) -> String {
+    format!("whsec_{}", base64::encode(endpoint_id))
+}
+
+fn now() -> Timestamp {
+    Timestamp::now()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MockSvixServer;
+    use crate::{
+        api::{
+            ApplicationIn, ApplicationListOptions, EndpointIn, EndpointListOptions,
+            MessageAttemptListOptions, MessageIn, MessageListOptions, Ordering, PostOptions,
+        },
+        error::Error,
+    };
+
+    #[tokio::test]
+    async fn test_mock_server() {
+        let server = MockSvixServer::start().await.unwrap();
+        let svix = server.client();
+
+        let app = svix
+            .application()
+            .create(
+                ApplicationIn {
+                    uid: Some("my-app".to_owned()),
+                    ..ApplicationIn::new("My app".to_owned())
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            svix.application().get("my-app".to_owned()).await.unwrap(),
+            app
+        );
+
+        for i in 0..3 {
+            svix.endpoint()
+                .create(
+                    app.id.clone(),
+                    EndpointIn {
+                        filter_types: (i == 2).then(|| vec!["other.event".to_owned()]),
+                        ..EndpointIn::new(format!("https://example.com/{i}"))
+                    },
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+        let page = svix
+            .endpoint()
+            .list(
+                app.id.clone(),
+                Some(EndpointListOptions {
+                    limit: Some(2),
+                    ..Default::default()
+                }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(page.data.len(), 2);
+        assert!(!page.done);
+        let page = svix
+            .endpoint()
+            .list(
+                app.id.clone(),
+                Some(EndpointListOptions {
+                    iterator: page.iterator,
+                    limit: Some(2),
+                    ..Default::default()
+                }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(page.data.len(), 1);
+        assert!(page.done);
+
+        let options = || {
+            Some(PostOptions {
+                idempotency_key: Some("key".to_owned()),
+            })
+        };
+        let msg_in = MessageIn::new("user.signup".parse().unwrap(), serde_json::json!({}));
+        let msg = svix
+            .message()
+            .create(app.id.clone(), msg_in.clone(), options())
+            .await
+            .unwrap();
+        let replayed = svix
+            .message()
+            .create(app.id.clone(), msg_in, options())
+            .await
+            .unwrap();
+        assert_eq!(msg, replayed);
+        let err = svix
+            .message()
+            .create(
+                app.id.clone(),
+                MessageIn::new("user.deleted".parse().unwrap(), serde_json::json!({})),
+                options(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Http(e) if e.status == http02::StatusCode::CONFLICT));
+        let messages = svix
+            .message()
+            .list(app.id.clone(), Some(MessageListOptions::default()))
+            .await
+            .unwrap();
+        assert_eq!(messages.data, vec![msg.clone()]);
+
+        let attempts = svix
+            .message_attempt()
+            .list_by_msg(
+                app.id.clone(),
+                msg.id,
+                Some(MessageAttemptListOptions::default()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(attempts.data.len(), 2);
+
+        let err = svix
+            .application()
+            .get("missing".to_owned())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Http(e) if e.status == http02::StatusCode::NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn test_pagination() {
+        let server = MockSvixServer::start_with_page_size(2).await.unwrap();
+        let svix = server.client();
+        let mut ids = Vec::new();
+        for i in 0..5 {
+            let app = svix
+                .application()
+                .create(ApplicationIn::new(format!("App {i}")), None)
+                .await
+                .unwrap();
+            ids.push(app.id);
+        }
+        ids.reverse();
+
+        let applications = svix.application();
+        let list = |iterator| {
+            applications.list(Some(ApplicationListOptions {
+                iterator,
+                limit: Some(10),
+                order: Some(Ordering::Descending),
+            }))
+        };
+        let mut pages = Vec::new();
+        let mut iterator = None;
+        loop {
+            let page = list(iterator).await.unwrap();
+            pages.push(page.data.into_iter().map(|app| app.id).collect::<Vec<_>>());
+            if page.done {
+                break;
+            }
+            iterator = page.iterator;
+        }
+        assert_eq!(pages, vec![&ids[..2], &ids[2..4], &ids[4..]]);
+
+        let last = list(Some(ids[3].clone())).await.unwrap();
+        let prev = list(last.prev_iterator).await.unwrap();
+        let prev_ids: Vec<_> = prev.data.into_iter().map(|app| app.id).collect();
+        assert_eq!(prev_ids, &ids[2..4]);
+        assert!(!prev.done);
+    }
+}
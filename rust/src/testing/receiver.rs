@@ -0,0 +1,208 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! A local webhook endpoint for end-to-end tests of sending webhooks.
+
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use http1::{HeaderMap, Response, StatusCode};
+use http_body_util::{BodyExt as _, Full};
+use hyper::body::Bytes;
+use tokio::{sync::Notify, task::JoinHandle};
+
+use super::mock_server::spawn_server;
+use crate::{
+    error::{Error, Result},
+    webhooks::Webhook,
+};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A local server which receives webhooks, for testing that they're sent.
+///
+/// Create an endpoint with [`TestReceiver::url`] and the receiver's secret,
+/// send messages, and then wait for them with [`TestReceiver::expect_event`].
+/// Every webhook is recorded, but only the ones with a valid signature are
+/// accepted, the others get a 401 response.
+pub struct TestReceiver {
+    addr: SocketAddr,
+    task: JoinHandle<()>,
+    state: Arc<ReceiverState>,
+}
+
+/// A webhook received by a [`TestReceiver`].
+#[derive(Clone, Debug)]
+pub struct ReceivedWebhook {
+    pub headers: HeaderMap,
+    pub body: Bytes,
+    /// Whether the signature of the webhook was valid.
+    pub verified: bool,
+}
+
+impl ReceivedWebhook {
+    /// The body of the webhook, parsed as JSON.
+    pub fn payload(&self) -> Option<serde_json::Value> {
+        serde_json::from_slice(&self.body).ok()
+    }
+
+    /// The `type` field of the payload, which is where Svix payloads
+    /// conventionally hold their event type.
+    pub fn event_type(&self) -> Option<String> {
+        self.payload()?.get("type")?.as_str().map(ToOwned::to_owned)
+    }
+}
+
+struct ReceiverState {
+    webhook: Webhook,
+    received: Mutex<Vec<ReceivedWebhook>>,
+    notify: Notify,
+}
+
+impl TestReceiver {
+    /// Starts a receiver on a random local port, which verifies webhooks with
+    /// the given endpoint secret.
+    pub async fn start(secret: &str) -> Result<Self> {
+        let state = Arc::new(ReceiverState {
+            webhook: Webhook::new(secret).map_err(Error::generic)?,
+            received: Mutex::default(),
+            notify: Notify::new(),
+        });
+
+        let handler_state = state.clone();
+        let (addr, task) = spawn_server(move |req| {
+            let state = handler_state.clone();
+            async move {
+                let (parts, body) = req.into_parts();
+                let body = body.collect().await.map(|b| b.to_bytes());
+                let status = match body {
+                    Ok(body) => {
+                        let verified = state.webhook.verify(&body, &parts.headers).is_ok();
+                        state.received.lock().unwrap().push(ReceivedWebhook {
+                            headers: parts.headers,
+                            body,
+                            verified,
+                        });
+                        state.notify.notify_waiters();
+                        if verified {
+                            StatusCode::NO_CONTENT
+                        } else {
+                            StatusCode::UNAUTHORIZED
+                        }
+                    }
+                    Err(_) => StatusCode::BAD_REQUEST,
+                };
+                let mut response = Response::new(Full::new(Bytes::new()));
+                *response.status_mut() = status;
+                response
+            }
+        })
+        .await
+        .map_err(Error::generic)?;
+        Ok(Self { addr, task, state })
+    }
+
+    /// The URL to use for the endpoint.
+    pub fn url(&self) -> String {
+        format!("http://{}/webhook", self.addr)
+    }
+
+    /// All webhooks received so far, in the order they arrived.
+    pub fn received(&self) -> Vec<ReceivedWebhook> {
+        self.state.received.lock().unwrap().clone()
+    }
+
+    /// Waits up to 10 seconds for a verified webhook with the given event type,
+    /// and returns the first one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no such webhook arrives in time.
+    pub async fn expect_event(&self, event_type: &str) -> ReceivedWebhook {
+        self.expect_event_within(event_type, DEFAULT_TIMEOUT).await
+    }
+
+    /// Like [`expect_event`](Self::expect_event), with a custom timeout.
+    pub async fn expect_event_within(
+        &self,
+        event_type: &str,
+        timeout: Duration,
+    ) -> ReceivedWebhook {
+        let find = || {
+            self.received().into_iter().find(|webhook| {
+                webhook.verified && webhook.event_type().as_deref() == Some(event_type)
+            })
+        };
+        let wait = async {
+            loop {
+                let notified = self.state.notify.notified();
+                if let Some(webhook) = find() {
+                    return webhook;
+                }
+                notified.await;
+            }
+        };
+        match tokio::time::timeout(timeout, wait).await {
+            Ok(webhook) => webhook,
+            Err(_) => panic!(
+                "no verified {event_type:?} webhook received within {timeout:?}, got {} webhooks",
+                self.received().len()
+            ),
+        }
+    }
+}
+
+impl Drop for TestReceiver {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http_body_util::Full;
+    use hyper::body::Bytes;
+    use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+
+    use super::TestReceiver;
+    use crate::{testing::SvixTestSigner, webhooks::unix_timestamp_now};
+
+    const SECRET: &str = "whsec_C2FVsBQIhrscChlQIMV+b5sSYspob7oD";
+
+    #[tokio::test]
+    async fn test_receiver() {
+        let receiver = TestReceiver::start(SECRET).await.unwrap();
+        let client = Client::builder(TokioExecutor::new()).build_http::<Full<Bytes>>();
+        let send = |secret: &str, payload: &'static [u8]| {
+            let headers = SvixTestSigner::new(secret)
+                .unwrap()
+                .headers("msg_1", unix_timestamp_now(), payload)
+                .unwrap();
+            let mut req = http1::Request::post(receiver.url())
+                .body(Full::new(Bytes::from_static(payload)))
+                .unwrap();
+            *req.headers_mut() = headers;
+            client.request(req)
+        };
+
+        let response = send(
+            "whsec_YW5vdGhlci10ZXN0LXNlY3JldC0yNGJ5",
+            br#"{"type": "invoice.paid"}"#,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), 401);
+        let response = send(SECRET, br#"{"type": "invoice.paid", "id": 1}"#)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 204);
+
+        let webhook = receiver.expect_event("invoice.paid").await;
+        assert!(webhook.verified);
+        assert_eq!(webhook.payload().unwrap()["id"], 1);
+        assert_eq!(receiver.received().len(), 2);
+    }
+}
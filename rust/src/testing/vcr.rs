@@ -0,0 +1,331 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Recording API traffic and replaying it in tests.
+
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use http1::{header, Request, Response, StatusCode};
+use http_body_util::{BodyExt as _, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper_util::{client::legacy::Client as HyperClient, rt::TokioExecutor};
+use serde_json::{json, Value};
+use tokio::task::JoinHandle;
+
+use super::mock_server::spawn_server;
+use crate::{
+    api::{Svix, SvixOptions},
+    dump::redacted_body,
+    Connector,
+};
+
+/// A local server that records the API traffic going through it to a
+/// "cassette" file, or replays a previously recorded one.
+///
+/// Tests can run against the real API once in record mode, and then
+/// deterministically and without network access in replay mode. Point the
+/// client at the server with [`VcrServer::client`].
+///
+/// Cassettes are JSON, meant to be checked in next to the tests. The
+/// `authorization` header is never recorded, and the values of fields
+/// holding credentials (like endpoint secrets) are redacted.
+pub struct VcrServer {
+    addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl VcrServer {
+    /// Forwards requests to `upstream_url` (e.g. `https://api.eu.svix.com`),
+    /// recording them to the cassette at `path`, which is overwritten.
+    pub async fn record(path: impl Into<PathBuf>, upstream_url: &str) -> std::io::Result<Self> {
+        let recorder = Arc::new(Recorder {
+            client: HyperClient::builder(TokioExecutor::new()).build(crate::default_connector()),
+            upstream_url: upstream_url.trim_end_matches('/').to_owned(),
+            path: path.into(),
+            cassette: Mutex::default(),
+        });
+        recorder.save()?;
+
+        let (addr, task) = spawn_server(move |req| {
+            let recorder = recorder.clone();
+            async move { recorder.forward(req).await }
+        })
+        .await?;
+        Ok(Self { addr, task })
+    }
+
+    /// Serves the responses recorded in the cassette at `path`.
+    ///
+    /// Each request is answered with the first recorded interaction with the
+    /// same method, path, query and body which hasn't been replayed yet.
+    /// Requests without one fail with a 500 response.
+    pub async fn replay(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let cassette: Cassette = serde_json::from_slice(&std::fs::read(path)?)?;
+        let unplayed = Arc::new(Mutex::new(cassette.interactions));
+
+        let (addr, task) = spawn_server(move |req| {
+            let unplayed = unplayed.clone();
+            async move {
+                let Ok((request, _)) = RecordedRequest::read(req).await else {
+                    return error_response(StatusCode::BAD_REQUEST, "invalid request");
+                };
+                let mut unplayed = unplayed.lock().unwrap();
+                match unplayed.iter().position(|i| i.request == request) {
+                    Some(idx) => unplayed.remove(idx).response.into_response(),
+                    None => error_response(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        &format!(
+                            "no recorded interaction for {} {}",
+                            request.method, request.uri
+                        ),
+                    ),
+                }
+            }
+        })
+        .await?;
+        Ok(Self { addr, task })
+    }
+
+    /// The URL to pass as [`SvixOptions::server_url`].
+    pub fn server_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Returns a client pointed at this server.
+    ///
+    /// The token is only checked by the upstream server when recording.
+    pub fn client(&self, token: String) -> Svix {
+        Svix::new(
+            token,
+            Some(SvixOptions {
+                server_url: Some(self.server_url()),
+                ..Default::default()
+            }),
+        )
+    }
+}
+
+impl Drop for VcrServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(PartialEq, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    /// The path and query, with the query parameters sorted.
+    uri: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    idempotency_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    body: Option<Value>,
+}
+
+impl RecordedRequest {
+    /// Returns the recorded form of `req`, along with the original.
+    async fn read(req: Request<Incoming>) -> Result<(Self, Request<Full<Bytes>>), hyper::Error> {
+        let (parts, body) = req.into_parts();
+        let body = body.collect().await?.to_bytes();
+
+        let mut query: Vec<_> = parts
+            .uri
+            .query()
+            .map(|query| {
+                url::form_urlencoded::parse(query.as_bytes())
+                    .into_owned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        query.sort();
+        let mut uri = parts.uri.path().to_owned();
+        if !query.is_empty() {
+            uri.push('?');
+            uri += &url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(query)
+                .finish();
+        }
+
+        let recorded = Self {
+            method: parts.method.to_string(),
+            uri,
+            idempotency_key: parts
+                .headers
+                .get("idempotency-key")
+                .and_then(|value| value.to_str().ok())
+                .map(ToOwned::to_owned),
+            body: redacted_body(&body),
+        };
+        Ok((recorded, Request::from_parts(parts, Full::new(body))))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    body: Option<Value>,
+}
+
+impl RecordedResponse {
+    fn into_response(self) -> Response<Full<Bytes>> {
+        let body = match &self.body {
+            Some(body) => serde_json::to_vec(body)
+                .expect("JSON values serialize")
+                .into(),
+            None => Bytes::new(),
+        };
+        let mut response = Response::new(Full::new(body));
+        *response.status_mut() = StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK);
+        if self.body.is_some() {
+            response.headers_mut().insert(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/json"),
+            );
+        }
+        response
+    }
+}
+
+struct Recorder {
+    client: HyperClient<Connector, Full<Bytes>>,
+    upstream_url: String,
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+}
+
+impl Recorder {
+    async fn forward(&self, req: Request<Incoming>) -> Response<Full<Bytes>> {
+        let Ok((recorded_request, mut req)) = RecordedRequest::read(req).await else {
+            return error_response(StatusCode::BAD_REQUEST, "invalid request");
+        };
+        let path_and_query = req.uri().path_and_query().map_or("/", |p| p.as_str());
+        let Ok(uri) = format!("{}{path_and_query}", self.upstream_url).parse() else {
+            return error_response(StatusCode::BAD_GATEWAY, "invalid upstream URL");
+        };
+        *req.uri_mut() = uri;
+        req.headers_mut().remove(header::HOST);
+
+        let response = match self.client.request(req).await {
+            Ok(response) => response,
+            Err(e) => return error_response(StatusCode::BAD_GATEWAY, &e.to_string()),
+        };
+        let status = response.status();
+        let body = match response.into_body().collect().await {
+            Ok(body) => body.to_bytes(),
+            Err(e) => return error_response(StatusCode::BAD_GATEWAY, &e.to_string()),
+        };
+
+        self.cassette
+            .lock()
+            .unwrap()
+            .interactions
+            .push(Interaction {
+                request: recorded_request,
+                response: RecordedResponse {
+                    status: status.as_u16(),
+                    body: redacted_body(&body),
+                },
+            });
+        if let Err(e) = self.save() {
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &format!("failed to save cassette: {e}"),
+            );
+        }
+
+        let mut response = Response::new(Full::new(body));
+        *response.status_mut() = status;
+        response.headers_mut().insert(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("application/json"),
+        );
+        response
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        let cassette = self.cassette.lock().unwrap();
+        std::fs::write(&self.path, serde_json::to_vec_pretty(&*cassette)?)
+    }
+}
+
+fn error_response(status: StatusCode, detail: &str) -> Response<Full<Bytes>> {
+    let body = json!({ "code": "vcr", "detail": detail }).to_string();
+    let mut response = Response::new(Full::new(body.into()));
+    *response.status_mut() = status;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VcrServer;
+    use crate::{
+        api::{ApplicationIn, EndpointIn},
+        error::Error,
+        testing::MockSvixServer,
+    };
+
+    #[tokio::test]
+    async fn test_record_replay() {
+        let path = std::env::temp_dir().join(format!("svix-vcr-{}.json", std::process::id()));
+
+        let upstream = MockSvixServer::start().await.unwrap();
+        let vcr = VcrServer::record(&path, &upstream.server_url())
+            .await
+            .unwrap();
+        let svix = vcr.client("testsk_secret".to_owned());
+        let app = svix
+            .application()
+            .create(ApplicationIn::new("My app".to_owned()), None)
+            .await
+            .unwrap();
+        let endpoint = svix
+            .endpoint()
+            .create(
+                app.id.clone(),
+                EndpointIn::new("https://example.com".to_owned()),
+                None,
+            )
+            .await
+            .unwrap();
+        let secret = svix
+            .endpoint()
+            .get_secret(app.id.clone(), endpoint.id.clone())
+            .await
+            .unwrap();
+        assert!(secret.key.starts_with("whsec_"));
+        drop((vcr, upstream));
+
+        let cassette = std::fs::read_to_string(&path).unwrap();
+        assert!(!cassette.contains("testsk_secret"));
+        assert!(!cassette.contains(&secret.key));
+
+        let vcr = VcrServer::replay(&path).await.unwrap();
+        let svix = vcr.client("testsk_other".to_owned());
+        let replayed = svix
+            .application()
+            .create(ApplicationIn::new("My app".to_owned()), None)
+            .await
+            .unwrap();
+        assert_eq!(replayed, app);
+        let err = svix.application().get(app.id.clone()).await.unwrap_err();
+        assert!(matches!(err, Error::Http(_)));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,189 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Helpers for stubbing the Svix API with [`wiremock`](::wiremock).
+//!
+//! ```ignore
+//! Mock::given(method("POST"))
+//!     .and(svix_path("/api/v1/app/{app_id}/msg"))
+//!     .and(idempotency_key("order-1234"))
+//!     .respond_with(json_response(202, &message_out))
+//!     .mount(&server)
+//!     .await;
+//! let svix = svix_client(&server);
+//! ```
+
+use ::wiremock::{
+    matchers::{header, header_exists, HeaderExactMatcher, HeaderExistsMatcher},
+    Match, MockServer, Request, ResponseTemplate,
+};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::api::{Svix, SvixOptions};
+
+/// Returns a client pointed at the given mock server.
+pub fn svix_client(server: &MockServer) -> Svix {
+    Svix::new(
+        "testsk_wiremock".to_owned(),
+        Some(SvixOptions {
+            server_url: Some(server.uri()),
+            ..Default::default()
+        }),
+    )
+}
+
+/// Matches requests to a path template from the API reference, e.g.
+/// `/api/v1/app/{app_id}/endpoint/{endpoint_id}`.
+///
+/// Each `{param}` matches exactly one path segment, whatever its value.
+pub fn svix_path(template: &str) -> SvixPathMatcher {
+    let segments = template
+        .trim_matches('/')
+        .split('/')
+        .map(|segment| {
+            let is_param = segment.starts_with('{') && segment.ends_with('}');
+            (!is_param).then(|| segment.to_owned())
+        })
+        .collect();
+    SvixPathMatcher { segments }
+}
+
+/// See [`svix_path`].
+#[derive(Debug)]
+pub struct SvixPathMatcher {
+    /// The literal path segments, `None` for parameters.
+    segments: Vec<Option<String>>,
+}
+
+impl Match for SvixPathMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        let path = request.url.path().trim_matches('/');
+        let mut segments = path.split('/');
+        self.segments.iter().all(|expected| {
+            segments
+                .next()
+                .is_some_and(|segment| expected.as_ref().is_none_or(|s| s == segment))
+        }) && segments.next().is_none()
+    }
+}
+
+/// Matches requests authenticated with a bearer token, like the ones sent by
+/// [`Svix`].
+pub fn authenticated() -> AuthenticatedMatcher {
+    AuthenticatedMatcher
+}
+
+/// See [`authenticated`].
+#[derive(Debug)]
+pub struct AuthenticatedMatcher;
+
+impl Match for AuthenticatedMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        request
+            .headers
+            .get(http1::header::AUTHORIZATION)
+            .is_some_and(|value| value.as_bytes().starts_with(b"Bearer "))
+    }
+}
+
+/// Matches requests with the given idempotency key.
+pub fn idempotency_key(key: &str) -> HeaderExactMatcher {
+    header("idempotency-key", key)
+}
+
+/// Matches requests with any idempotency key.
+pub fn has_idempotency_key() -> HeaderExistsMatcher {
+    header_exists("idempotency-key")
+}
+
+/// A response with the given status and JSON body.
+pub fn json_response(status: u16, body: &impl Serialize) -> ResponseTemplate {
+    ResponseTemplate::new(status).set_body_json(body)
+}
+
+/// A page of a list response, the last one if `iterator` is `None`.
+pub fn list_response<T: Serialize>(data: &[T], iterator: Option<&str>) -> ResponseTemplate {
+    json_response(
+        200,
+        &json!({
+            "data": data,
+            "done": iterator.is_none(),
+            "iterator": iterator,
+            "prevIterator": null,
+        }),
+    )
+}
+
+/// An error response in the format used by the API, e.g.
+/// `error_response(409, "conflict", "...")`.
+pub fn error_response(status: u16, code: &str, detail: &str) -> ResponseTemplate {
+    json_response(status, &json!({ "code": code, "detail": detail }))
+}
+
+/// The API's response for a missing resource.
+pub fn not_found() -> ResponseTemplate {
+    error_response(404, "not_found", "Entity not found")
+}
+
+#[cfg(all(test, feature = "api-application"))]
+mod tests {
+    use ::wiremock::{matchers::method, Mock, MockServer};
+
+    use super::{
+        authenticated, error_response, idempotency_key, json_response, list_response, svix_client,
+        svix_path,
+    };
+    use crate::{
+        api::{ApplicationIn, ApplicationOut, PostOptions},
+        error::Error,
+    };
+
+    #[tokio::test]
+    async fn test_stubs() {
+        let server = MockServer::start().await;
+        let app = ApplicationOut {
+            id: "app_1".to_owned(),
+            name: "My app".to_owned(),
+            ..Default::default()
+        };
+        Mock::given(method("GET"))
+            .and(svix_path("/api/v1/app/{app_id}"))
+            .and(authenticated())
+            .respond_with(json_response(200, &app))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(svix_path("/api/v1/app"))
+            .respond_with(list_response(std::slice::from_ref(&app), None))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(svix_path("/api/v1/app"))
+            .and(idempotency_key("key"))
+            .respond_with(error_response(409, "conflict", "Duplicate"))
+            .mount(&server)
+            .await;
+
+        let svix = svix_client(&server);
+        assert_eq!(
+            svix.application().get("app_1".to_owned()).await.unwrap(),
+            app
+        );
+        let list = svix.application().list(None).await.unwrap();
+        assert!(list.done);
+        assert_eq!(list.data, vec![app]);
+
+        let err = svix
+            .application()
+            .create(
+                ApplicationIn::new("My app".to_owned()),
+                Some(PostOptions {
+                    idempotency_key: Some("key".to_owned()),
+                }),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Http(e) if e.status == http02::StatusCode::CONFLICT));
+    }
+}
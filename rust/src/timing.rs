@@ -0,0 +1,182 @@
+// SPDX-FileCopyrightText: © 2022 Svix Authors
+// SPDX-License-Identifier: MIT
+
+//! Timing of the stages of opening a connection, for the timing breakdown of
+//! requests with [`SvixOptions::debug`](crate::api::SvixOptions::debug).
+//!
+//! The connector's layers record how long they took into the slot of the
+//! request they're connecting for, which is found through a task-local, so
+//! connections opened in the background (or for other requests) aren't
+//! attributed to the request.
+
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use http1::Uri;
+use hyper_util::client::legacy::connect::{
+    dns::{GaiAddrs, GaiResolver, Name},
+    HttpConnector,
+};
+use tower_service::Service;
+
+tokio::task_local! {
+    static CONNECT_TIMINGS: Arc<Mutex<ConnectTimings>>;
+}
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+/// How long opening the connection of a request took, if it opened one.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ConnectTimings {
+    /// Resolving the server's name.
+    pub dns: Option<Duration>,
+    /// Resolving the name and opening the TCP connection.
+    pub tcp: Option<Duration>,
+    /// Everything, including the TLS handshake.
+    pub total: Option<Duration>,
+}
+
+/// Runs `future`, returning how long the connection it opened took to open.
+pub(crate) async fn record_connect<F: Future>(future: F) -> (F::Output, ConnectTimings) {
+    let timings = Arc::new(Mutex::new(ConnectTimings::default()));
+    let output = CONNECT_TIMINGS.scope(timings.clone(), future).await;
+    let timings = *timings.lock().unwrap();
+    (output, timings)
+}
+
+/// Times `future` into the slot of the current request, if any.
+fn timed<F: Future + Send + 'static>(
+    future: F,
+    record: fn(&mut ConnectTimings, Duration),
+) -> BoxFuture<F::Output> {
+    let Ok(timings) = CONNECT_TIMINGS.try_with(Arc::clone) else {
+        return Box::pin(future);
+    };
+    Box::pin(async move {
+        let start = Instant::now();
+        let output = future.await;
+        record(&mut timings.lock().unwrap(), start.elapsed());
+        output
+    })
+}
+
+/// The HTTP connector, with name resolution and connecting timed.
+pub(crate) fn http_connector(enforce_http: bool) -> TimedConnector<HttpConnector<TimedResolver>> {
+    let mut http = HttpConnector::new_with_resolver(TimedResolver(GaiResolver::new()));
+    http.enforce_http(enforce_http);
+    TimedConnector {
+        inner: http,
+        record: |timings, elapsed| timings.tcp = Some(elapsed),
+    }
+}
+
+/// Wraps the connector which establishes TLS over the HTTP connector, so
+/// the handshake is timed too.
+#[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+pub(crate) fn tls_connector<C>(inner: C) -> TimedConnector<C> {
+    TimedConnector {
+        inner,
+        record: |timings, elapsed| timings.total = Some(elapsed),
+    }
+}
+
+/// The system resolver, timed.
+#[derive(Clone)]
+pub struct TimedResolver(GaiResolver);
+
+impl Service<Name> for TimedResolver {
+    type Response = GaiAddrs;
+    type Error = io::Error;
+    type Future = BoxFuture<io::Result<GaiAddrs>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        timed(self.0.call(name), |timings, elapsed| {
+            timings.dns = Some(elapsed)
+        })
+    }
+}
+
+/// A connector, timed.
+#[derive(Clone)]
+pub struct TimedConnector<C> {
+    inner: C,
+    record: fn(&mut ConnectTimings, Duration),
+}
+
+impl<C> Service<Uri> for TimedConnector<C>
+where
+    C: Service<Uri>,
+    C::Future: Send + 'static,
+{
+    type Response = C::Response;
+    type Error = C::Error;
+    type Future = BoxFuture<Result<C::Response, C::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), C::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        timed(self.inner.call(uri), self.record)
+    }
+}
+
+#[cfg(all(test, feature = "wiremock", feature = "api-application"))]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::api::{Svix, SvixOptions};
+
+    #[tokio::test]
+    async fn test_request_timings() {
+        let server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/api/v1/app/app_1"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let timings = Arc::new(Mutex::new(Vec::new()));
+        let svix = Svix::new(
+            "testsk_timings".to_owned(),
+            Some(SvixOptions {
+                debug: true,
+                server_url: Some(server.uri()),
+                on_request_end: Some(Arc::new({
+                    let timings = timings.clone();
+                    move |end| timings.lock().unwrap().push(end.timings.unwrap())
+                })),
+                ..Default::default()
+            }),
+        );
+        for _ in 0..2 {
+            svix.application().delete("app_1".to_owned()).await.unwrap();
+        }
+
+        let timings = timings.lock().unwrap();
+        // The server's URL has an IP address, so there's nothing to resolve,
+        // and the second request reuses the first one's connection
+        let [first, second] = &timings[..] else {
+            panic!("unexpected timings: {timings:?}");
+        };
+        assert_eq!(first.dns, None);
+        assert!(first.connect.is_some());
+        assert_eq!(first.tls, None);
+        assert_eq!(second.connect, None);
+    }
+}
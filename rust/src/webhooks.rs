@@ -1,7 +1,10 @@
 // SPDX-FileCopyrightText: © 2022 Svix Authors
 // SPDX-License-Identifier: MIT
 
-use time::OffsetDateTime;
+use std::{fmt, pin::pin, sync::Arc};
+
+use ed25519_compact::{PublicKey, SecretKey};
+use futures_util::{Stream, StreamExt as _};
 
 #[derive(thiserror::Error, Debug)]
 pub enum WebhookError {
@@ -11,6 +14,26 @@ pub enum WebhookError {
     #[error("invalid secret")]
     InvalidSecret(#[from] base64::DecodeError),
 
+    #[error("invalid secret prefix `{0}_`, expected `whsec_`, `whpk_`, `whsk_` or no prefix")]
+    InvalidSecretPrefix(String),
+
+    #[error("invalid secret, the base64 padding is incorrect")]
+    InvalidSecretPadding,
+
+    #[error("invalid secret length {0}, expected between 24 and 75 bytes")]
+    InvalidSecretLength(usize),
+
+    #[error(
+        "invalid key length {0}, expected 32 bytes for a public key or 32/64 for a secret key"
+    )]
+    InvalidKeyLength(usize),
+
+    #[error("signing requires a secret key")]
+    MissingSigningKey,
+
+    #[error("the secret uses signature version `{0}`, which is not accepted")]
+    UnacceptedSignatureVersion(SignatureVersion),
+
     #[error("invalid header {0}")]
     InvalidHeader(&'static str),
 
@@ -30,33 +53,310 @@ pub enum WebhookError {
     InvalidPayload,
 }
 
+/// A signature scheme used in the `svix-signature` header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SignatureVersion {
+    /// HMAC-SHA256 with the endpoint's shared secret.
+    V1,
+    /// Ed25519 with the endpoint's key pair.
+    V1a,
+}
+
+impl SignatureVersion {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::V1 => "v1",
+            Self::V1a => "v1a",
+        }
+    }
+}
+
+impl fmt::Display for SignatureVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Metadata of a webhook whose signature was successfully verified.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifiedWebhook {
+    /// The message id, useful for idempotent processing.
+    pub msg_id: String,
+    /// The time the webhook was signed at, in seconds since the Unix epoch.
+    pub timestamp: i64,
+    /// The version of the signature that matched.
+    pub matched_version: SignatureVersion,
+}
+
+#[derive(Clone)]
 pub struct Webhook {
-    key: Vec<u8>,
+    key: Key,
+    /// Where the current time is read from, the system clock if `None`.
+    clock: Option<Arc<dyn Clock>>,
+}
+
+/// A source of the current time, used to check signature timestamps against
+/// the tolerance.
+///
+/// Implemented for closures returning seconds since the Unix epoch, so tests
+/// can verify webhooks at a frozen time with e.g. `|| 1_700_000_000`.
+pub trait Clock: Send + Sync {
+    /// Returns the current time in seconds since the Unix epoch.
+    fn now(&self) -> i64;
+}
+
+impl<F: Fn() -> i64 + Send + Sync> Clock for F {
+    fn now(&self) -> i64 {
+        self()
+    }
+}
+
+#[derive(Clone)]
+enum Key {
+    /// The HMAC state after absorbing the secret, so the key is only derived
+    /// once rather than on every signature.
+    Symmetric(hmac_sha256::HMAC),
+    PublicKey(PublicKey),
+    SecretKey(SecretKey),
+}
+
+impl Key {
+    fn version(&self) -> SignatureVersion {
+        match self {
+            Self::Symmetric(_) => SignatureVersion::V1,
+            Self::PublicKey(_) | Self::SecretKey(_) => SignatureVersion::V1a,
+        }
+    }
+}
+
+/// Builder for a [`Webhook`] with non-default settings.
+pub struct WebhookBuilder<'a> {
+    secret: &'a str,
+    accepted_versions: Option<Vec<SignatureVersion>>,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl WebhookBuilder<'_> {
+    /// Restricts the signature schemes this receiver accepts.
+    ///
+    /// Building fails with [`WebhookError::UnacceptedSignatureVersion`] if the
+    /// secret belongs to a scheme that isn't listed, e.g. a `whsec_` secret
+    /// when only [`SignatureVersion::V1a`] is accepted.
+    pub fn accepted_versions(
+        mut self,
+        versions: impl IntoIterator<Item = SignatureVersion>,
+    ) -> Self {
+        self.accepted_versions = Some(versions.into_iter().collect());
+        self
+    }
+
+    /// Reads the current time from `clock` instead of the system clock.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    pub fn build(self) -> Result<Webhook, WebhookError> {
+        let mut webhook = Webhook::new(self.secret)?;
+        webhook.clock = self.clock;
+
+        let version = webhook.key.version();
+        if let Some(accepted_versions) = &self.accepted_versions {
+            if !accepted_versions.contains(&version) {
+                return Err(WebhookError::UnacceptedSignatureVersion(version));
+            }
+        }
+
+        Ok(webhook)
+    }
 }
 
 const PREFIX: &str = "whsec_";
-const SVIX_MSG_ID_KEY: &str = "svix-id";
-const SVIX_MSG_SIGNATURE_KEY: &str = "svix-signature";
-const SVIX_MSG_TIMESTAMP_KEY: &str = "svix-timestamp";
-const UNBRANDED_MSG_ID_KEY: &str = "webhook-id";
-const UNBRANDED_MSG_SIGNATURE_KEY: &str = "webhook-signature";
-const UNBRANDED_MSG_TIMESTAMP_KEY: &str = "webhook-timestamp";
-const TOLERANCE_IN_SECONDS: i64 = 5 * 60;
-const SIGNATURE_VERSION: &str = "v1";
+const PUBLIC_KEY_PREFIX: &str = "whpk_";
+const SECRET_KEY_PREFIX: &str = "whsk_";
+pub(crate) const SVIX_MSG_ID_KEY: &str = "svix-id";
+pub(crate) const SVIX_MSG_SIGNATURE_KEY: &str = "svix-signature";
+pub(crate) const SVIX_MSG_TIMESTAMP_KEY: &str = "svix-timestamp";
+pub(crate) const UNBRANDED_MSG_ID_KEY: &str = "webhook-id";
+pub(crate) const UNBRANDED_MSG_SIGNATURE_KEY: &str = "webhook-signature";
+pub(crate) const UNBRANDED_MSG_TIMESTAMP_KEY: &str = "webhook-timestamp";
+pub(crate) const TOLERANCE_IN_SECONDS: i64 = 5 * 60;
+// The lengths of secrets generated by Svix. Others are accepted too, but a
+// secret which fails to decode and is far off is reported as such.
+const MIN_SECRET_LENGTH: usize = 24;
+const MAX_SECRET_LENGTH: usize = 75;
 
 impl Webhook {
+    /// Creates a new `Webhook` from a base64 encoded secret.
+    ///
+    /// Symmetric secrets may optionally be prefixed with `whsec_` and can use
+    /// either the standard or the URL-safe base64 alphabet. Ed25519 keys are
+    /// accepted with the `whpk_` (public key, verification only) and `whsk_`
+    /// (secret key or seed) prefixes.
     pub fn new(secret: &str) -> Result<Self, WebhookError> {
-        let secret = secret.strip_prefix(PREFIX).unwrap_or(secret);
-        let key = base64::decode(secret)?;
+        let key = if let Some(encoded) = secret.strip_prefix(PUBLIC_KEY_PREFIX) {
+            let bytes = base64::decode(encoded)?;
+            let key = PublicKey::from_slice(&bytes)
+                .map_err(|_| WebhookError::InvalidKeyLength(bytes.len()))?;
+            Key::PublicKey(key)
+        } else if let Some(encoded) = secret.strip_prefix(SECRET_KEY_PREFIX) {
+            let bytes = base64::decode(encoded)?;
+            let key = match bytes.len() {
+                SecretKey::BYTES => SecretKey::from_slice(&bytes).ok(),
+                ed25519_compact::Seed::BYTES => ed25519_compact::Seed::from_slice(&bytes)
+                    .ok()
+                    .map(|seed| ed25519_compact::KeyPair::from_seed(seed).sk),
+                _ => None,
+            };
+            Key::SecretKey(key.ok_or(WebhookError::InvalidKeyLength(bytes.len()))?)
+        } else {
+            Key::Symmetric(hmac_sha256::HMAC::new(Self::decode_secret(secret)?))
+        };
+
+        Ok(Webhook { key, clock: None })
+    }
 
-        Ok(Webhook { key })
+    /// Returns a builder for configuring a `Webhook` beyond its secret.
+    pub fn builder(secret: &str) -> WebhookBuilder<'_> {
+        WebhookBuilder {
+            secret,
+            accepted_versions: None,
+            clock: None,
+        }
     }
 
     pub fn from_bytes(secret: Vec<u8>) -> Result<Self, WebhookError> {
-        Ok(Webhook { key: secret })
+        Ok(Webhook {
+            key: Key::Symmetric(hmac_sha256::HMAC::new(secret)),
+            clock: None,
+        })
     }
 
-    pub fn verify<HM: HeaderMap>(&self, payload: &[u8], headers: &HM) -> Result<(), WebhookError> {
+    fn decode_secret(secret: &str) -> Result<Vec<u8>, WebhookError> {
+        let encoded = secret.strip_prefix(PREFIX).unwrap_or(secret);
+        let config = if encoded.contains(['-', '_']) {
+            base64::URL_SAFE
+        } else {
+            base64::STANDARD
+        };
+
+        let key = base64::decode_config(encoded, config).map_err(|e| {
+            // Base64url secrets can contain underscores too, so a prefix is only
+            // reported once the secret has failed to decode.
+            match encoded.split_once('_') {
+                Some((prefix, _))
+                    if encoded == secret
+                        && !prefix.is_empty()
+                        && prefix.bytes().all(|b| b.is_ascii_lowercase()) =>
+                {
+                    return WebhookError::InvalidSecretPrefix(prefix.to_owned());
+                }
+                _ => {}
+            }
+
+            let decoded_len = encoded.trim_end_matches('=').len() * 3 / 4;
+            if !(MIN_SECRET_LENGTH..=MAX_SECRET_LENGTH).contains(&decoded_len) {
+                return WebhookError::InvalidSecretLength(decoded_len);
+            }
+
+            match e {
+                base64::DecodeError::InvalidLength | base64::DecodeError::InvalidByte(_, b'=') => {
+                    WebhookError::InvalidSecretPadding
+                }
+                e => WebhookError::InvalidSecret(e),
+            }
+        })?;
+
+        Ok(key)
+    }
+
+    pub fn verify<HM: HeaderMap>(
+        &self,
+        payload: &[u8],
+        headers: &HM,
+    ) -> Result<VerifiedWebhook, WebhookError> {
+        self.verify_inner(payload, headers, true)
+    }
+
+    /// Verifies the webhook signature without enforcing the timestamp
+    /// tolerance.
+    ///
+    /// This is meant for replay and debugging tools that intentionally
+    /// re-deliver old payloads. Receivers of live webhooks should always use
+    /// [`Webhook::verify`], as the timestamp check is what protects them
+    /// against replay attacks.
+    pub fn verify_ignoring_timestamp<HM: HeaderMap>(
+        &self,
+        payload: &[u8],
+        headers: &HM,
+    ) -> Result<VerifiedWebhook, WebhookError> {
+        self.verify_inner(payload, headers, false)
+    }
+
+    /// Verifies a webhook whose payload arrives as a stream of chunks.
+    ///
+    /// The signature is computed incrementally while the stream is consumed,
+    /// so large payloads don't need to be buffered just for verification.
+    /// Like with [`Webhook::verify`], payloads which aren't UTF-8 are
+    /// rejected.
+    pub async fn verify_stream<S, B, HM>(
+        &self,
+        payload: S,
+        headers: &HM,
+    ) -> Result<VerifiedWebhook, WebhookError>
+    where
+        S: Stream<Item = B>,
+        B: AsRef<[u8]>,
+        HM: HeaderMap,
+    {
+        let (msg_id, msg_signature, msg_ts) = Self::get_signature_headers(headers)?;
+        self.verify_timestamp(msg_ts)?;
+
+        let mut verifier = self.verifier(msg_id, msg_ts, msg_signature);
+        // Payloads must be UTF-8, like for `verify`
+        let mut utf8 = Utf8Validator::default();
+        let mut payload = pin!(payload);
+        while let Some(chunk) = payload.next().await {
+            utf8.update(chunk.as_ref())?;
+            verifier.update(chunk.as_ref());
+        }
+        utf8.finish()?;
+
+        Ok(VerifiedWebhook {
+            msg_id: msg_id.to_owned(),
+            timestamp: msg_ts,
+            matched_version: verifier.finalize()?,
+        })
+    }
+
+    fn verify_inner<HM: HeaderMap>(
+        &self,
+        payload: &[u8],
+        headers: &HM,
+        enforce_tolerance: bool,
+    ) -> Result<VerifiedWebhook, WebhookError> {
+        let (msg_id, msg_signature, msg_ts) = Self::get_signature_headers(headers)?;
+
+        if enforce_tolerance {
+            self.verify_timestamp(msg_ts)?;
+        }
+
+        std::str::from_utf8(payload).map_err(|_| WebhookError::InvalidPayload)?;
+        let mut verifier = self.verifier(msg_id, msg_ts, msg_signature);
+        verifier.update(payload);
+
+        Ok(VerifiedWebhook {
+            msg_id: msg_id.to_owned(),
+            timestamp: msg_ts,
+            matched_version: verifier.finalize()?,
+        })
+    }
+
+    /// Returns the message id, the signature and the timestamp headers.
+    fn get_signature_headers<HM: HeaderMap>(
+        headers: &HM,
+    ) -> Result<(&str, &str, i64), WebhookError> {
         let msg_id = Self::get_header(headers, SVIX_MSG_ID_KEY, UNBRANDED_MSG_ID_KEY, "id")?;
         let msg_signature = Self::get_header(
             headers,
@@ -72,29 +372,31 @@ impl Webhook {
         )
         .and_then(Self::parse_timestamp)?;
 
-        Self::verify_timestamp(msg_ts)?;
-
-        let versioned_signature = self.sign(msg_id, msg_ts, payload)?;
-        let expected_signature = versioned_signature
-            .split_once(',')
-            .map(|x| x.1)
-            .ok_or(WebhookError::InvalidSignature)?;
-
-        msg_signature
-            .split(' ')
-            .filter_map(|x| x.split_once(','))
-            .filter(|x| x.0 == SIGNATURE_VERSION)
-            .any(|x| {
-                (x.1.len() == expected_signature.len())
-                    && (x
-                        .1
-                        .bytes()
-                        .zip(expected_signature.bytes())
-                        .fold(0, |acc, (a, b)| acc | (a ^ b))
-                        == 0)
-            })
-            .then_some(())
-            .ok_or(WebhookError::InvalidSignature)
+        Ok((msg_id, msg_signature, msg_ts))
+    }
+
+    /// Sets up the verification of every signature in `msg_signature` that
+    /// this key is able to check. Entries with unknown or unsupported versions
+    /// are skipped.
+    fn verifier<'a>(&self, msg_id: &str, msg_ts: i64, msg_signature: &'a str) -> Verifier<'a> {
+        let signatures = msg_signature
+            .split_whitespace()
+            .filter_map(|x| x.split_once(','));
+
+        let mut verifier = match &self.key {
+            Key::Symmetric(key) => Verifier::V1 {
+                mac: key.clone(),
+                signatures: signatures
+                    .filter(|x| x.0 == SignatureVersion::V1.as_str())
+                    .map(|x| x.1)
+                    .collect(),
+            },
+            Key::PublicKey(key) => Verifier::v1a(key, signatures),
+            Key::SecretKey(key) => Verifier::v1a(&key.public_key(), signatures),
+        };
+
+        verifier.update(format!("{msg_id}.{msg_ts}.").as_bytes());
+        verifier
     }
 
     pub fn sign(
@@ -105,10 +407,76 @@ impl Webhook {
     ) -> Result<String, WebhookError> {
         let payload = std::str::from_utf8(payload).map_err(|_| WebhookError::InvalidPayload)?;
         let to_sign = format!("{msg_id}.{timestamp}.{payload}",);
-        let signed = hmac_sha256::HMAC::mac(to_sign.as_bytes(), &self.key);
-        let encoded = base64::encode(signed);
 
-        Ok(format!("{SIGNATURE_VERSION},{encoded}"))
+        match &self.key {
+            Key::Symmetric(key) => {
+                let mut mac = key.clone();
+                mac.update(to_sign);
+                let encoded = base64::encode(mac.finalize());
+                Ok(format!("{},{encoded}", SignatureVersion::V1))
+            }
+            Key::SecretKey(key) => {
+                let encoded = base64::encode(key.sign(to_sign, None));
+                Ok(format!("{},{encoded}", SignatureVersion::V1a))
+            }
+            Key::PublicKey(_) => Err(WebhookError::MissingSigningKey),
+        }
+    }
+
+    /// Signs `payload` and returns the matching `svix-*` headers.
+    pub fn sign_headers(
+        &self,
+        msg_id: &str,
+        timestamp: i64,
+        payload: &[u8],
+    ) -> Result<http1::HeaderMap, WebhookError> {
+        self.signed_headers(
+            [
+                SVIX_MSG_ID_KEY,
+                SVIX_MSG_SIGNATURE_KEY,
+                SVIX_MSG_TIMESTAMP_KEY,
+            ],
+            msg_id,
+            timestamp,
+            payload,
+        )
+    }
+
+    /// Signs the body of `request` and attaches the `svix-*` headers to it.
+    pub fn sign_request<B: AsRef<[u8]>>(
+        &self,
+        msg_id: &str,
+        timestamp: i64,
+        request: &mut http1::Request<B>,
+    ) -> Result<(), WebhookError> {
+        let headers = self.sign_headers(msg_id, timestamp, request.body().as_ref())?;
+        request.headers_mut().extend(headers);
+        Ok(())
+    }
+
+    pub(crate) fn signed_headers(
+        &self,
+        [id_key, signature_key, timestamp_key]: [&'static str; 3],
+        msg_id: &str,
+        timestamp: i64,
+        payload: &[u8],
+    ) -> Result<http1::HeaderMap, WebhookError> {
+        use http1::HeaderValue;
+
+        let signature = self.sign(msg_id, timestamp, payload)?;
+
+        let mut headers = http1::HeaderMap::new();
+        headers.insert(
+            id_key,
+            HeaderValue::from_str(msg_id).map_err(|_| WebhookError::InvalidHeader("id"))?,
+        );
+        headers.insert(
+            signature_key,
+            HeaderValue::from_str(&signature)
+                .map_err(|_| WebhookError::InvalidHeader("signature"))?,
+        );
+        headers.insert(timestamp_key, HeaderValue::from(timestamp));
+        Ok(headers)
     }
 
     fn get_header<'a, HM: HeaderMap>(
@@ -131,8 +499,11 @@ impl Webhook {
         str::parse::<i64>(hdr).map_err(|_| WebhookError::InvalidTimestamp)
     }
 
-    fn verify_timestamp(ts: i64) -> Result<(), WebhookError> {
-        let now = OffsetDateTime::now_utc().unix_timestamp();
+    fn verify_timestamp(&self, ts: i64) -> Result<(), WebhookError> {
+        let now = match &self.clock {
+            Some(clock) => clock.now(),
+            None => unix_timestamp_now(),
+        };
         if now - ts > TOLERANCE_IN_SECONDS {
             Err(WebhookError::TimestampTooOldError)
         } else if ts > now + TOLERANCE_IN_SECONDS {
@@ -143,6 +514,149 @@ impl Webhook {
     }
 }
 
+/// Returns the current time in seconds since the Unix epoch.
+///
+/// `std::time::SystemTime::now` panics on `wasm32-unknown-unknown`, which is
+/// what edge runtimes like Cloudflare Workers run on. `web-time` reads the
+/// clock from JavaScript's `Date.now()` there and uses `std` everywhere else.
+pub(crate) fn unix_timestamp_now() -> i64 {
+    match web_time::SystemTime::now().duration_since(web_time::UNIX_EPOCH) {
+        Ok(since_epoch) => since_epoch.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    }
+}
+
+/// Incremental verification of the signatures found in a `svix-signature`
+/// header.
+enum Verifier<'a> {
+    V1 {
+        mac: hmac_sha256::HMAC,
+        signatures: Vec<&'a str>,
+    },
+    V1a(Vec<ed25519_compact::VerifyingState>),
+}
+
+impl<'a> Verifier<'a> {
+    fn v1a(public_key: &PublicKey, signatures: impl Iterator<Item = (&'a str, &'a str)>) -> Self {
+        Self::V1a(
+            signatures
+                .filter(|x| x.0 == SignatureVersion::V1a.as_str())
+                .filter_map(|x| base64::decode(x.1).ok())
+                .filter_map(|x| ed25519_compact::Signature::from_slice(&x).ok())
+                .filter_map(|x| public_key.verify_incremental(&x).ok())
+                .collect(),
+        )
+    }
+
+    fn update(&mut self, chunk: &[u8]) {
+        match self {
+            Self::V1 { mac, .. } => mac.update(chunk),
+            Self::V1a(states) => {
+                for state in states {
+                    state.absorb(chunk);
+                }
+            }
+        }
+    }
+
+    fn finalize(self) -> Result<SignatureVersion, WebhookError> {
+        let matched = match self {
+            Self::V1 { mac, signatures } => {
+                let expected = base64::encode(mac.finalize());
+                signatures
+                    .into_iter()
+                    .any(|signature| {
+                        (signature.len() == expected.len())
+                            && (signature
+                                .bytes()
+                                .zip(expected.bytes())
+                                .fold(0, |acc, (a, b)| acc | (a ^ b))
+                                == 0)
+                    })
+                    .then_some(SignatureVersion::V1)
+            }
+            Self::V1a(states) => states
+                .iter()
+                .any(|state| state.verify().is_ok())
+                .then_some(SignatureVersion::V1a),
+        };
+
+        matched.ok_or(WebhookError::InvalidSignature)
+    }
+}
+
+/// Incremental UTF-8 validation of a payload arriving in chunks, which may
+/// split characters.
+#[derive(Default)]
+struct Utf8Validator {
+    /// The start of a character at the end of the previous chunk.
+    incomplete: Vec<u8>,
+}
+
+impl Utf8Validator {
+    fn update(&mut self, mut chunk: &[u8]) -> Result<(), WebhookError> {
+        while !self.incomplete.is_empty() {
+            let Some((&byte, rest)) = chunk.split_first() else {
+                return Ok(());
+            };
+            self.incomplete.push(byte);
+            chunk = rest;
+            match std::str::from_utf8(&self.incomplete) {
+                Ok(_) => self.incomplete.clear(),
+                Err(e) if e.error_len().is_none() => {}
+                Err(_) => return Err(WebhookError::InvalidPayload),
+            }
+        }
+
+        match std::str::from_utf8(chunk) {
+            Ok(_) => Ok(()),
+            Err(e) if e.error_len().is_none() => {
+                self.incomplete = chunk[e.valid_up_to()..].to_vec();
+                Ok(())
+            }
+            Err(_) => Err(WebhookError::InvalidPayload),
+        }
+    }
+
+    fn finish(self) -> Result<(), WebhookError> {
+        if self.incomplete.is_empty() {
+            Ok(())
+        } else {
+            Err(WebhookError::InvalidPayload)
+        }
+    }
+}
+
+/// Extension trait for attaching Svix signature headers while building an
+/// `http` request.
+pub trait RequestBuilderExt: private::RequestBuilderSealed + Sized {
+    /// Signs `body` with `webhook` and adds the `svix-*` headers.
+    ///
+    /// The same `body` must then be passed to `Builder::body`.
+    fn svix_signed(
+        self,
+        webhook: &Webhook,
+        msg_id: &str,
+        timestamp: i64,
+        body: &[u8],
+    ) -> Result<Self, WebhookError>;
+}
+
+impl RequestBuilderExt for http1::request::Builder {
+    fn svix_signed(
+        self,
+        webhook: &Webhook,
+        msg_id: &str,
+        timestamp: i64,
+        body: &[u8],
+    ) -> Result<Self, WebhookError> {
+        let headers = webhook.sign_headers(msg_id, timestamp, body)?;
+        Ok(headers
+            .iter()
+            .fold(self, |builder, (name, value)| builder.header(name, value)))
+    }
+}
+
 /// Trait to abstract over the `HeaderMap` types from both v0.2 and v1.0 of the
 /// `http` crate.
 pub trait HeaderMap: private::HeaderMapSealed {}
@@ -151,6 +665,10 @@ impl HeaderMap for http02::HeaderMap {}
 impl HeaderMap for http1::HeaderMap {}
 
 mod private {
+    pub trait RequestBuilderSealed {}
+
+    impl RequestBuilderSealed for http1::request::Builder {}
+
     pub trait HeaderMapSealed {
         type HeaderValue: HeaderValueSealed;
         fn _get(&self, name: &str) -> Option<&Self::HeaderValue>;
@@ -191,8 +709,9 @@ mod tests {
     use time::OffsetDateTime;
 
     use super::{
-        Webhook, SVIX_MSG_ID_KEY, SVIX_MSG_SIGNATURE_KEY, SVIX_MSG_TIMESTAMP_KEY,
-        UNBRANDED_MSG_ID_KEY, UNBRANDED_MSG_SIGNATURE_KEY, UNBRANDED_MSG_TIMESTAMP_KEY,
+        Key, SignatureVersion, Webhook, WebhookError, SVIX_MSG_ID_KEY, SVIX_MSG_SIGNATURE_KEY,
+        SVIX_MSG_TIMESTAMP_KEY, UNBRANDED_MSG_ID_KEY, UNBRANDED_MSG_SIGNATURE_KEY,
+        UNBRANDED_MSG_TIMESTAMP_KEY,
     };
 
     fn get_svix_headers(msg_id: &str, signature: &str) -> HeaderMap {
@@ -225,6 +744,55 @@ mod tests {
         headers
     }
 
+    fn symmetric_signature(secret: &str) -> String {
+        let wh = Webhook::new(secret).unwrap();
+        assert!(matches!(wh.key, Key::Symmetric(_)));
+        wh.sign("msg_27UH4WbU6Z5A5EzD8u03UvzRbpk", 1649367553, b"{}")
+            .unwrap()
+    }
+
+    #[test]
+    fn test_secret_formats() {
+        let expected = symmetric_signature("whsec_C2FVsBQIhrscChlQIMV+b5sSYspob7oD");
+        for secret in [
+            "C2FVsBQIhrscChlQIMV+b5sSYspob7oD",
+            "whsec_C2FVsBQIhrscChlQIMV-b5sSYspob7oD",
+            "C2FVsBQIhrscChlQIMV-b5sSYspob7oD",
+        ] {
+            assert_eq!(symmetric_signature(secret), expected);
+        }
+
+        assert!(matches!(
+            Webhook::new("sk_C2FVsBQIhrscChlQIMV+b5sSYspob7oD"),
+            Err(WebhookError::InvalidSecretPrefix(prefix)) if prefix == "sk"
+        ));
+        assert!(matches!(
+            Webhook::new("whpk_C2FVsBQIhrscChlQIMV+b5sSYspob7oD"),
+            Err(WebhookError::InvalidKeyLength(24))
+        ));
+        assert!(matches!(
+            Webhook::new("whsec_C2FVsBQIhrscChlQIMV+b5sSYspob7o=D"),
+            Err(WebhookError::InvalidSecretPadding)
+        ));
+        assert!(matches!(
+            Webhook::new("whsec_C2FVsBQIhrscChlQIMV+b5sSYspob7oDa"),
+            Err(WebhookError::InvalidSecretPadding)
+        ));
+        // Short custom secrets are accepted, the length is only reported for
+        // secrets which don't decode
+        assert!(Webhook::new("whsec_C2FVsBQIhrscChlQ").is_ok());
+        assert!(matches!(
+            Webhook::new("whsec_C2FVsBQIhrscChl"),
+            Err(WebhookError::InvalidSecretLength(11))
+        ));
+    }
+
+    #[test]
+    fn test_webhook_is_send_sync_clone() {
+        fn assert_send_sync_clone<T: Send + Sync + Clone>() {}
+        assert_send_sync_clone::<Webhook>();
+    }
+
     #[test]
     fn test_sign() {
         let wh = Webhook::new("whsec_C2FVsBQIhrscChlQIMV+b5sSYspob7oD").unwrap();
@@ -246,14 +814,16 @@ mod tests {
         let payload = br#"{"email":"test@example.com","username":"test_user"}"#;
         let wh = Webhook::new(&secret).unwrap();
 
-        let signature = wh
-            .sign(msg_id, OffsetDateTime::now_utc().unix_timestamp(), payload)
-            .unwrap();
+        let timestamp = OffsetDateTime::now_utc().unix_timestamp();
+        let signature = wh.sign(msg_id, timestamp, payload).unwrap();
         for headers in [
             get_svix_headers(msg_id, &signature),
             get_unbranded_headers(msg_id, &signature),
         ] {
-            wh.verify(payload, &headers).unwrap();
+            let verified = wh.verify(payload, &headers).unwrap();
+            assert_eq!(verified.msg_id, msg_id);
+            assert!((verified.timestamp - timestamp).abs() <= 1);
+            assert_eq!(verified.matched_version, SignatureVersion::V1);
         }
     }
 
@@ -335,6 +905,112 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_verify_with_clock() {
+        let secret = "whsec_C2FVsBQIhrscChlQIMV+b5sSYspob7oD";
+        let msg_id = "msg_27UH4WbU6Z5A5EzD8u03UvzRbpk";
+        let payload = br#"{"email":"test@example.com","username":"test_user"}"#;
+        let signed_at = 1649367553;
+
+        let signature = Webhook::new(secret)
+            .unwrap()
+            .sign(msg_id, signed_at, payload)
+            .unwrap();
+        let mut headers = get_svix_headers(msg_id, &signature);
+        headers.insert(
+            SVIX_MSG_TIMESTAMP_KEY,
+            signed_at.to_string().parse().unwrap(),
+        );
+
+        let at = |now: i64| Webhook::builder(secret).clock(move || now).build().unwrap();
+        at(signed_at).verify(payload, &headers).unwrap();
+        at(signed_at + super::TOLERANCE_IN_SECONDS)
+            .verify(payload, &headers)
+            .unwrap();
+        assert!(matches!(
+            at(signed_at + super::TOLERANCE_IN_SECONDS + 1).verify(payload, &headers),
+            Err(WebhookError::TimestampTooOldError)
+        ));
+        assert!(matches!(
+            at(signed_at - super::TOLERANCE_IN_SECONDS - 1).verify(payload, &headers),
+            Err(WebhookError::FutureTimestampError)
+        ));
+    }
+
+    #[test]
+    fn test_verify_ignoring_timestamp() {
+        let secret = "whsec_C2FVsBQIhrscChlQIMV+b5sSYspob7oD".to_owned();
+        let msg_id = "msg_27UH4WbU6Z5A5EzD8u03UvzRbpk";
+        let payload = br#"{"email":"test@example.com","username":"test_user"}"#;
+        let wh = Webhook::new(&secret).unwrap();
+
+        for ts in [
+            OffsetDateTime::now_utc().unix_timestamp() - (super::TOLERANCE_IN_SECONDS + 1),
+            OffsetDateTime::now_utc().unix_timestamp() + (super::TOLERANCE_IN_SECONDS + 1),
+        ] {
+            let signature = wh.sign(msg_id, ts, payload).unwrap();
+            let mut headers = get_svix_headers(msg_id, &signature);
+            headers.insert(SVIX_MSG_TIMESTAMP_KEY, ts.to_string().parse().unwrap());
+
+            assert!(wh.verify(payload, &headers).is_err());
+            wh.verify_ignoring_timestamp(payload, &headers).unwrap();
+
+            // The signature itself is still checked
+            let tampered = br#"{"email":"test@example.com","username":"other_user"}"#;
+            assert!(wh.verify_ignoring_timestamp(tampered, &headers).is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_stream() {
+        let secret = "whsec_C2FVsBQIhrscChlQIMV+b5sSYspob7oD".to_owned();
+        let msg_id = "msg_27UH4WbU6Z5A5EzD8u03UvzRbpk";
+        let payload = br#"{"email":"test@example.com","username":"test_user"}"#;
+        let wh = Webhook::new(&secret).unwrap();
+
+        let signature = wh
+            .sign(msg_id, OffsetDateTime::now_utc().unix_timestamp(), payload)
+            .unwrap();
+        let headers = get_svix_headers(msg_id, &signature);
+
+        let chunks = payload.chunks(7);
+        wh.verify_stream(futures_util::stream::iter(chunks), &headers)
+            .await
+            .unwrap();
+
+        let tampered = br#"{"email":"test@example.com","username":"other_user"}"#;
+        let chunks = tampered.chunks(7);
+        assert!(wh
+            .verify_stream(futures_util::stream::iter(chunks), &headers)
+            .await
+            .is_err());
+
+        // Like `verify`, only UTF-8 payloads are accepted, even with
+        // characters split across chunks
+        let timestamp = OffsetDateTime::now_utc().unix_timestamp();
+        for (payload, valid) in [
+            ("{\"name\":\"Zoë 🦀\"}".as_bytes(), true),
+            (b"{\"name\":\"\xff\"}", false),
+            (b"{\"name\":\"\xf0\x9f\xa6", false),
+        ] {
+            // Only UTF-8 payloads can be signed, the others are rejected
+            // before their signature is checked
+            let signature = wh.sign(msg_id, timestamp, payload).ok();
+            let headers = get_svix_headers(msg_id, signature.as_deref().unwrap_or("v1,invalid"));
+            assert_eq!(wh.verify(payload, &headers).is_ok(), valid);
+            for chunk_size in 1..payload.len() {
+                let chunks = payload.chunks(chunk_size);
+                let result = wh
+                    .verify_stream(futures_util::stream::iter(chunks), &headers)
+                    .await;
+                match result {
+                    Ok(_) => assert!(valid),
+                    Err(e) => assert!(!valid && matches!(e, WebhookError::InvalidPayload)),
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_verify_with_multiple_signatures() {
         let secret = "whsec_C2FVsBQIhrscChlQIMV+b5sSYspob7oD".to_owned();
@@ -378,6 +1054,114 @@ mod tests {
         assert!(wh.verify(payload, &headers).is_err());
     }
 
+    #[test]
+    fn test_verify_skips_unknown_versions() {
+        let secret = "whsec_C2FVsBQIhrscChlQIMV+b5sSYspob7oD".to_owned();
+        let msg_id = "msg_27UH4WbU6Z5A5EzD8u03UvzRbpk";
+        let payload = br#"{"email":"test@example.com","username":"test_user"}"#;
+        let wh = Webhook::new(&secret).unwrap();
+
+        let signature = wh
+            .sign(msg_id, OffsetDateTime::now_utc().unix_timestamp(), payload)
+            .unwrap();
+
+        let multi_sig = format!(
+            "v2,tFtCZ5RDCPxzWQRWXWPgrCgE2frDBe9gjpbWQxnVfsQ=  garbage v1a,Mm7xgUVICxZfQ3bgf0h0Dof65L/IFx+PnZvnDWPCX6Q= {signature}",
+        );
+        let headers = get_svix_headers(msg_id, &multi_sig);
+        let verified = wh.verify(payload, &headers).unwrap();
+        assert_eq!(verified.matched_version, SignatureVersion::V1);
+
+        // A v1 signature can't be accepted under another version
+        let (_, sig) = signature.split_once(',').unwrap();
+        let headers = get_svix_headers(msg_id, &format!("v1a,{sig}"));
+        assert!(wh.verify(payload, &headers).is_err());
+    }
+
+    #[test]
+    fn test_builder_accepted_versions() {
+        let symmetric = "whsec_C2FVsBQIhrscChlQIMV+b5sSYspob7oD";
+        let asymmetric = format!("whsk_{}", base64::encode([7u8; 32]));
+
+        Webhook::builder(symmetric).build().unwrap();
+        Webhook::builder(&asymmetric)
+            .accepted_versions([SignatureVersion::V1a])
+            .build()
+            .unwrap();
+        assert!(matches!(
+            Webhook::builder(symmetric)
+                .accepted_versions([SignatureVersion::V1a])
+                .build(),
+            Err(WebhookError::UnacceptedSignatureVersion(
+                SignatureVersion::V1
+            ))
+        ));
+        assert!(matches!(
+            Webhook::builder(&asymmetric).accepted_versions([]).build(),
+            Err(WebhookError::UnacceptedSignatureVersion(
+                SignatureVersion::V1a
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_verify_asymmetric() {
+        let msg_id = "msg_27UH4WbU6Z5A5EzD8u03UvzRbpk";
+        let payload = br#"{"email":"test@example.com","username":"test_user"}"#;
+        let secret_key = format!("whsk_{}", base64::encode([7u8; 32]));
+        let signer = Webhook::new(&secret_key).unwrap();
+        let public_key = match &signer.key {
+            Key::SecretKey(key) => format!("whpk_{}", base64::encode(*key.public_key())),
+            _ => panic!("expected a secret key"),
+        };
+        let verifier = Webhook::new(&public_key).unwrap();
+
+        let signature = signer
+            .sign(msg_id, OffsetDateTime::now_utc().unix_timestamp(), payload)
+            .unwrap();
+        assert!(signature.starts_with("v1a,"));
+        assert!(matches!(
+            verifier.sign(msg_id, 0, payload),
+            Err(WebhookError::MissingSigningKey)
+        ));
+
+        let symmetric = Webhook::new("whsec_C2FVsBQIhrscChlQIMV+b5sSYspob7oD").unwrap();
+        let hmac_signature = symmetric
+            .sign(msg_id, OffsetDateTime::now_utc().unix_timestamp(), payload)
+            .unwrap();
+        let headers = get_svix_headers(msg_id, &format!("{hmac_signature} {signature}"));
+        for wh in [&signer, &verifier] {
+            let verified = wh.verify(payload, &headers).unwrap();
+            assert_eq!(verified.matched_version, SignatureVersion::V1a);
+        }
+        let verified = symmetric.verify(payload, &headers).unwrap();
+        assert_eq!(verified.matched_version, SignatureVersion::V1);
+
+        let tampered = br#"{"email":"test@example.com","username":"other_user"}"#;
+        assert!(verifier.verify(tampered, &headers).is_err());
+    }
+
+    #[test]
+    fn test_sign_request() {
+        use super::RequestBuilderExt as _;
+
+        let msg_id = "msg_27UH4WbU6Z5A5EzD8u03UvzRbpk";
+        let payload = br#"{"email":"test@example.com","username":"test_user"}"#;
+        let wh = Webhook::new("whsec_C2FVsBQIhrscChlQIMV+b5sSYspob7oD").unwrap();
+        let timestamp = OffsetDateTime::now_utc().unix_timestamp();
+
+        let request = http1::Request::post("https://example.com/webhook")
+            .svix_signed(&wh, msg_id, timestamp, payload)
+            .unwrap()
+            .body(payload.to_vec())
+            .unwrap();
+        wh.verify(request.body(), request.headers()).unwrap();
+
+        let mut request = http1::Request::new(payload.to_vec());
+        wh.sign_request(msg_id, timestamp, &mut request).unwrap();
+        wh.verify(request.body(), request.headers()).unwrap();
+    }
+
     #[test]
     fn test_missing_headers() {
         let secret = "whsec_C2FVsBQIhrscChlQIMV+b5sSYspob7oD".to_owned();
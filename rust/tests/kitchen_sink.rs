@@ -1,6 +1,10 @@
+#![cfg(feature = "api")]
+
 use std::collections::HashSet;
 use svix::{
-    api::{ApplicationIn, EndpointIn, EndpointPatch, EventTypeIn, Svix, SvixOptions},
+    api::{
+        ApplicationIn, EndpointIn, EndpointPatch, EventTypeIn, EventTypeName, Svix, SvixOptions,
+    },
     error::Error,
 };
 
@@ -52,7 +56,7 @@ async fn test_endpoint_crud() {
         .event_type()
         .create(
             EventTypeIn {
-                name: String::from("event.started"),
+                name: EventTypeName::new("event.started").unwrap(),
                 description: String::from("Something started"),
                 ..Default::default()
             },
@@ -67,7 +71,7 @@ async fn test_endpoint_crud() {
         .event_type()
         .create(
             EventTypeIn {
-                name: String::from("event.ended"),
+                name: EventTypeName::new("event.ended").unwrap(),
                 description: String::from("Something ended"),
                 ..Default::default()
             },